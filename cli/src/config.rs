@@ -0,0 +1,126 @@
+use crate::cli_args::CliArgs;
+use clap::error::ErrorKind;
+use clap::{Command, CommandFactory, FromArgMatches};
+use serde_json::{Map, Value};
+use std::ffi::OsString;
+use std::path::Path;
+
+/// Prefix of the environment variables, e.g. SKI_RPC_URL for --rpc-url
+pub const ENV_PREFIX: &str = "SKI_";
+
+/// Parses CliArgs from (in increasing order of precedence) the --config file, SKI_* environment variables and the command line.
+/// The profile (if any) is expanded
+pub fn parse_cli_args() -> CliArgs {
+    parse_cli_args_from(std::env::args_os())
+}
+
+pub fn parse_cli_args_from<I, T>(args: I) -> CliArgs
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    try_parse_cli_args_from(args).unwrap_or_else(|e| e.exit())
+}
+
+/// Like parse_cli_args, but returns errors instead of exiting, e.g. for reloading the config file while running
+pub fn try_parse_cli_args() -> Result<CliArgs, clap::Error> {
+    try_parse_cli_args_from(std::env::args_os())
+}
+
+pub fn try_parse_cli_args_from<I, T>(args: I) -> Result<CliArgs, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let matches = cli_command(&args)?.try_get_matches_from(args)?;
+    let mut cli_args = CliArgs::from_arg_matches(&matches)?;
+    cli_args.apply_profile();
+    Ok(cli_args)
+}
+
+/// The CliArgs command with an environment variable for every option and the config file values as defaults.
+/// Options are global, so they can be given before or after the subcommand
+pub fn cli_command(args: &[OsString]) -> Result<Command, clap::Error> {
+    let mut command = CliArgs::command();
+    let mut file_values = match config_path(args) {
+        Some(path) => load_config(&path).map_err(|e| command.error(ErrorKind::InvalidValue, e))?,
+        None => Map::new(),
+    };
+    let ids: Vec<String> = command.get_arguments().map(|a| a.get_id().to_string()).collect();
+    for id in ids {
+        let file_value = file_values.remove(&id);
+        command = command.mut_arg(&id, |arg| {
+            let arg = arg.env(format!("{ENV_PREFIX}{}", id.to_uppercase())).hide_env(true).global(true);
+            match file_value {
+                Some(Value::Array(values)) => arg.default_values(values.iter().map(value_to_string)),
+                Some(value) => arg.default_value(value_to_string(&value)),
+                None => arg,
+            }
+        });
+    }
+    if let Some(unknown) = file_values.keys().next() {
+        return Err(command.error(ErrorKind::UnknownArgument, format!("Unknown option '{unknown}' in config file")));
+    }
+    Ok(command)
+}
+
+/// The --config argument (or SKI_CONFIG), located before the rest of the arguments are parsed
+fn config_path(args: &[OsString]) -> Option<String> {
+    let mut args = args.iter().skip(1).map(|a| a.to_string_lossy().to_string());
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next();
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.to_string());
+        }
+    }
+    std::env::var(format!("{ENV_PREFIX}CONFIG")).ok()
+}
+
+/// Loads a TOML or YAML config file, keys may be camelCase (like the serialized CliArgs), snake_case or kebab-case
+fn load_config(path: &str) -> Result<Map<String, Value>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("Unable to read config file {path}: {e}"))?;
+    let value: Value = match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(&content).map_err(|e| format!("Invalid config file {path}: {e}"))?,
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&content).map_err(|e| format!("Invalid config file {path}: {e}"))?,
+        _ => return Err(format!("Unsupported config file {path}, expected .toml, .yaml or .yml")),
+    };
+    match value {
+        Value::Object(map) => Ok(map.into_iter().filter(|(_, v)| !v.is_null()).map(|(k, v)| (to_snake_case(&k), v)).collect()),
+        Value::Null => Ok(Map::new()),
+        _ => Err(format!("Invalid config file {path}, expected a map of options")),
+    }
+}
+
+fn to_snake_case(key: &str) -> String {
+    let mut snake = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(c.to_ascii_lowercase());
+        } else if c == '-' {
+            snake.push('_');
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+/// The effective configuration as TOML, with secrets redacted
+pub fn print_config(cli_args: &CliArgs) -> String {
+    let value = serde_json::to_value(cli_args.redacted()).expect("Unable to serialize config");
+    let value = match value {
+        Value::Object(map) => Value::Object(map.into_iter().filter(|(k, v)| !v.is_null() && k != "config").collect()),
+        v => v,
+    };
+    toml::to_string(&value).expect("Unable to serialize config")
+}
@@ -1 +1,3 @@
 pub mod cli_args;
+pub mod config;
+pub mod profiles;
@@ -0,0 +1,88 @@
+use crate::cli_args::{CliDisable, CliEnable, CliField, CliProfile};
+
+/// The enable, disable and exclude-fields values a profile expands to, disable and exclude-fields are composed of shared lists
+pub struct ProfileDefinition {
+    pub profile: CliProfile,
+    pub enable: &'static [CliEnable],
+    pub disable: &'static [&'static [CliDisable]],
+    pub exclude_fields: &'static [&'static [CliField]],
+}
+
+impl ProfileDefinition {
+    pub fn enable(&self) -> Vec<CliEnable> {
+        self.enable.to_vec()
+    }
+
+    pub fn disable(&self) -> Vec<CliDisable> {
+        self.disable.concat()
+    }
+
+    pub fn exclude_fields(&self) -> Vec<CliField> {
+        self.exclude_fields.concat()
+    }
+}
+
+/// Tables with relations which can be derived from other tables
+const DERIVED_TABLES: &[CliDisable] = &[CliDisable::BlockParentTable, CliDisable::BlocksTransactionsTable];
+
+/// Block header fields which are rarely queried
+const BLOCK_HEADER_FIELDS: &[CliField] = &[
+    CliField::BlockAcceptedIdMerkleRoot,
+    CliField::BlockMergeSetBluesHashes,
+    CliField::BlockMergeSetRedsHashes,
+    CliField::BlockSelectedParentHash,
+    CliField::BlockBits,
+    CliField::BlockBlueWork,
+    CliField::BlockDaaScore,
+    CliField::BlockHashMerkleRoot,
+    CliField::BlockNonce,
+    CliField::BlockPruningPoint,
+    CliField::BlockUtxoCommitment,
+    CliField::BlockVersion,
+];
+
+/// Transaction fields which are rarely queried
+const TRANSACTION_DETAIL_FIELDS: &[CliField] =
+    &[CliField::TxHash, CliField::TxMass, CliField::TxPayload, CliField::TxInSignatureScript, CliField::TxInSigOpCount];
+
+/// Fields which duplicate values of other tables
+const DENORMALIZED_FIELDS: &[CliField] = &[CliField::TxInBlockTime, CliField::TxOutScriptPublicKeyAddress, CliField::TxOutBlockTime];
+
+/// Profiles are applied on top of the defaults, explicitly supplied enable, disable or exclude-fields values are merged with
+/// those of the profile. A supplied 'none' drops the values of the profile
+pub const PROFILES: &[ProfileDefinition] = &[
+    ProfileDefinition {
+        profile: CliProfile::Explorer,
+        enable: &[CliEnable::TransactionsInputsResolve, CliEnable::MempoolProcessing, CliEnable::BlockRewardsTable],
+        disable: &[],
+        exclude_fields: &[],
+    },
+    ProfileDefinition {
+        profile: CliProfile::Exchange,
+        enable: &[CliEnable::TransactionsInputsResolve, CliEnable::MempoolProcessing],
+        disable: &[DERIVED_TABLES],
+        exclude_fields: &[BLOCK_HEADER_FIELDS, TRANSACTION_DETAIL_FIELDS],
+    },
+    ProfileDefinition {
+        profile: CliProfile::Minimal,
+        enable: &[],
+        disable: &[DERIVED_TABLES, &[CliDisable::AddressesTransactionsTable]],
+        exclude_fields: &[BLOCK_HEADER_FIELDS, TRANSACTION_DETAIL_FIELDS, DENORMALIZED_FIELDS],
+    },
+    ProfileDefinition {
+        profile: CliProfile::Archive,
+        enable: &[
+            CliEnable::TransactionsInputsResolve,
+            CliEnable::AddressBalanceChangesTable,
+            CliEnable::MempoolProcessing,
+            CliEnable::BlockRewardsTable,
+            CliEnable::Krc20OperationsTable,
+        ],
+        disable: &[],
+        exclude_fields: &[],
+    },
+];
+
+pub fn profile_definition(profile: &CliProfile) -> &'static ProfileDefinition {
+    PROFILES.iter().find(|p| &p.profile == profile).expect("Profile definition not found")
+}
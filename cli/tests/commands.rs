@@ -0,0 +1,18 @@
+use clap::error::ErrorKind;
+use clap::Parser;
+use simply_kaspa_cli::cli_args::{CliArgs, CliCommand};
+
+#[test]
+fn requires_confirmation_for_init() {
+    let error = CliArgs::try_parse_from(["simply-kaspa-indexer", "init"]).err().expect("init without --confirm was accepted");
+    assert_eq!(error.kind(), ErrorKind::MissingRequiredArgument);
+    assert!(error.to_string().contains("--confirm"));
+
+    let cli_args = CliArgs::try_parse_from(["simply-kaspa-indexer", "init", "--confirm"]).unwrap();
+    assert_eq!(cli_args.command(), CliCommand::Init { confirm: true });
+}
+
+#[test]
+fn runs_without_command() {
+    assert_eq!(CliArgs::parse_from(["simply-kaspa-indexer"]).command(), CliCommand::Run);
+}
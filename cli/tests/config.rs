@@ -0,0 +1,117 @@
+use clap::error::ErrorKind;
+use simply_kaspa_cli::cli_args::{CliArgs, CliCommand, CliDisable, CliField};
+use simply_kaspa_cli::config::{print_config, try_parse_cli_args_from};
+use std::path::PathBuf;
+
+/// Writes a config file unique to the test, removed when dropped
+struct ConfigFile(PathBuf);
+
+impl ConfigFile {
+    fn new(name: &str, content: &str) -> ConfigFile {
+        let path = std::env::temp_dir().join(format!("ski-{}-{name}", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        ConfigFile(path)
+    }
+
+    fn arg(&self) -> String {
+        format!("--config={}", self.0.display())
+    }
+}
+
+impl Drop for ConfigFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn parse(args: &[&str]) -> Result<CliArgs, clap::Error> {
+    try_parse_cli_args_from(["simply-kaspa-indexer"].iter().chain(args))
+}
+
+// The only test using SKI_* variables, as the environment is shared by the tests
+#[test]
+fn overrides_file_with_env_and_env_with_command_line() {
+    let file = ConfigFile::new("precedence.toml", "network = \"testnet-10\"\nbatchScale = 2.0\ncache_ttl = 30\n");
+    std::env::set_var("SKI_BATCH_SCALE", "3.0");
+    std::env::set_var("SKI_CACHE_TTL", "40");
+    let from_env = parse(&[&file.arg()]);
+    let from_cli = parse(&[&file.arg(), "--cache-ttl=45"]);
+    std::env::remove_var("SKI_BATCH_SCALE");
+    std::env::remove_var("SKI_CACHE_TTL");
+
+    let cli_args = from_env.unwrap();
+    assert_eq!(cli_args.network, "testnet-10");
+    assert_eq!(cli_args.batch_scale, 3.0);
+    assert_eq!(cli_args.cache_ttl, 40);
+    let cli_args = from_cli.unwrap();
+    assert_eq!(cli_args.batch_scale, 3.0);
+    assert_eq!(cli_args.cache_ttl, 45);
+}
+
+#[test]
+fn reads_lists_from_toml() {
+    let file = ConfigFile::new(
+        "lists.toml",
+        "rpcUrl = [\"ws://node-a:17210\", \"ws://node-b:17210\"]\n\
+        disable = [\"vcp_wait_for_sync\", \"block_parent_table\"]\n\
+        exclude_fields = [\"block_nonce\", \"tx_in_sig_op_count\"]\n",
+    );
+    let cli_args = parse(&[&file.arg()]).unwrap();
+    assert_eq!(cli_args.rpc_url.unwrap(), vec!["ws://node-a:17210", "ws://node-b:17210"]);
+    assert_eq!(cli_args.disable.unwrap(), vec![CliDisable::VcpWaitForSync, CliDisable::BlockParentTable]);
+    assert_eq!(cli_args.exclude_fields.unwrap(), vec![CliField::BlockNonce, CliField::TxInSigOpCount]);
+
+    // Values from the command line replace the list of the file
+    let cli_args = parse(&[&file.arg(), "--rpc-url=ws://node-c:17210"]).unwrap();
+    assert_eq!(cli_args.rpc_url.unwrap(), vec!["ws://node-c:17210"]);
+}
+
+#[test]
+fn reads_lists_and_flags_from_yaml() {
+    let file = ConfigFile::new(
+        "flags.yaml",
+        "rpc-url:\n  - ws://node-a:17210\n  - ws://node-b:17210\nlog-no-color: true\nupgradeDb: true\ndatabase_max_connections: 5\n",
+    );
+    let cli_args = parse(&[&file.arg()]).unwrap();
+    assert_eq!(cli_args.rpc_url.unwrap().len(), 2);
+    assert!(cli_args.log_no_color);
+    assert!(cli_args.upgrade_db);
+    assert_eq!(cli_args.database_max_connections, 5);
+
+    let file = ConfigFile::new("flags.toml", "log_no_color = false\n");
+    assert!(!parse(&[&file.arg()]).unwrap().log_no_color);
+}
+
+#[test]
+fn rejects_unknown_keys() {
+    let file = ConfigFile::new("unknown.toml", "network = \"mainnet\"\nrpc_urls = [\"ws://node-a:17110\"]\n");
+    let error = parse(&[&file.arg()]).err().expect("Unknown key was accepted");
+    assert_eq!(error.kind(), ErrorKind::UnknownArgument);
+    assert!(error.to_string().contains("rpc_urls"));
+}
+
+#[test]
+fn rejects_invalid_files() {
+    let file = ConfigFile::new("invalid.json", "{}");
+    assert_eq!(parse(&[&file.arg()]).err().unwrap().kind(), ErrorKind::InvalidValue);
+    let file = ConfigFile::new("invalid.toml", "network = ");
+    assert_eq!(parse(&[&file.arg()]).err().unwrap().kind(), ErrorKind::InvalidValue);
+    assert_eq!(parse(&["--config=/nonexistent/ski.toml"]).err().unwrap().kind(), ErrorKind::InvalidValue);
+}
+
+#[test]
+fn prints_config_with_command() {
+    let file = ConfigFile::new("print.toml", "database_url = \"postgres://indexer:secret@db/kaspa\"\n");
+    let cli_args = parse(&["config", &file.arg(), "--network=testnet-11"]).unwrap();
+    assert_eq!(cli_args.command(), CliCommand::Config);
+    let config = print_config(&cli_args);
+    assert!(config.contains("network = \"testnet-11\""));
+    assert!(config.contains("databaseUrl = \"postgres://**hidden**:**hidden**@db/kaspa\""));
+    assert!(!config.contains("secret"));
+    assert!(!config.contains("config ="));
+
+    // The printed config is a valid config file
+    let printed = ConfigFile::new("printed.toml", &config);
+    let reparsed = parse(&[&printed.arg()]).unwrap();
+    assert_eq!(reparsed.network, "testnet-11");
+}
@@ -0,0 +1,69 @@
+use clap::Parser;
+use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable, CliField};
+use simply_kaspa_cli::profiles::profile_definition;
+
+fn parse(args: &[&str]) -> CliArgs {
+    let mut cli_args = CliArgs::parse_from(["simply-kaspa-indexer"].iter().chain(args));
+    cli_args.apply_profile();
+    cli_args
+}
+
+#[test]
+fn merges_supplied_values_with_profile() {
+    let cli_args = parse(&["--profile=exchange", "--exclude-fields=tx_in_block_time", "--enable=block_rewards_table"]);
+    let definition = profile_definition(cli_args.profile.as_ref().unwrap());
+    for field in definition.exclude_fields() {
+        assert!(cli_args.is_excluded(field.clone()), "Profile field {:?} was not excluded", field);
+    }
+    assert!(cli_args.is_excluded(CliField::TxInBlockTime));
+    assert!(cli_args.is_enabled(CliEnable::MempoolProcessing));
+    assert!(cli_args.is_enabled(CliEnable::BlockRewardsTable));
+    assert!(cli_args.is_disabled(CliDisable::BlockParentTable));
+    assert!(!cli_args.is_disabled(CliDisable::AddressesTransactionsTable));
+}
+
+#[test]
+fn does_not_duplicate_profile_values() {
+    let cli_args = parse(&["--profile=minimal", "--disable=block_parent_table,addresses_transactions_table"]);
+    let disable = cli_args.disable.unwrap();
+    assert_eq!(disable.len(), profile_definition(cli_args.profile.as_ref().unwrap()).disable().len());
+}
+
+#[test]
+fn none_drops_profile_values() {
+    let cli_args = parse(&["--profile=minimal", "--disable=none"]);
+    assert!(!cli_args.is_disabled(CliDisable::BlockParentTable));
+    assert!(!cli_args.is_disabled(CliDisable::AddressesTransactionsTable));
+    assert!(cli_args.is_excluded(CliField::TxInBlockTime), "Exclude fields of the profile were dropped too");
+}
+
+#[test]
+fn shares_field_lists_between_profiles() {
+    let exchange = parse(&["--profile=exchange"]).exclude_fields.unwrap();
+    let minimal = parse(&["--profile=minimal"]).exclude_fields.unwrap();
+    assert!(exchange.iter().all(|f| minimal.contains(f)), "Minimal should exclude at least the fields excluded by exchange");
+    assert!(minimal.len() > exchange.len());
+}
+
+#[test]
+fn removes_single_profile_values() {
+    let cli_args = parse(&["--profile=minimal", "--no-disable=addresses_transactions_table", "--no-exclude-fields=tx_in_block_time"]);
+    assert!(!cli_args.is_disabled(CliDisable::AddressesTransactionsTable));
+    assert!(cli_args.is_disabled(CliDisable::BlockParentTable));
+    assert!(!cli_args.is_excluded(CliField::TxInBlockTime));
+    assert!(cli_args.is_excluded(CliField::TxOutBlockTime));
+
+    let cli_args = parse(&["--profile=archive", "--no-enable=krc20_operations_table,mempool_processing"]);
+    assert!(!cli_args.is_enabled(CliEnable::Krc20OperationsTable));
+    assert!(!cli_args.is_enabled(CliEnable::MempoolProcessing));
+    assert!(cli_args.is_enabled(CliEnable::AddressBalanceChangesTable));
+}
+
+#[test]
+fn removes_supplied_values_without_profile() {
+    let cli_args = parse(&["--enable=mempool_processing,block_rewards_table", "--no-enable=block_rewards_table"]);
+    assert!(cli_args.is_enabled(CliEnable::MempoolProcessing));
+    assert!(!cli_args.is_enabled(CliEnable::BlockRewardsTable));
+    // Removing values which are not present has no effect
+    assert_eq!(parse(&["--no-disable=blocks_table"]).disable, None);
+}
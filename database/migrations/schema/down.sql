@@ -1,3 +1,9 @@
+DROP TABLE IF EXISTS krc20_operations;
+DROP TABLE IF EXISTS block_rewards;
+DROP TABLE IF EXISTS mempool_outputs;
+DROP TABLE IF EXISTS mempool_inputs;
+DROP TABLE IF EXISTS mempool_transactions;
+DROP TABLE IF EXISTS address_balance_changes;
 DROP TABLE IF EXISTS scripts_transactions;
 DROP TABLE IF EXISTS addresses_transactions;
 DROP TABLE IF EXISTS transactions_inputs;
@@ -3,7 +3,7 @@ CREATE TABLE vars
     key   VARCHAR(255) PRIMARY KEY,
     value TEXT NOT NULL
 );
-INSERT INTO vars (key, value) VALUES ('schema_version', '9');
+INSERT INTO vars (key, value) VALUES ('schema_version', '13');
 
 
 CREATE TABLE blocks
@@ -121,3 +121,91 @@ CREATE TABLE scripts_transactions
     PRIMARY KEY (script_public_key, transaction_id)
 );
 CREATE INDEX ON scripts_transactions (script_public_key, block_time DESC);
+
+
+CREATE TABLE address_balance_changes
+(
+    address        VARCHAR,
+    transaction_id BYTEA,
+    block_hash     BYTEA,
+    amount         BIGINT,
+    blue_score     BIGINT,
+    daa_score      BIGINT,
+    block_time     BIGINT,
+    PRIMARY KEY (address, transaction_id)
+);
+CREATE INDEX ON address_balance_changes (address, daa_score DESC);
+CREATE INDEX ON address_balance_changes (address, block_time DESC);
+CREATE INDEX ON address_balance_changes (block_hash);
+
+
+CREATE TABLE mempool_transactions
+(
+    transaction_id BYTEA PRIMARY KEY,
+    subnetwork_id  INTEGER,
+    hash           BYTEA,
+    mass           INTEGER,
+    payload        BYTEA,
+    fee            BIGINT,
+    is_orphan      BOOLEAN,
+    first_seen     BIGINT
+);
+
+
+CREATE TABLE mempool_inputs
+(
+    transaction_id          BYTEA,
+    index                   SMALLINT,
+    previous_outpoint_hash  BYTEA,
+    previous_outpoint_index SMALLINT,
+    signature_script        BYTEA,
+    sig_op_count            SMALLINT,
+    PRIMARY KEY (transaction_id, index)
+);
+CREATE INDEX ON mempool_inputs (previous_outpoint_hash, previous_outpoint_index);
+
+
+CREATE TABLE mempool_outputs
+(
+    transaction_id            BYTEA,
+    index                     SMALLINT,
+    amount                    BIGINT,
+    script_public_key         BYTEA,
+    script_public_key_address VARCHAR,
+    PRIMARY KEY (transaction_id, index)
+);
+CREATE INDEX ON mempool_outputs (script_public_key_address);
+CREATE INDEX ON mempool_outputs (script_public_key);
+
+
+CREATE TABLE block_rewards
+(
+    block_hash              BYTEA PRIMARY KEY,
+    blue_score              BIGINT,
+    subsidy                 BIGINT,
+    coinbase_amount         BIGINT,
+    miner_script_public_key BYTEA,
+    miner_address           VARCHAR,
+    extra_data              BYTEA,
+    block_time              BIGINT
+);
+CREATE INDEX ON block_rewards (block_time DESC);
+CREATE INDEX ON block_rewards (miner_address, block_time DESC);
+
+
+CREATE TABLE krc20_operations
+(
+    transaction_id BYTEA,
+    index          SMALLINT,
+    operation      VARCHAR,
+    tick           VARCHAR,
+    amount         VARCHAR,
+    max_supply     VARCHAR,
+    mint_limit     VARCHAR,
+    decimals       SMALLINT,
+    to_address     VARCHAR,
+    content        TEXT,
+    block_time     BIGINT,
+    PRIMARY KEY (transaction_id, index)
+);
+CREATE INDEX ON krc20_operations (tick, block_time DESC);
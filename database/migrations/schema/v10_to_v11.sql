@@ -0,0 +1,45 @@
+--------------------------------------------------------------
+-- v11: Support mempool indexing
+--------------------------------------------------------------
+
+CREATE TABLE mempool_transactions
+(
+    transaction_id BYTEA PRIMARY KEY,
+    subnetwork_id  INTEGER,
+    hash           BYTEA,
+    mass           INTEGER,
+    payload        BYTEA,
+    fee            BIGINT,
+    is_orphan      BOOLEAN,
+    first_seen     BIGINT
+);
+
+
+CREATE TABLE mempool_inputs
+(
+    transaction_id          BYTEA,
+    index                   SMALLINT,
+    previous_outpoint_hash  BYTEA,
+    previous_outpoint_index SMALLINT,
+    signature_script        BYTEA,
+    sig_op_count            SMALLINT,
+    PRIMARY KEY (transaction_id, index)
+);
+CREATE INDEX ON mempool_inputs (previous_outpoint_hash, previous_outpoint_index);
+
+
+CREATE TABLE mempool_outputs
+(
+    transaction_id            BYTEA,
+    index                     SMALLINT,
+    amount                    BIGINT,
+    script_public_key         BYTEA,
+    script_public_key_address VARCHAR,
+    PRIMARY KEY (transaction_id, index)
+);
+CREATE INDEX ON mempool_outputs (script_public_key_address);
+CREATE INDEX ON mempool_outputs (script_public_key);
+
+
+-- Update schema_version
+UPDATE vars SET value = '11' WHERE key = 'schema_version';
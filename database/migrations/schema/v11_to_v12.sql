@@ -0,0 +1,21 @@
+--------------------------------------------------------------
+-- v12: Support decoded coinbase payloads / miner statistics
+--------------------------------------------------------------
+
+CREATE TABLE block_rewards
+(
+    block_hash              BYTEA PRIMARY KEY,
+    blue_score              BIGINT,
+    subsidy                 BIGINT,
+    coinbase_amount         BIGINT,
+    miner_script_public_key BYTEA,
+    miner_address           VARCHAR,
+    extra_data              BYTEA,
+    block_time              BIGINT
+);
+CREATE INDEX ON block_rewards (block_time DESC);
+CREATE INDEX ON block_rewards (miner_address, block_time DESC);
+
+
+-- Update schema_version
+UPDATE vars SET value = '12' WHERE key = 'schema_version';
@@ -0,0 +1,24 @@
+--------------------------------------------------------------
+-- v13: Support KRC-20 (kasplex) operations
+--------------------------------------------------------------
+
+CREATE TABLE krc20_operations
+(
+    transaction_id BYTEA,
+    index          SMALLINT,
+    operation      VARCHAR,
+    tick           VARCHAR,
+    amount         VARCHAR,
+    max_supply     VARCHAR,
+    mint_limit     VARCHAR,
+    decimals       SMALLINT,
+    to_address     VARCHAR,
+    content        TEXT,
+    block_time     BIGINT,
+    PRIMARY KEY (transaction_id, index)
+);
+CREATE INDEX ON krc20_operations (tick, block_time DESC);
+
+
+-- Update schema_version
+UPDATE vars SET value = '13' WHERE key = 'schema_version';
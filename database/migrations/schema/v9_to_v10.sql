@@ -0,0 +1,22 @@
+--------------------------------------------------------------
+-- v10: Support per address balance history
+--------------------------------------------------------------
+
+CREATE TABLE address_balance_changes
+(
+    address        VARCHAR,
+    transaction_id BYTEA,
+    block_hash     BYTEA,
+    amount         BIGINT,
+    blue_score     BIGINT,
+    daa_score      BIGINT,
+    block_time     BIGINT,
+    PRIMARY KEY (address, transaction_id)
+);
+CREATE INDEX ON address_balance_changes (address, daa_score DESC);
+CREATE INDEX ON address_balance_changes (address, block_time DESC);
+CREATE INDEX ON address_balance_changes (block_hash);
+
+
+-- Update schema_version
+UPDATE vars SET value = '10' WHERE key = 'schema_version';
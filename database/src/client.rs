@@ -1,17 +1,31 @@
-use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use log::{debug, info, trace, warn, LevelFilter};
-use regex::Regex;
-use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::PgPoolOptions;
 use sqlx::{ConnectOptions, Error, Pool, Postgres};
 
+use crate::connect::{display_connect_options, BulkMode, DbConnectOptions};
+use crate::layout::{AddressKey, ReadLayout};
 use crate::models::address_transaction::AddressTransaction;
 use crate::models::block::Block;
 use crate::models::block_parent::BlockParent;
+use crate::models::block_reward::BlockReward;
 use crate::models::block_transaction::BlockTransaction;
+use crate::models::krc20_operation::Krc20Operation;
+use crate::models::mempool_input::MempoolInput;
+use crate::models::mempool_output::MempoolOutput;
+use crate::models::mempool_transaction::MempoolTransaction;
 use crate::models::query::database_details::DatabaseDetails;
+use crate::models::query::krc20_operation_status::Krc20OperationStatus;
+use crate::models::query::miner_statistics::MinerStatistics;
+use crate::models::query::pool_status::PoolStatus;
 use crate::models::query::table_details::TableDetails;
+use crate::models::query::transaction_details::TransactionDetails;
+use crate::models::query::transaction_ref::TransactionRef;
+use crate::models::query::utxo::Utxo;
 use crate::models::script_transaction::ScriptTransaction;
 use crate::models::subnetwork::Subnetwork;
 use crate::models::transaction::Transaction;
@@ -24,26 +38,90 @@ use crate::query;
 #[derive(Clone)]
 pub struct KaspaDbClient {
     pool: Pool<Postgres>,
+    bulk_mode: Arc<BulkMode>,
+    /// Callers currently waiting for a connection
+    waiters: Arc<AtomicU32>,
 }
 
 impl KaspaDbClient {
-    const SCHEMA_VERSION: u8 = 9;
+    pub const SCHEMA_VERSION: u8 = 13;
+    /// Schema upgrade scripts, the first one upgrades v1 to v2
+    const MIGRATIONS: [&'static str; 12] = [
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v1_to_v2.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v2_to_v3.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v3_to_v4.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v4_to_v5.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v5_to_v6.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v6_to_v7.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v7_to_v8.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v8_to_v9.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v9_to_v10.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v10_to_v11.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v11_to_v12.sql")),
+        include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v12_to_v13.sql")),
+    ];
 
     pub async fn new(url: &str) -> Result<KaspaDbClient, Error> {
-        Self::new_with_args(url, 10).await
+        Self::new_with_options(url, &DbConnectOptions::default()).await
     }
 
     pub async fn new_with_args(url: &str, pool_size: u32) -> Result<KaspaDbClient, Error> {
-        let url_cleaned = Regex::new(r"(postgres://postgres:)[^@]+(@)").expect("Failed to parse url").replace(url, "$1$2");
-        debug!("Connecting to PostgreSQL {}", url_cleaned);
-        let connect_opts = PgConnectOptions::from_str(url)?.log_slow_statements(LevelFilter::Warn, Duration::from_secs(60));
+        Self::new_with_options(url, &DbConnectOptions { max_connections: pool_size, ..Default::default() }).await
+    }
+
+    pub async fn new_with_options(url: &str, options: &DbConnectOptions) -> Result<KaspaDbClient, Error> {
+        let connect_opts = options.pg_connect_options(url)?.log_slow_statements(LevelFilter::Warn, Duration::from_secs(60));
+        let target = display_connect_options(&connect_opts);
+        let bulk_mode = Arc::new(BulkMode::new(options.bulk_synchronous_commit.clone()));
+        let (connect_bulk_mode, acquire_bulk_mode) = (bulk_mode.clone(), bulk_mode.clone());
+        debug!("Connecting to PostgreSQL {}", target);
         let pool = PgPoolOptions::new()
-            .acquire_timeout(Duration::from_secs(10))
-            .max_connections(pool_size)
+            .min_connections(options.min_connections)
+            .max_connections(options.max_connections)
+            .acquire_timeout(options.acquire_timeout)
+            .idle_timeout(options.idle_timeout)
+            .after_connect(move |conn, _| {
+                let bulk_mode = connect_bulk_mode.clone();
+                Box::pin(async move { bulk_mode.after_connect(conn).await })
+            })
+            .before_acquire(move |_, metadata| {
+                let is_current = acquire_bulk_mode.is_current(&metadata);
+                Box::pin(async move { Ok(is_current) })
+            })
             .connect_with(connect_opts)
             .await?;
-        info!("Connected to PostgreSQL {}", url_cleaned);
-        Ok(KaspaDbClient { pool })
+        info!("Connected to PostgreSQL {}", target);
+        Ok(KaspaDbClient { pool, bulk_mode, waiters: Arc::new(AtomicU32::new(0)) })
+    }
+
+    /// Switches the connections to the bulk synchronous_commit (if configured), for phases where throughput matters more than
+    /// durability of the last commits, like the UTXO import and catching up. Connections of the previous mode are replaced
+    pub fn set_bulk_mode(&self, bulk: bool) {
+        if let Some(synchronous_commit) = self.bulk_mode.set(bulk) {
+            if bulk {
+                info!("Using synchronous_commit={synchronous_commit} for bulk processing");
+            } else {
+                info!("Bulk processing completed, restoring synchronous_commit");
+            }
+        }
+    }
+
+    pub fn pool_status(&self) -> PoolStatus {
+        PoolStatus {
+            size: self.pool.size(),
+            idle: self.pool.num_idle() as u32,
+            min_connections: self.pool.options().get_min_connections(),
+            max_connections: self.pool.options().get_max_connections(),
+            waiters: self.waiters.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Acquires a connection from the pool, counting the callers waiting for one
+    async fn acquire(&self) -> Result<PoolConnection<Postgres>, Error> {
+        self.waiters.fetch_add(1, Ordering::Relaxed);
+        // Decremented on drop, as the caller might be cancelled while waiting
+        let _waiting = WaiterGuard(&self.waiters);
+        self.pool.acquire().await
     }
 
     pub async fn close(&mut self) -> Result<(), Error> {
@@ -52,96 +130,17 @@ impl KaspaDbClient {
     }
 
     pub async fn create_schema(&self, upgrade_db: bool) -> Result<(), Error> {
-        match &self.select_var("schema_version").await {
-            Ok(v) => {
-                let mut version = v.parse::<u8>().expect("Expected valid schema version");
+        match self.schema_version().await {
+            Some(mut version) => {
                 if version < Self::SCHEMA_VERSION {
-                    if version == 1 {
-                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v1_to_v2.sql"));
-                        if upgrade_db {
-                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
-                            query::misc::execute_ddl(ddl, &self.pool).await?;
-                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
-                            version += 1;
-                        } else {
-                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
-                        }
-                    }
-                    if version == 2 {
-                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v2_to_v3.sql"));
-                        if upgrade_db {
-                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
-                            query::misc::execute_ddl(ddl, &self.pool).await?;
-                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
-                            version += 1;
-                        } else {
-                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
-                        }
-                    }
-                    if version == 3 {
-                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v3_to_v4.sql"));
-                        if upgrade_db {
-                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
-                            query::misc::execute_ddl(ddl, &self.pool).await?;
-                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
-                            version += 1;
-                        } else {
-                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
-                        }
-                    }
-                    if version == 4 {
-                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v4_to_v5.sql"));
-                        if upgrade_db {
-                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
-                            query::misc::execute_ddl(ddl, &self.pool).await?;
-                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
-                            version += 1;
-                        } else {
-                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
-                        }
-                    }
-                    if version == 5 {
-                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v5_to_v6.sql"));
+                    for (from_version, ddl) in Self::pending_migrations(version) {
                         if upgrade_db {
-                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
-                            query::misc::execute_ddl(ddl, &self.pool).await?;
+                            warn!("\n{ddl}\nUpgrading schema from v{from_version} to v{}. ^", from_version + 1);
+                            query::misc::execute_ddl(ddl, &mut *self.acquire().await?).await?;
                             info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
-                            version += 1;
+                            version = from_version + 1;
                         } else {
-                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
-                        }
-                    }
-                    if version == 6 {
-                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v6_to_v7.sql"));
-                        if upgrade_db {
-                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
-                            query::misc::execute_ddl(ddl, &self.pool).await?;
-                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
-                            version += 1;
-                        } else {
-                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
-                        }
-                    }
-                    if version == 7 {
-                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v7_to_v8.sql"));
-                        if upgrade_db {
-                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
-                            query::misc::execute_ddl(ddl, &self.pool).await?;
-                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
-                            version += 1;
-                        } else {
-                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
-                        }
-                    }
-                    if version == 8 {
-                        let ddl = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/v8_to_v9.sql"));
-                        if upgrade_db {
-                            warn!("\n{ddl}\nUpgrading schema from v{version} to v{}. ^", version + 1);
-                            query::misc::execute_ddl(ddl, &self.pool).await?;
-                            info!("\x1b[32mSchema upgrade completed successfully\x1b[0m");
-                            version += 1;
-                        } else {
-                            panic!("\n{ddl}\nFound outdated schema v{version}. Set flag '-u' to upgrade, or apply manually ^")
+                            panic!("\n{ddl}\nFound outdated schema v{from_version}. Run 'migrate' to upgrade, or apply manually ^")
                         }
                     }
                     trace!("Schema version is v{version}")
@@ -155,58 +154,139 @@ impl KaspaDbClient {
                 }
                 info!("Schema v{} is up to date", version)
             }
-            Err(_) => {
+            None => {
                 warn!("Applying schema v{}", Self::SCHEMA_VERSION);
-                query::misc::execute_ddl(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/up.sql")), &self.pool)
-                    .await?;
+                query::misc::execute_ddl(
+                    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/up.sql")),
+                    &mut *self.acquire().await?,
+                )
+                .await?;
                 info!("\x1b[32mSchema applied successfully\x1b[0m");
             }
         };
         Ok(())
     }
 
+    /// The version of the existing schema, None if the schema has not been created
+    pub async fn schema_version(&self) -> Option<u8> {
+        self.select_var("schema_version").await.ok().map(|v| v.parse::<u8>().expect("Expected valid schema version"))
+    }
+
+    /// The upgrade scripts (with the version they upgrade from) needed to bring a schema at version up to date
+    pub fn pending_migrations(version: u8) -> Vec<(u8, &'static str)> {
+        (version..Self::SCHEMA_VERSION).filter(|v| *v > 0).map(|v| (v, Self::MIGRATIONS[v as usize - 1])).collect()
+    }
+
     pub async fn drop_schema(&self) -> Result<(), Error> {
-        query::misc::execute_ddl(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/down.sql")), &self.pool).await
+        query::misc::execute_ddl(
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/schema/down.sql")),
+            &mut *self.acquire().await?,
+        )
+        .await
     }
 
     pub async fn select_database_details(&self) -> Result<DatabaseDetails, Error> {
-        query::select::select_database_details(&self.pool).await
+        query::select::select_database_details(&mut *self.acquire().await?).await
     }
 
     pub async fn select_all_table_details(&self) -> Result<Vec<TableDetails>, Error> {
-        query::select::select_all_table_details(&self.pool).await
+        query::select::select_all_table_details(&mut *self.acquire().await?).await
+    }
+
+    pub async fn select_index_definitions(&self, table_name: &str) -> Result<Vec<String>, Error> {
+        query::select::select_index_definitions(table_name, &mut *self.acquire().await?).await
     }
 
     pub async fn select_var(&self, key: &str) -> Result<String, Error> {
-        query::select::select_var(key, &self.pool).await
+        query::select::select_var(key, &mut *self.acquire().await?).await
     }
 
     pub async fn select_subnetworks(&self) -> Result<Vec<Subnetwork>, Error> {
-        query::select::select_subnetworks(&self.pool).await
+        query::select::select_subnetworks(&mut *self.acquire().await?).await
     }
 
     pub async fn select_tx_count(&self, block_hash: &Hash) -> Result<i64, Error> {
-        query::select::select_tx_count(block_hash, &self.pool).await
+        query::select::select_tx_count(block_hash, &mut *self.acquire().await?).await
     }
 
     pub async fn select_is_chain_block(&self, block_hash: &Hash) -> Result<bool, Error> {
-        query::select::select_is_chain_block(block_hash, &self.pool).await
+        query::select::select_is_chain_block(block_hash, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_address_balance_at_daa_score(&self, address: &str, daa_score: i64) -> Result<i64, Error> {
+        query::select::select_address_balance_at_daa_score(address, daa_score, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_address_balance_at_block_time(&self, address: &str, block_time: i64) -> Result<i64, Error> {
+        query::select::select_address_balance_at_block_time(address, block_time, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_miner_statistics(&self, from_block_time: i64, to_block_time: i64) -> Result<Vec<MinerStatistics>, Error> {
+        query::select::select_miner_statistics(from_block_time, to_block_time, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_krc20_operations_by_tick(
+        &self,
+        tick: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Krc20OperationStatus>, Error> {
+        query::select::select_krc20_operations_by_tick(tick, limit, offset, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_block(&self, layout: &ReadLayout, block_hash: &Hash) -> Result<Option<Block>, Error> {
+        query::read::select_block(layout, block_hash, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_transactions_by_block(&self, layout: &ReadLayout, block_hash: &Hash) -> Result<Vec<Transaction>, Error> {
+        query::read::select_transactions_by_block(layout, block_hash, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_transaction(&self, layout: &ReadLayout, transaction_id: &Hash) -> Result<Option<TransactionDetails>, Error> {
+        query::read::select_transaction(layout, transaction_id, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_address_transactions(
+        &self,
+        layout: &ReadLayout,
+        key: &AddressKey,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TransactionRef>, Error> {
+        query::read::select_address_transactions(layout, key, limit, offset, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_utxos(&self, layout: &ReadLayout, key: &AddressKey, accepted_only: bool) -> Result<Vec<Utxo>, Error> {
+        query::read::select_utxos(layout, key, accepted_only, &mut *self.acquire().await?).await
+    }
+
+    pub async fn select_chain_blocks(
+        &self,
+        layout: &ReadLayout,
+        from_blue_score: i64,
+        to_blue_score: i64,
+    ) -> Result<Vec<Block>, Error> {
+        query::read::select_chain_blocks(layout, from_blue_score, to_blue_score, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_subnetwork(&self, subnetwork_id: &String) -> Result<i32, Error> {
-        query::insert::insert_subnetwork(subnetwork_id, &self.pool).await
+        query::insert::insert_subnetwork(subnetwork_id, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_blocks(&self, blocks: &[Block]) -> Result<u64, Error> {
-        query::insert::insert_blocks(blocks, &self.pool).await
+        query::insert::insert_blocks(blocks, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_block_parents(&self, block_parents: &[BlockParent]) -> Result<u64, Error> {
-        query::insert::insert_block_parents(block_parents, &self.pool).await
+        query::insert::insert_block_parents(block_parents, &mut *self.acquire().await?).await
+    }
+
+    pub async fn insert_block_rewards(&self, block_rewards: &[BlockReward]) -> Result<u64, Error> {
+        query::insert::insert_block_rewards(block_rewards, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_transactions(&self, transactions: &[Transaction]) -> Result<u64, Error> {
-        query::insert::insert_transactions(transactions, &self.pool).await
+        query::insert::insert_transactions(transactions, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_transaction_inputs(
@@ -214,42 +294,90 @@ impl KaspaDbClient {
         resolve_previous_outpoints: bool,
         transaction_inputs: &[TransactionInput],
     ) -> Result<u64, Error> {
-        query::insert::insert_transaction_inputs(resolve_previous_outpoints, transaction_inputs, &self.pool).await
+        query::insert::insert_transaction_inputs(resolve_previous_outpoints, transaction_inputs, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_transaction_outputs(&self, transaction_outputs: &[TransactionOutput]) -> Result<u64, Error> {
-        query::insert::insert_transaction_outputs(transaction_outputs, &self.pool).await
+        query::insert::insert_transaction_outputs(transaction_outputs, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_address_transactions(&self, address_transactions: &[AddressTransaction]) -> Result<u64, Error> {
-        query::insert::insert_address_transactions(address_transactions, &self.pool).await
+        query::insert::insert_address_transactions(address_transactions, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_script_transactions(&self, script_transactions: &[ScriptTransaction]) -> Result<u64, Error> {
-        query::insert::insert_script_transactions(script_transactions, &self.pool).await
+        query::insert::insert_script_transactions(script_transactions, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_address_transactions_from_inputs(&self, use_tx: bool, transaction_ids: &[Hash]) -> Result<u64, Error> {
-        query::insert::insert_address_transactions_from_inputs(use_tx, transaction_ids, &self.pool).await
+        query::insert::insert_address_transactions_from_inputs(use_tx, transaction_ids, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_script_transactions_from_inputs(&self, use_tx: bool, transaction_ids: &[Hash]) -> Result<u64, Error> {
-        query::insert::insert_script_transactions_from_inputs(use_tx, transaction_ids, &self.pool).await
+        query::insert::insert_script_transactions_from_inputs(use_tx, transaction_ids, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_block_transactions(&self, block_transactions: &[BlockTransaction]) -> Result<u64, Error> {
-        query::insert::insert_block_transactions(block_transactions, &self.pool).await
+        query::insert::insert_block_transactions(block_transactions, &mut *self.acquire().await?).await
     }
 
     pub async fn insert_transaction_acceptances(&self, transaction_acceptances: &[TransactionAcceptance]) -> Result<u64, Error> {
-        query::insert::insert_transaction_acceptances(transaction_acceptances, &self.pool).await
+        query::insert::insert_transaction_acceptances(transaction_acceptances, &mut *self.acquire().await?).await
+    }
+
+    pub async fn insert_address_balance_changes(&self, block_hashes: &[Hash]) -> Result<u64, Error> {
+        query::insert::insert_address_balance_changes(block_hashes, &mut *self.acquire().await?).await
+    }
+
+    pub async fn insert_address_balance_changes_for_transactions(&self, transaction_ids: &[Hash]) -> Result<u64, Error> {
+        query::insert::insert_address_balance_changes_for_transactions(transaction_ids, &mut *self.acquire().await?).await
+    }
+
+    pub async fn insert_krc20_operations(&self, krc20_operations: &[Krc20Operation]) -> Result<u64, Error> {
+        query::insert::insert_krc20_operations(krc20_operations, &mut *self.acquire().await?).await
+    }
+
+    pub async fn insert_mempool_transactions(&self, transactions: &[MempoolTransaction]) -> Result<u64, Error> {
+        query::insert::insert_mempool_transactions(transactions, &mut *self.acquire().await?).await
+    }
+
+    pub async fn insert_mempool_inputs(&self, mempool_inputs: &[MempoolInput]) -> Result<u64, Error> {
+        query::insert::insert_mempool_inputs(mempool_inputs, &mut *self.acquire().await?).await
+    }
+
+    pub async fn insert_mempool_outputs(&self, mempool_outputs: &[MempoolOutput]) -> Result<u64, Error> {
+        query::insert::insert_mempool_outputs(mempool_outputs, &mut *self.acquire().await?).await
     }
 
     pub async fn upsert_var(&self, key: &str, value: &String) -> Result<u64, Error> {
-        query::upsert::upsert_var(key, value, &self.pool).await
+        query::upsert::upsert_var(key, value, &mut *self.acquire().await?).await
+    }
+
+    pub async fn delete_var(&self, key: &str) -> Result<u64, Error> {
+        query::delete::delete_var(key, &mut *self.acquire().await?).await
     }
 
     pub async fn delete_transaction_acceptances(&self, block_hashes: &[Hash]) -> Result<u64, Error> {
-        query::delete::delete_transaction_acceptances(block_hashes, &self.pool).await
+        query::delete::delete_transaction_acceptances(block_hashes, &mut *self.acquire().await?).await
+    }
+
+    pub async fn delete_address_balance_changes(&self, block_hashes: &[Hash]) -> Result<u64, Error> {
+        query::delete::delete_address_balance_changes(block_hashes, &mut *self.acquire().await?).await
+    }
+
+    pub async fn delete_mempool_transactions(&self, transaction_ids: &[Hash]) -> Result<u64, Error> {
+        query::delete::delete_mempool_transactions(transaction_ids, &mut *self.acquire().await?).await
+    }
+
+    pub async fn delete_mempool_transactions_except(&self, transaction_ids: &[Hash]) -> Result<u64, Error> {
+        query::delete::delete_mempool_transactions_except(transaction_ids, &mut *self.acquire().await?).await
+    }
+}
+
+struct WaiterGuard<'a>(&'a AtomicU32);
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
     }
 }
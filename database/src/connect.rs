@@ -0,0 +1,145 @@
+use sqlx::pool::PoolConnectionMetadata;
+use sqlx::postgres::{PgConnectOptions, PgSslMode};
+use sqlx::{Error, PgConnection};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The accepted values of PostgreSQL's synchronous_commit
+pub const SYNCHRONOUS_COMMIT_VALUES: [&str; 5] = ["off", "local", "remote_write", "on", "remote_apply"];
+
+/// Connection and pool settings in addition to the PostgreSQL url
+#[derive(Clone, Debug)]
+pub struct DbConnectOptions {
+    pub min_connections: u32,
+    pub max_connections: u32,
+    pub acquire_timeout: Duration,
+    /// Idle connections above min_connections are closed after this, None keeps them open
+    pub idle_timeout: Option<Duration>,
+    /// None uses the server setting
+    pub statement_timeout: Option<Duration>,
+    pub application_name: String,
+    /// synchronous_commit while in bulk mode, see KaspaDbClient::set_bulk_mode
+    pub bulk_synchronous_commit: Option<String>,
+    /// Overrides the password of the url. Without either, PGPASSWORD or .pgpass (PGPASSFILE) is used
+    pub password_file: Option<String>,
+    /// Overrides the sslmode of the url
+    pub ssl_mode: Option<String>,
+    pub ssl_root_cert: Option<String>,
+    /// Client certificate (PEM) for certificate authentication
+    pub ssl_client_cert: Option<String>,
+    pub ssl_client_key: Option<String>,
+}
+
+impl Default for DbConnectOptions {
+    fn default() -> Self {
+        DbConnectOptions {
+            min_connections: 0,
+            max_connections: 10,
+            acquire_timeout: Duration::from_secs(10),
+            idle_timeout: Some(Duration::from_secs(600)),
+            statement_timeout: None,
+            application_name: "simply-kaspa-indexer".to_string(),
+            bulk_synchronous_commit: None,
+            password_file: None,
+            ssl_mode: None,
+            ssl_root_cert: None,
+            ssl_client_cert: None,
+            ssl_client_key: None,
+        }
+    }
+}
+
+impl DbConnectOptions {
+    /// Parses the url and applies the options, the password of the url is replaced if a password file is set
+    pub fn pg_connect_options(&self, url: &str) -> Result<PgConnectOptions, Error> {
+        // Falls back to PGPASSWORD and .pgpass if the url has no password
+        let mut connect_opts = PgConnectOptions::from_str(url)?.application_name(&self.application_name);
+        if let Some(password_file) = &self.password_file {
+            let password = std::fs::read_to_string(password_file)
+                .map_err(|e| Error::Configuration(format!("Unable to read database password file {password_file}: {e}").into()))?;
+            connect_opts = connect_opts.password(password.trim_end_matches(['\r', '\n']));
+        }
+        if let Some(statement_timeout) = self.statement_timeout {
+            connect_opts = connect_opts.options([("statement_timeout", format!("{}ms", statement_timeout.as_millis()))]);
+        }
+        if let Some(ssl_mode) = &self.ssl_mode {
+            connect_opts = connect_opts.ssl_mode(PgSslMode::from_str(ssl_mode)?);
+        }
+        if let Some(ssl_root_cert) = &self.ssl_root_cert {
+            connect_opts = connect_opts.ssl_root_cert(ssl_root_cert);
+        }
+        if let Some(ssl_client_cert) = &self.ssl_client_cert {
+            connect_opts = connect_opts.ssl_client_cert(ssl_client_cert);
+        }
+        if let Some(ssl_client_key) = &self.ssl_client_key {
+            connect_opts = connect_opts.ssl_client_key(ssl_client_key);
+        }
+        if let Some(synchronous_commit) = &self.bulk_synchronous_commit {
+            if !SYNCHRONOUS_COMMIT_VALUES.contains(&synchronous_commit.as_str()) {
+                return Err(Error::Configuration(format!("Invalid synchronous_commit '{synchronous_commit}'").into()));
+            }
+        }
+        Ok(connect_opts)
+    }
+
+    /// Whether the effective sslmode (of the options, or else the url) verifies the server certificate
+    pub fn verifies_server_certificate(&self, url: &str) -> Result<bool, Error> {
+        let ssl_mode = match &self.ssl_mode {
+            Some(ssl_mode) => PgSslMode::from_str(ssl_mode)?,
+            None => PgConnectOptions::from_str(url)?.get_ssl_mode(),
+        };
+        Ok(matches!(ssl_mode, PgSslMode::VerifyCa | PgSslMode::VerifyFull))
+    }
+}
+
+/// The connection target without any credentials, for logging
+pub fn display_connect_options(connect_opts: &PgConnectOptions) -> String {
+    format!(
+        "postgres://{}@{}:{}/{}",
+        connect_opts.get_username(),
+        connect_opts.get_host(),
+        connect_opts.get_port(),
+        connect_opts.get_database().unwrap_or_default()
+    )
+}
+
+/// Applies the bulk synchronous_commit to new connections while in bulk mode. Connections opened before the last mode
+/// change are discarded on acquire, so that every connection handed out has the setting of the current mode
+pub(crate) struct BulkMode {
+    synchronous_commit: Option<String>,
+    /// Whether bulk mode is active, and when it last changed
+    state: Mutex<(bool, Option<Instant>)>,
+}
+
+impl BulkMode {
+    pub(crate) fn new(synchronous_commit: Option<String>) -> BulkMode {
+        BulkMode { synchronous_commit, state: Mutex::new((false, None)) }
+    }
+
+    /// Returns the bulk synchronous_commit if the mode was changed
+    pub(crate) fn set(&self, bulk: bool) -> Option<&str> {
+        let synchronous_commit = self.synchronous_commit.as_deref()?;
+        let mut state = self.state.lock().unwrap();
+        if state.0 == bulk {
+            return None;
+        }
+        *state = (bulk, Some(Instant::now()));
+        Some(synchronous_commit)
+    }
+
+    pub(crate) async fn after_connect(&self, conn: &mut PgConnection) -> Result<(), Error> {
+        let bulk = self.state.lock().unwrap().0;
+        if let (true, Some(synchronous_commit)) = (bulk, &self.synchronous_commit) {
+            sqlx::query(&format!("SET synchronous_commit = {synchronous_commit}")).execute(&mut *conn).await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn is_current(&self, metadata: &PoolConnectionMetadata) -> bool {
+        match self.state.lock().unwrap().1 {
+            Some(changed) => metadata.age <= changed.elapsed(),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,14 @@
+pub use sqlx::Error;
+
+/// SQLSTATE codes worth retrying: connection exceptions, serialization failure, deadlock, too many connections and server restarts
+const TRANSIENT_SQLSTATES: [&str; 11] =
+    ["08000", "08001", "08003", "08004", "08006", "40001", "40P01", "53300", "57P01", "57P02", "57P03"];
+
+/// Returns true if the error is likely to succeed on retry, false if it is fatal
+pub fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Io(_) | Error::PoolTimedOut | Error::WorkerCrashed => true,
+        Error::Database(e) => e.code().is_some_and(|code| TRANSIENT_SQLSTATES.contains(&&*code)),
+        _ => false,
+    }
+}
@@ -0,0 +1,85 @@
+/// Where the address to transaction mapping is stored
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressMapping {
+    /// The addresses_transactions table
+    Addresses,
+    /// The scripts_transactions table, used when tx_out_script_public_key_address is excluded
+    Scripts,
+    /// No mapping table, addresses are looked up in transactions_outputs and transactions_inputs
+    None,
+}
+
+impl AddressMapping {
+    /// The mapping table, if any
+    pub fn table(&self) -> Option<&'static str> {
+        match self {
+            AddressMapping::Addresses => Some("addresses_transactions"),
+            AddressMapping::Scripts => Some("scripts_transactions"),
+            AddressMapping::None => None,
+        }
+    }
+}
+
+/// The optional tables and fields populated by the indexer, the read queries adapt to them.
+/// Disabled tables are never queried, so they may be dropped
+#[derive(Clone, Debug)]
+pub struct ReadLayout {
+    pub blocks_table: bool,
+    pub blocks_transactions_table: bool,
+    pub transactions_table: bool,
+    pub transactions_inputs_table: bool,
+    pub transactions_outputs_table: bool,
+    /// Chain blocks are recorded in transactions_acceptances (virtual chain processing)
+    pub chain_blocks: bool,
+    /// Accepted transactions are recorded in transactions_acceptances
+    pub transaction_acceptance: bool,
+    pub address_mapping: AddressMapping,
+    /// previous_outpoint_script and previous_outpoint_amount of inputs are populated (transactions_inputs_resolve)
+    pub inputs_resolved: bool,
+    pub block_blue_score: bool,
+    pub tx_in_previous_outpoint: bool,
+    pub tx_out_script_public_key: bool,
+    pub tx_out_script_public_key_address: bool,
+}
+
+impl Default for ReadLayout {
+    /// The layout of the indexer's default options
+    fn default() -> Self {
+        ReadLayout {
+            blocks_table: true,
+            blocks_transactions_table: true,
+            transactions_table: true,
+            transactions_inputs_table: true,
+            transactions_outputs_table: true,
+            chain_blocks: true,
+            transaction_acceptance: true,
+            address_mapping: AddressMapping::Addresses,
+            inputs_resolved: false,
+            block_blue_score: true,
+            tx_in_previous_outpoint: true,
+            tx_out_script_public_key: true,
+            tx_out_script_public_key_address: true,
+        }
+    }
+}
+
+/// An address (without prefix, as stored) and/or its script public key. Depending on the layout either of them is needed, supply both if possible
+#[derive(Clone, Debug, Default)]
+pub struct AddressKey {
+    pub address: Option<String>,
+    pub script_public_key: Option<Vec<u8>>,
+}
+
+impl AddressKey {
+    pub fn new(address: String, script_public_key: Vec<u8>) -> AddressKey {
+        AddressKey { address: Some(address), script_public_key: Some(script_public_key) }
+    }
+
+    pub fn address(address: String) -> AddressKey {
+        AddressKey { address: Some(address), script_public_key: None }
+    }
+
+    pub fn script_public_key(script_public_key: Vec<u8>) -> AddressKey {
+        AddressKey { address: None, script_public_key: Some(script_public_key) }
+    }
+}
@@ -1,3 +1,6 @@
 pub mod client;
+pub mod connect;
+pub mod error;
+pub mod layout;
 pub mod models;
 mod query;
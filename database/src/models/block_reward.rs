@@ -0,0 +1,26 @@
+use crate::models::types::hash::Hash;
+
+pub struct BlockReward {
+    pub block_hash: Hash,
+    pub blue_score: i64,
+    pub subsidy: i64,
+    pub coinbase_amount: i64,
+    pub miner_script_public_key: Vec<u8>,
+    pub miner_address: Option<String>,
+    pub extra_data: Option<Vec<u8>>,
+    pub block_time: i64,
+}
+
+impl Eq for BlockReward {}
+
+impl PartialEq for BlockReward {
+    fn eq(&self, other: &Self) -> bool {
+        self.block_hash == other.block_hash
+    }
+}
+
+impl std::hash::Hash for BlockReward {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.block_hash.hash(state);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::models::types::hash::Hash;
+
+pub struct Krc20Operation {
+    pub transaction_id: Hash,
+    pub index: i16,
+    pub operation: String,
+    pub tick: Option<String>,
+    pub amount: Option<String>,
+    pub max_supply: Option<String>,
+    pub mint_limit: Option<String>,
+    pub decimals: Option<i16>,
+    pub to_address: Option<String>,
+    pub content: String,
+    pub block_time: i64,
+}
+
+impl Eq for Krc20Operation {}
+
+impl PartialEq for Krc20Operation {
+    fn eq(&self, other: &Self) -> bool {
+        self.transaction_id == other.transaction_id && self.index == other.index
+    }
+}
+
+impl std::hash::Hash for Krc20Operation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.transaction_id.hash(state);
+        self.index.hash(state);
+    }
+}
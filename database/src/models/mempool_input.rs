@@ -0,0 +1,25 @@
+use crate::models::types::hash::Hash;
+
+pub struct MempoolInput {
+    pub transaction_id: Hash,
+    pub index: i16,
+    pub previous_outpoint_hash: Option<Hash>,
+    pub previous_outpoint_index: Option<i16>,
+    pub signature_script: Option<Vec<u8>>,
+    pub sig_op_count: Option<i16>,
+}
+
+impl Eq for MempoolInput {}
+
+impl PartialEq for MempoolInput {
+    fn eq(&self, other: &Self) -> bool {
+        self.transaction_id == other.transaction_id && self.index == other.index
+    }
+}
+
+impl std::hash::Hash for MempoolInput {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.transaction_id.hash(state);
+        self.index.hash(state);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::models::types::hash::Hash;
+use crate::models::types::payload::Payload;
+
+pub struct MempoolTransaction {
+    pub transaction_id: Hash,
+    pub subnetwork_id: Option<i32>,
+    pub hash: Option<Hash>,
+    pub mass: Option<i32>,
+    pub payload: Option<Payload>,
+    pub fee: Option<i64>,
+    pub is_orphan: bool,
+    pub first_seen: i64,
+}
+
+impl Eq for MempoolTransaction {}
+
+impl PartialEq for MempoolTransaction {
+    fn eq(&self, other: &Self) -> bool {
+        self.transaction_id == other.transaction_id
+    }
+}
+
+impl std::hash::Hash for MempoolTransaction {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.transaction_id.hash(state);
+    }
+}
@@ -1,7 +1,12 @@
 pub mod address_transaction;
 pub mod block;
 pub mod block_parent;
+pub mod block_reward;
 pub mod block_transaction;
+pub mod krc20_operation;
+pub mod mempool_input;
+pub mod mempool_output;
+pub mod mempool_transaction;
 pub mod query;
 pub mod script_transaction;
 pub mod subnetwork;
@@ -0,0 +1,13 @@
+use crate::models::types::hash::Hash;
+
+#[derive(Clone, sqlx::FromRow)]
+pub struct Krc20OperationStatus {
+    pub transaction_id: Hash,
+    pub index: i16,
+    pub operation: String,
+    pub tick: Option<String>,
+    pub amount: Option<String>,
+    pub to_address: Option<String>,
+    pub block_time: i64,
+    pub accepting_block_hash: Option<Hash>,
+}
@@ -0,0 +1,8 @@
+#[derive(Clone, sqlx::FromRow)]
+pub struct MinerStatistics {
+    pub miner_address: Option<String>,
+    pub blocks: i64,
+    pub subsidy: i64,
+    pub first_block_time: i64,
+    pub last_block_time: i64,
+}
@@ -1,2 +1,8 @@
 pub mod database_details;
+pub mod krc20_operation_status;
+pub mod miner_statistics;
+pub mod pool_status;
 pub mod table_details;
+pub mod transaction_details;
+pub mod transaction_ref;
+pub mod utxo;
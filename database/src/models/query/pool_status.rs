@@ -0,0 +1,9 @@
+#[derive(Clone, Debug)]
+pub struct PoolStatus {
+    pub size: u32,
+    pub idle: u32,
+    pub min_connections: u32,
+    pub max_connections: u32,
+    /// Callers waiting for a connection
+    pub waiters: u32,
+}
@@ -0,0 +1,15 @@
+use crate::models::transaction::Transaction;
+use crate::models::transaction_input::TransactionInput;
+use crate::models::transaction_output::TransactionOutput;
+use crate::models::types::hash::Hash;
+
+pub struct TransactionDetails {
+    /// Only the transaction_id is set without the transactions table
+    pub transaction: Transaction,
+    pub inputs: Vec<TransactionInput>,
+    pub outputs: Vec<TransactionOutput>,
+    /// Empty without the blocks_transactions table
+    pub block_hashes: Vec<Hash>,
+    /// None if not (yet) accepted, or without transaction acceptance
+    pub accepting_block_hash: Option<Hash>,
+}
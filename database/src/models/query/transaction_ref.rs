@@ -0,0 +1,7 @@
+use crate::models::types::hash::Hash;
+
+#[derive(Clone, sqlx::FromRow)]
+pub struct TransactionRef {
+    pub transaction_id: Hash,
+    pub block_time: Option<i64>,
+}
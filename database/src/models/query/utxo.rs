@@ -0,0 +1,13 @@
+use crate::models::types::hash::Hash;
+
+#[derive(Clone, sqlx::FromRow)]
+pub struct Utxo {
+    pub transaction_id: Hash,
+    pub index: i16,
+    pub amount: Option<i64>,
+    pub script_public_key: Option<Vec<u8>>,
+    pub script_public_key_address: Option<String>,
+    pub block_time: Option<i64>,
+    /// None if not (yet) accepted, or without transaction acceptance
+    pub accepting_block_hash: Option<Hash>,
+}
@@ -1,6 +1,7 @@
 use crate::models::types::hash::Hash;
 use crate::models::types::payload::Payload;
 
+#[derive(sqlx::FromRow)]
 pub struct Transaction {
     pub transaction_id: Hash,
     pub subnetwork_id: Option<i32>,
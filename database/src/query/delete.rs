@@ -1,10 +1,52 @@
 use crate::models::types::hash::Hash;
-use sqlx::{Error, Pool, Postgres};
+use sqlx::{Connection, Error, Executor, PgConnection, Row};
 
-pub async fn delete_transaction_acceptances(block_hashes: &[Hash], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn delete_var(key: &str, conn: &mut PgConnection) -> Result<u64, Error> {
+    Ok(sqlx::query("DELETE FROM vars WHERE key = $1").bind(key).execute(&mut *conn).await?.rows_affected())
+}
+
+pub async fn delete_transaction_acceptances(block_hashes: &[Hash], conn: &mut PgConnection) -> Result<u64, Error> {
     Ok(sqlx::query("DELETE FROM transactions_acceptances WHERE block_hash = ANY($1)")
         .bind(block_hashes)
-        .execute(pool)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected())
+}
+
+pub async fn delete_address_balance_changes(block_hashes: &[Hash], conn: &mut PgConnection) -> Result<u64, Error> {
+    Ok(sqlx::query("DELETE FROM address_balance_changes WHERE block_hash = ANY($1)")
+        .bind(block_hashes)
+        .execute(&mut *conn)
         .await?
         .rows_affected())
 }
+
+pub async fn delete_mempool_transactions(transaction_ids: &[Hash], conn: &mut PgConnection) -> Result<u64, Error> {
+    let mut tx = conn.begin().await?;
+    tx.execute(sqlx::query("DELETE FROM mempool_inputs WHERE transaction_id = ANY($1)").bind(transaction_ids)).await?;
+    tx.execute(sqlx::query("DELETE FROM mempool_outputs WHERE transaction_id = ANY($1)").bind(transaction_ids)).await?;
+    let rows_affected = tx
+        .execute(sqlx::query("DELETE FROM mempool_transactions WHERE transaction_id = ANY($1)").bind(transaction_ids))
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+    Ok(rows_affected)
+}
+
+pub async fn delete_mempool_transactions_except(transaction_ids: &[Hash], conn: &mut PgConnection) -> Result<u64, Error> {
+    let mut tx = conn.begin().await?;
+    let removed_ids: Vec<Hash> =
+        sqlx::query("DELETE FROM mempool_transactions WHERE NOT (transaction_id = ANY($1)) RETURNING transaction_id")
+            .bind(transaction_ids)
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .map(|row| row.get(0))
+            .collect();
+    if !removed_ids.is_empty() {
+        tx.execute(sqlx::query("DELETE FROM mempool_inputs WHERE transaction_id = ANY($1)").bind(&removed_ids)).await?;
+        tx.execute(sqlx::query("DELETE FROM mempool_outputs WHERE transaction_id = ANY($1)").bind(&removed_ids)).await?;
+    }
+    tx.commit().await?;
+    Ok(removed_ids.len() as u64)
+}
@@ -1,10 +1,15 @@
 use itertools::Itertools;
-use sqlx::{Error, Executor, Pool, Postgres, Row};
+use sqlx::{Connection, Error, Executor, PgConnection, Row};
 
 use crate::models::address_transaction::AddressTransaction;
 use crate::models::block::Block;
 use crate::models::block_parent::BlockParent;
+use crate::models::block_reward::BlockReward;
 use crate::models::block_transaction::BlockTransaction;
+use crate::models::krc20_operation::Krc20Operation;
+use crate::models::mempool_input::MempoolInput;
+use crate::models::mempool_output::MempoolOutput;
+use crate::models::mempool_transaction::MempoolTransaction;
 use crate::models::script_transaction::ScriptTransaction;
 use crate::models::transaction::Transaction;
 use crate::models::transaction_acceptance::TransactionAcceptance;
@@ -12,17 +17,17 @@ use crate::models::transaction_input::TransactionInput;
 use crate::models::transaction_output::TransactionOutput;
 use crate::models::types::hash::Hash;
 
-pub async fn insert_subnetwork(subnetwork_id: &String, pool: &Pool<Postgres>) -> Result<i32, Error> {
+pub async fn insert_subnetwork(subnetwork_id: &String, conn: &mut PgConnection) -> Result<i32, Error> {
     sqlx::query("INSERT INTO subnetworks (subnetwork_id) VALUES ($1) ON CONFLICT DO NOTHING RETURNING id")
         .bind(subnetwork_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?
         .try_get(0)
 }
 
-pub async fn insert_blocks(blocks: &[Block], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn insert_blocks(blocks: &[Block], conn: &mut PgConnection) -> Result<u64, Error> {
     const COLS: usize = 15;
-    let mut tx = pool.begin().await?;
+    let mut tx = conn.begin().await?;
 
     let sql = format!(
         "INSERT INTO blocks (hash, accepted_id_merkle_root, merge_set_blues_hashes, merge_set_reds_hashes,
@@ -55,7 +60,7 @@ pub async fn insert_blocks(blocks: &[Block], pool: &Pool<Postgres>) -> Result<u6
     Ok(rows_affected)
 }
 
-pub async fn insert_block_parents(block_parents: &[BlockParent], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn insert_block_parents(block_parents: &[BlockParent], conn: &mut PgConnection) -> Result<u64, Error> {
     const COLS: usize = 2;
     let sql = format!(
         "INSERT INTO block_parent (block_hash, parent_hash)
@@ -67,10 +72,32 @@ pub async fn insert_block_parents(block_parents: &[BlockParent], pool: &Pool<Pos
         query = query.bind(&block_transaction.block_hash);
         query = query.bind(&block_transaction.parent_hash);
     }
-    Ok(query.execute(pool).await?.rows_affected())
+    Ok(query.execute(&mut *conn).await?.rows_affected())
 }
 
-pub async fn insert_transactions(transactions: &[Transaction], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn insert_block_rewards(block_rewards: &[BlockReward], conn: &mut PgConnection) -> Result<u64, Error> {
+    const COLS: usize = 8;
+    let sql = format!(
+        "INSERT INTO block_rewards (block_hash, blue_score, subsidy, coinbase_amount, miner_script_public_key, miner_address,
+            extra_data, block_time)
+        VALUES {} ON CONFLICT DO NOTHING",
+        generate_placeholders(block_rewards.len(), COLS)
+    );
+    let mut query = sqlx::query(&sql);
+    for block_reward in block_rewards {
+        query = query.bind(&block_reward.block_hash);
+        query = query.bind(block_reward.blue_score);
+        query = query.bind(block_reward.subsidy);
+        query = query.bind(block_reward.coinbase_amount);
+        query = query.bind(&block_reward.miner_script_public_key);
+        query = query.bind(&block_reward.miner_address);
+        query = query.bind(&block_reward.extra_data);
+        query = query.bind(block_reward.block_time);
+    }
+    Ok(query.execute(&mut *conn).await?.rows_affected())
+}
+
+pub async fn insert_transactions(transactions: &[Transaction], conn: &mut PgConnection) -> Result<u64, Error> {
     const COLS: usize = 6;
     let sql = format!(
         "INSERT INTO transactions (transaction_id, subnetwork_id, hash, mass, payload, block_time)
@@ -86,13 +113,13 @@ pub async fn insert_transactions(transactions: &[Transaction], pool: &Pool<Postg
         query = query.bind(&tx.payload);
         query = query.bind(tx.block_time);
     }
-    Ok(query.execute(pool).await?.rows_affected())
+    Ok(query.execute(&mut *conn).await?.rows_affected())
 }
 
 pub async fn insert_transaction_inputs(
     resolve_previous_outpoints: bool,
     transaction_inputs: &[TransactionInput],
-    pool: &Pool<Postgres>,
+    conn: &mut PgConnection,
 ) -> Result<u64, Error> {
     const COLS: usize = 9;
     let sql = if resolve_previous_outpoints {
@@ -131,10 +158,10 @@ pub async fn insert_transaction_inputs(
         query = query.bind(&tin.previous_outpoint_script);
         query = query.bind(tin.previous_outpoint_amount);
     }
-    Ok(query.execute(pool).await?.rows_affected())
+    Ok(query.execute(&mut *conn).await?.rows_affected())
 }
 
-pub async fn insert_transaction_outputs(transaction_outputs: &[TransactionOutput], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn insert_transaction_outputs(transaction_outputs: &[TransactionOutput], conn: &mut PgConnection) -> Result<u64, Error> {
     const COLS: usize = 6;
     let sql = format!(
         "INSERT INTO transactions_outputs (transaction_id, index, amount, script_public_key, script_public_key_address, block_time)
@@ -150,10 +177,10 @@ pub async fn insert_transaction_outputs(transaction_outputs: &[TransactionOutput
         query = query.bind(&tout.script_public_key_address);
         query = query.bind(tout.block_time);
     }
-    Ok(query.execute(pool).await?.rows_affected())
+    Ok(query.execute(&mut *conn).await?.rows_affected())
 }
 
-pub async fn insert_address_transactions(address_transactions: &[AddressTransaction], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn insert_address_transactions(address_transactions: &[AddressTransaction], conn: &mut PgConnection) -> Result<u64, Error> {
     const COLS: usize = 3;
     let sql = format!(
         "INSERT INTO addresses_transactions (address, transaction_id, block_time)
@@ -166,10 +193,10 @@ pub async fn insert_address_transactions(address_transactions: &[AddressTransact
         query = query.bind(&address_transaction.transaction_id);
         query = query.bind(address_transaction.block_time);
     }
-    Ok(query.execute(pool).await?.rows_affected())
+    Ok(query.execute(&mut *conn).await?.rows_affected())
 }
 
-pub async fn insert_script_transactions(script_transactions: &[ScriptTransaction], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn insert_script_transactions(script_transactions: &[ScriptTransaction], conn: &mut PgConnection) -> Result<u64, Error> {
     const COLS: usize = 3;
     let sql = format!(
         "INSERT INTO scripts_transactions (script_public_key, transaction_id, block_time)
@@ -182,13 +209,13 @@ pub async fn insert_script_transactions(script_transactions: &[ScriptTransaction
         query = query.bind(&script_transaction.transaction_id);
         query = query.bind(script_transaction.block_time);
     }
-    Ok(query.execute(pool).await?.rows_affected())
+    Ok(query.execute(&mut *conn).await?.rows_affected())
 }
 
 pub async fn insert_address_transactions_from_inputs(
     use_tx: bool,
     transaction_ids: &[Hash],
-    pool: &Pool<Postgres>,
+    conn: &mut PgConnection,
 ) -> Result<u64, Error> {
     let sql = if use_tx {
         "INSERT INTO addresses_transactions (address, transaction_id, block_time)
@@ -206,13 +233,13 @@ pub async fn insert_address_transactions_from_inputs(
         WHERE i.transaction_id = ANY($1)
         ON CONFLICT DO NOTHING"
     };
-    Ok(sqlx::query(sql).bind(transaction_ids).execute(pool).await?.rows_affected())
+    Ok(sqlx::query(sql).bind(transaction_ids).execute(&mut *conn).await?.rows_affected())
 }
 
 pub async fn insert_script_transactions_from_inputs(
     use_tx: bool,
     transaction_ids: &[Hash],
-    pool: &Pool<Postgres>,
+    conn: &mut PgConnection,
 ) -> Result<u64, Error> {
     let sql = if use_tx {
         "INSERT INTO scripts_transactions (script_public_key, transaction_id, block_time)
@@ -230,10 +257,10 @@ pub async fn insert_script_transactions_from_inputs(
         WHERE i.transaction_id = ANY($1)
         ON CONFLICT DO NOTHING"
     };
-    Ok(sqlx::query(sql).bind(transaction_ids).execute(pool).await?.rows_affected())
+    Ok(sqlx::query(sql).bind(transaction_ids).execute(&mut *conn).await?.rows_affected())
 }
 
-pub async fn insert_block_transactions(block_transactions: &[BlockTransaction], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn insert_block_transactions(block_transactions: &[BlockTransaction], conn: &mut PgConnection) -> Result<u64, Error> {
     const COLS: usize = 2;
     let sql = format!(
         "INSERT INTO blocks_transactions (block_hash, transaction_id)
@@ -245,10 +272,10 @@ pub async fn insert_block_transactions(block_transactions: &[BlockTransaction],
         query = query.bind(&block_transaction.block_hash);
         query = query.bind(&block_transaction.transaction_id);
     }
-    Ok(query.execute(pool).await?.rows_affected())
+    Ok(query.execute(&mut *conn).await?.rows_affected())
 }
 
-pub async fn insert_transaction_acceptances(tx_acceptances: &[TransactionAcceptance], pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn insert_transaction_acceptances(tx_acceptances: &[TransactionAcceptance], conn: &mut PgConnection) -> Result<u64, Error> {
     const COLS: usize = 2;
     let sql = format!(
         "INSERT INTO transactions_acceptances (transaction_id, block_hash) VALUES {} ON CONFLICT DO NOTHING",
@@ -259,7 +286,129 @@ pub async fn insert_transaction_acceptances(tx_acceptances: &[TransactionAccepta
         query = query.bind(&ta.transaction_id);
         query = query.bind(&ta.block_hash);
     }
-    Ok(query.execute(pool).await?.rows_affected())
+    Ok(query.execute(&mut *conn).await?.rows_affected())
+}
+
+pub async fn insert_address_balance_changes(block_hashes: &[Hash], conn: &mut PgConnection) -> Result<u64, Error> {
+    let sql = address_balance_changes_sql("ta.block_hash = ANY($1)");
+    Ok(sqlx::query(&sql).bind(block_hashes).execute(&mut *conn).await?.rows_affected())
+}
+
+pub async fn insert_address_balance_changes_for_transactions(transaction_ids: &[Hash], conn: &mut PgConnection) -> Result<u64, Error> {
+    let sql = address_balance_changes_sql("ta.transaction_id = ANY($1)");
+    Ok(sqlx::query(&sql).bind(transaction_ids).execute(&mut *conn).await?.rows_affected())
+}
+
+/// The acceptance, the transaction rows and the accepting block row are committed independently by different processors.
+/// Each of them re-runs this after committing, the rows are recalculated from scratch, so the last one completes them.
+fn address_balance_changes_sql(acceptances_filter: &str) -> String {
+    format!(
+        "INSERT INTO address_balance_changes (address, transaction_id, block_hash, amount, blue_score, daa_score, block_time)
+        SELECT c.address, c.transaction_id, c.block_hash, SUM(c.amount)::BIGINT, b.blue_score, b.daa_score, b.timestamp
+        FROM (
+            SELECT o.script_public_key_address AS address, ta.transaction_id, ta.block_hash, o.amount
+                FROM transactions_acceptances ta
+                JOIN transactions_outputs o ON o.transaction_id = ta.transaction_id
+            WHERE {acceptances_filter}
+            UNION ALL
+            SELECT o.script_public_key_address AS address, ta.transaction_id, ta.block_hash, -o.amount
+                FROM transactions_acceptances ta
+                JOIN transactions_inputs i ON i.transaction_id = ta.transaction_id
+                JOIN transactions_outputs o ON o.transaction_id = i.previous_outpoint_hash AND o.index = i.previous_outpoint_index
+            WHERE {acceptances_filter}
+        ) c
+        JOIN blocks b ON b.hash = c.block_hash
+        WHERE c.address IS NOT NULL
+        GROUP BY c.address, c.transaction_id, c.block_hash, b.blue_score, b.daa_score, b.timestamp
+        ON CONFLICT (address, transaction_id) DO UPDATE
+            SET block_hash = EXCLUDED.block_hash, amount = EXCLUDED.amount, blue_score = EXCLUDED.blue_score,
+                daa_score = EXCLUDED.daa_score, block_time = EXCLUDED.block_time
+            WHERE (address_balance_changes.block_hash, address_balance_changes.amount)
+                IS DISTINCT FROM (EXCLUDED.block_hash, EXCLUDED.amount)"
+    )
+}
+
+pub async fn insert_krc20_operations(krc20_operations: &[Krc20Operation], conn: &mut PgConnection) -> Result<u64, Error> {
+    const COLS: usize = 11;
+    let sql = format!(
+        "INSERT INTO krc20_operations (transaction_id, index, operation, tick, amount, max_supply, mint_limit, decimals,
+            to_address, content, block_time
+        ) VALUES {} ON CONFLICT DO NOTHING",
+        generate_placeholders(krc20_operations.len(), COLS)
+    );
+    let mut query = sqlx::query(&sql);
+    for krc20_operation in krc20_operations {
+        query = query.bind(&krc20_operation.transaction_id);
+        query = query.bind(krc20_operation.index);
+        query = query.bind(&krc20_operation.operation);
+        query = query.bind(&krc20_operation.tick);
+        query = query.bind(&krc20_operation.amount);
+        query = query.bind(&krc20_operation.max_supply);
+        query = query.bind(&krc20_operation.mint_limit);
+        query = query.bind(krc20_operation.decimals);
+        query = query.bind(&krc20_operation.to_address);
+        query = query.bind(&krc20_operation.content);
+        query = query.bind(krc20_operation.block_time);
+    }
+    Ok(query.execute(&mut *conn).await?.rows_affected())
+}
+
+pub async fn insert_mempool_transactions(transactions: &[MempoolTransaction], conn: &mut PgConnection) -> Result<u64, Error> {
+    const COLS: usize = 8;
+    let sql = format!(
+        "INSERT INTO mempool_transactions (transaction_id, subnetwork_id, hash, mass, payload, fee, is_orphan, first_seen)
+        VALUES {} ON CONFLICT (transaction_id) DO UPDATE SET fee = EXCLUDED.fee, is_orphan = EXCLUDED.is_orphan",
+        generate_placeholders(transactions.len(), COLS)
+    );
+    let mut query = sqlx::query(&sql);
+    for tx in transactions {
+        query = query.bind(&tx.transaction_id);
+        query = query.bind(tx.subnetwork_id);
+        query = query.bind(&tx.hash);
+        query = query.bind(tx.mass);
+        query = query.bind(&tx.payload);
+        query = query.bind(tx.fee);
+        query = query.bind(tx.is_orphan);
+        query = query.bind(tx.first_seen);
+    }
+    Ok(query.execute(&mut *conn).await?.rows_affected())
+}
+
+pub async fn insert_mempool_inputs(mempool_inputs: &[MempoolInput], conn: &mut PgConnection) -> Result<u64, Error> {
+    const COLS: usize = 6;
+    let sql = format!(
+        "INSERT INTO mempool_inputs (transaction_id, index, previous_outpoint_hash, previous_outpoint_index, signature_script, sig_op_count)
+        VALUES {} ON CONFLICT DO NOTHING",
+        generate_placeholders(mempool_inputs.len(), COLS)
+    );
+    let mut query = sqlx::query(&sql);
+    for input in mempool_inputs {
+        query = query.bind(&input.transaction_id);
+        query = query.bind(input.index);
+        query = query.bind(&input.previous_outpoint_hash);
+        query = query.bind(input.previous_outpoint_index);
+        query = query.bind(&input.signature_script);
+        query = query.bind(input.sig_op_count);
+    }
+    Ok(query.execute(&mut *conn).await?.rows_affected())
+}
+
+pub async fn insert_mempool_outputs(mempool_outputs: &[MempoolOutput], conn: &mut PgConnection) -> Result<u64, Error> {
+    const COLS: usize = 5;
+    let sql = format!(
+        "INSERT INTO mempool_outputs (transaction_id, index, amount, script_public_key, script_public_key_address)
+        VALUES {} ON CONFLICT DO NOTHING",
+        generate_placeholders(mempool_outputs.len(), COLS)
+    );
+    let mut query = sqlx::query(&sql);
+    for output in mempool_outputs {
+        query = query.bind(&output.transaction_id);
+        query = query.bind(output.index);
+        query = query.bind(output.amount);
+        query = query.bind(&output.script_public_key);
+        query = query.bind(&output.script_public_key_address);
+    }
+    Ok(query.execute(&mut *conn).await?.rows_affected())
 }
 
 fn generate_placeholders(rows: usize, columns: usize) -> String {
@@ -1,8 +1,8 @@
-use sqlx::{Error, Pool, Postgres};
+use sqlx::{Error, PgConnection};
 
-pub async fn execute_ddl(ddl: &str, pool: &Pool<Postgres>) -> Result<(), Error> {
+pub async fn execute_ddl(ddl: &str, conn: &mut PgConnection) -> Result<(), Error> {
     for statement in ddl.split(";").filter(|stmt| !stmt.trim().is_empty()) {
-        sqlx::query(statement).execute(pool).await?;
+        sqlx::query(statement).execute(&mut *conn).await?;
     }
     Ok(())
 }
@@ -1,5 +1,6 @@
 pub(crate) mod delete;
 pub(crate) mod insert;
 pub(crate) mod misc;
+pub(crate) mod read;
 pub(crate) mod select;
 pub(crate) mod upsert;
@@ -0,0 +1,314 @@
+use crate::layout::{AddressKey, AddressMapping, ReadLayout};
+use crate::models::block::Block;
+use crate::models::query::transaction_details::TransactionDetails;
+use crate::models::query::transaction_ref::TransactionRef;
+use crate::models::query::utxo::Utxo;
+use crate::models::transaction::Transaction;
+use crate::models::transaction_input::TransactionInput;
+use crate::models::transaction_output::TransactionOutput;
+use crate::models::types::hash::Hash;
+use sqlx::{Error, PgConnection};
+
+const BLOCK_COLUMNS: &str = "hash, accepted_id_merkle_root, merge_set_blues_hashes, merge_set_reds_hashes, selected_parent_hash, \
+    bits, blue_score, blue_work, daa_score, hash_merkle_root, nonce, pruning_point, \"timestamp\", utxo_commitment, version";
+
+/// The column of transactions_outputs (and its value) used for looking up an address
+enum OutputFilter {
+    Script(Vec<u8>),
+    Address(String),
+}
+
+impl OutputFilter {
+    fn column(&self) -> &'static str {
+        match self {
+            OutputFilter::Script(_) => "script_public_key",
+            OutputFilter::Address(_) => "script_public_key_address",
+        }
+    }
+}
+
+fn unsupported(msg: &str) -> Error {
+    Error::Configuration(format!("Not supported by the layout: {msg}").into())
+}
+
+/// Prefers the script public key, as it is also available for non-standard scripts
+fn output_filter(layout: &ReadLayout, key: &AddressKey) -> Result<OutputFilter, Error> {
+    match (&key.script_public_key, &key.address) {
+        (Some(script), _) if layout.tx_out_script_public_key => Ok(OutputFilter::Script(script.clone())),
+        (_, Some(address)) if layout.tx_out_script_public_key_address => Ok(OutputFilter::Address(address.clone())),
+        _ => Err(unsupported("the outputs are indexed without the supplied address or script public key")),
+    }
+}
+
+pub async fn select_block(layout: &ReadLayout, block_hash: &Hash, conn: &mut PgConnection) -> Result<Option<Block>, Error> {
+    if !layout.blocks_table {
+        return Err(unsupported("blocks_table is disabled"));
+    }
+    sqlx::query_as::<_, Block>(&format!("SELECT {BLOCK_COLUMNS} FROM blocks WHERE hash = $1"))
+        .bind(block_hash)
+        .fetch_optional(&mut *conn)
+        .await
+}
+
+pub async fn select_transactions_by_block(
+    layout: &ReadLayout,
+    block_hash: &Hash,
+    conn: &mut PgConnection,
+) -> Result<Vec<Transaction>, Error> {
+    if !layout.blocks_transactions_table {
+        return Err(unsupported("blocks_transactions_table is disabled"));
+    }
+    let sql = if layout.transactions_table {
+        "
+        SELECT bt.transaction_id, t.subnetwork_id, t.hash, t.mass, t.payload, t.block_time
+        FROM blocks_transactions bt
+        LEFT JOIN transactions t ON t.transaction_id = bt.transaction_id
+        WHERE bt.block_hash = $1
+        ORDER BY bt.transaction_id
+    "
+    } else {
+        "
+        SELECT transaction_id, NULL::INTEGER AS subnetwork_id, NULL::BYTEA AS hash, NULL::INTEGER AS mass,
+            NULL::BYTEA AS payload, NULL::BIGINT AS block_time
+        FROM blocks_transactions
+        WHERE block_hash = $1
+        ORDER BY transaction_id
+    "
+    };
+    sqlx::query_as::<_, Transaction>(sql).bind(block_hash).fetch_all(&mut *conn).await
+}
+
+/// Previous outpoint script and amount of unresolved inputs are taken from transactions_outputs when available
+pub async fn select_transaction(
+    layout: &ReadLayout,
+    transaction_id: &Hash,
+    conn: &mut PgConnection,
+) -> Result<Option<TransactionDetails>, Error> {
+    let transaction = if layout.transactions_table {
+        sqlx::query_as::<_, Transaction>(
+            "SELECT transaction_id, subnetwork_id, hash, mass, payload, block_time FROM transactions WHERE transaction_id = $1",
+        )
+        .bind(transaction_id)
+        .fetch_optional(&mut *conn)
+        .await?
+    } else {
+        None
+    };
+    let inputs = if !layout.transactions_inputs_table {
+        vec![]
+    } else if layout.transactions_outputs_table && layout.tx_in_previous_outpoint {
+        sqlx::query_as::<_, TransactionInput>(
+            "
+            SELECT i.transaction_id, i.index, i.previous_outpoint_hash, i.previous_outpoint_index, i.signature_script,
+                i.sig_op_count, i.block_time,
+                COALESCE(i.previous_outpoint_script, o.script_public_key) AS previous_outpoint_script,
+                COALESCE(i.previous_outpoint_amount, o.amount) AS previous_outpoint_amount
+            FROM transactions_inputs i
+            LEFT JOIN transactions_outputs o ON o.transaction_id = i.previous_outpoint_hash AND o.index = i.previous_outpoint_index
+            WHERE i.transaction_id = $1
+            ORDER BY i.index
+        ",
+        )
+        .bind(transaction_id)
+        .fetch_all(&mut *conn)
+        .await?
+    } else {
+        sqlx::query_as::<_, TransactionInput>(
+            "
+            SELECT transaction_id, index, previous_outpoint_hash, previous_outpoint_index, signature_script, sig_op_count,
+                block_time, previous_outpoint_script, previous_outpoint_amount
+            FROM transactions_inputs
+            WHERE transaction_id = $1
+            ORDER BY index
+        ",
+        )
+        .bind(transaction_id)
+        .fetch_all(&mut *conn)
+        .await?
+    };
+    let outputs = if layout.transactions_outputs_table {
+        sqlx::query_as::<_, TransactionOutput>(
+            "
+            SELECT transaction_id, index, amount, script_public_key, script_public_key_address, block_time
+            FROM transactions_outputs
+            WHERE transaction_id = $1
+            ORDER BY index
+        ",
+        )
+        .bind(transaction_id)
+        .fetch_all(&mut *conn)
+        .await?
+    } else {
+        vec![]
+    };
+    let block_hashes: Vec<Hash> = if layout.blocks_transactions_table {
+        sqlx::query_scalar("SELECT block_hash FROM blocks_transactions WHERE transaction_id = $1")
+            .bind(transaction_id)
+            .fetch_all(&mut *conn)
+            .await?
+    } else {
+        vec![]
+    };
+    let accepting_block_hash: Option<Hash> = if layout.transaction_acceptance {
+        sqlx::query_scalar("SELECT block_hash FROM transactions_acceptances WHERE transaction_id = $1")
+            .bind(transaction_id)
+            .fetch_optional(&mut *conn)
+            .await?
+    } else {
+        None
+    };
+    if transaction.is_none() && inputs.is_empty() && outputs.is_empty() && block_hashes.is_empty() {
+        return Ok(None);
+    }
+    let transaction = transaction.unwrap_or_else(|| Transaction {
+        transaction_id: transaction_id.clone(),
+        subnetwork_id: None,
+        hash: None,
+        mass: None,
+        payload: None,
+        block_time: None,
+    });
+    Ok(Some(TransactionDetails { transaction, inputs, outputs, block_hashes, accepting_block_hash }))
+}
+
+/// Transactions sending to or spending from the address, newest first
+pub async fn select_address_transactions(
+    layout: &ReadLayout,
+    key: &AddressKey,
+    limit: i64,
+    offset: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<TransactionRef>, Error> {
+    let (sql, filter) = match layout.address_mapping {
+        AddressMapping::Addresses => {
+            let address = key.address.clone().ok_or_else(|| unsupported("addresses_transactions requires the address"))?;
+            (
+                "SELECT transaction_id, block_time FROM addresses_transactions WHERE address = $1 \
+                ORDER BY block_time DESC LIMIT $2 OFFSET $3"
+                    .to_string(),
+                OutputFilter::Address(address),
+            )
+        }
+        AddressMapping::Scripts => {
+            let script =
+                key.script_public_key.clone().ok_or_else(|| unsupported("scripts_transactions requires the script public key"))?;
+            (
+                "SELECT transaction_id, block_time FROM scripts_transactions WHERE script_public_key = $1 \
+                ORDER BY block_time DESC LIMIT $2 OFFSET $3"
+                    .to_string(),
+                OutputFilter::Script(script),
+            )
+        }
+        AddressMapping::None => {
+            if !layout.transactions_outputs_table {
+                return Err(unsupported("address lookups without a mapping table require transactions_outputs_table"));
+            }
+            let filter = output_filter(layout, key)?;
+            let column = filter.column();
+            // Resolved inputs can be filtered directly, otherwise the spent outputs are joined
+            let inputs = match &filter {
+                _ if !layout.transactions_inputs_table || !layout.tx_in_previous_outpoint => String::new(),
+                OutputFilter::Script(_) if layout.inputs_resolved => {
+                    "UNION ALL SELECT transaction_id, block_time FROM transactions_inputs WHERE previous_outpoint_script = $1"
+                        .to_string()
+                }
+                _ => format!(
+                    "UNION ALL SELECT i.transaction_id, i.block_time FROM transactions_inputs i \
+                    JOIN transactions_outputs o ON o.transaction_id = i.previous_outpoint_hash AND o.index = i.previous_outpoint_index \
+                    WHERE o.{column} = $1"
+                ),
+            };
+            (
+                format!(
+                    "SELECT transaction_id, MAX(block_time) AS block_time FROM ( \
+                    SELECT transaction_id, block_time FROM transactions_outputs WHERE {column} = $1 {inputs} \
+                    ) a GROUP BY transaction_id ORDER BY block_time DESC NULLS LAST LIMIT $2 OFFSET $3"
+                ),
+                filter,
+            )
+        }
+    };
+    let query = sqlx::query_as::<_, TransactionRef>(&sql);
+    let query = match &filter {
+        OutputFilter::Script(script) => query.bind(script),
+        OutputFilter::Address(address) => query.bind(address),
+    };
+    query.bind(limit).bind(offset).fetch_all(&mut *conn).await
+}
+
+/// Outputs to the address which are not spent by an (accepted, if available) input, newest first.
+/// With accepted_only, outputs of transactions which are not (yet) accepted are left out, this requires transaction acceptance
+pub async fn select_utxos(
+    layout: &ReadLayout,
+    key: &AddressKey,
+    accepted_only: bool,
+    conn: &mut PgConnection,
+) -> Result<Vec<Utxo>, Error> {
+    if !layout.transactions_outputs_table || !layout.transactions_inputs_table || !layout.tx_in_previous_outpoint {
+        return Err(unsupported("utxos require the transactions_inputs and transactions_outputs tables and tx_in_previous_outpoint"));
+    }
+    if accepted_only && !layout.transaction_acceptance {
+        return Err(unsupported("accepted utxos require transaction acceptance"));
+    }
+    let filter = output_filter(layout, key)?;
+    let column = filter.column();
+    let (accepting_block_hash, accepted_outputs, accepted_inputs) = if layout.transaction_acceptance {
+        (
+            "ta.block_hash",
+            if accepted_only {
+                "JOIN transactions_acceptances ta ON ta.transaction_id = o.transaction_id"
+            } else {
+                "LEFT JOIN transactions_acceptances ta ON ta.transaction_id = o.transaction_id"
+            },
+            "JOIN transactions_acceptances tai ON tai.transaction_id = i.transaction_id",
+        )
+    } else {
+        ("NULL::BYTEA", "", "")
+    };
+    let sql = format!(
+        "
+        SELECT o.transaction_id, o.index, o.amount, o.script_public_key, o.script_public_key_address, o.block_time,
+            {accepting_block_hash} AS accepting_block_hash
+        FROM transactions_outputs o
+        {accepted_outputs}
+        WHERE o.{column} = $1
+        AND NOT EXISTS (
+            SELECT 1 FROM transactions_inputs i
+            {accepted_inputs}
+            WHERE i.previous_outpoint_hash = o.transaction_id AND i.previous_outpoint_index = o.index
+        )
+        ORDER BY o.block_time DESC NULLS LAST
+    "
+    );
+    let query = sqlx::query_as::<_, Utxo>(&sql);
+    let query = match &filter {
+        OutputFilter::Script(script) => query.bind(script),
+        OutputFilter::Address(address) => query.bind(address),
+    };
+    query.fetch_all(&mut *conn).await
+}
+
+/// Chain blocks with from_blue_score <= blue_score < to_blue_score, in blue score order
+pub async fn select_chain_blocks(
+    layout: &ReadLayout,
+    from_blue_score: i64,
+    to_blue_score: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Block>, Error> {
+    if !layout.chain_blocks || !layout.blocks_table || !layout.block_blue_score {
+        return Err(unsupported("chain blocks require virtual chain processing, blocks_table and block_blue_score"));
+    }
+    sqlx::query_as::<_, Block>(&format!(
+        "
+        SELECT {BLOCK_COLUMNS}
+        FROM blocks b
+        WHERE b.blue_score >= $1 AND b.blue_score < $2
+        AND EXISTS (SELECT 1 FROM transactions_acceptances ta WHERE ta.block_hash = b.hash)
+        ORDER BY b.blue_score
+    "
+    ))
+    .bind(from_blue_score)
+    .bind(to_blue_score)
+    .fetch_all(&mut *conn)
+    .await
+}
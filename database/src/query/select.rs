@@ -1,10 +1,12 @@
 use crate::models::query::database_details::DatabaseDetails;
+use crate::models::query::krc20_operation_status::Krc20OperationStatus;
+use crate::models::query::miner_statistics::MinerStatistics;
 use crate::models::query::table_details::TableDetails;
 use crate::models::subnetwork::Subnetwork;
 use crate::models::types::hash::Hash;
-use sqlx::{Error, Pool, Postgres, Row};
+use sqlx::{Error, PgConnection, Row};
 
-pub async fn select_database_details(pool: &Pool<Postgres>) -> Result<DatabaseDetails, Error> {
+pub async fn select_database_details(conn: &mut PgConnection) -> Result<DatabaseDetails, Error> {
     sqlx::query_as::<_, DatabaseDetails>(
         "
         SELECT 
@@ -17,11 +19,11 @@ pub async fn select_database_details(pool: &Pool<Postgres>) -> Result<DatabaseDe
             (SELECT setting::int FROM pg_settings WHERE name = 'max_connections') AS max_connections;
     ",
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *conn)
     .await
 }
 
-pub async fn select_all_table_details(pool: &Pool<Postgres>) -> Result<Vec<TableDetails>, Error> {
+pub async fn select_all_table_details(conn: &mut PgConnection) -> Result<Vec<TableDetails>, Error> {
     sqlx::query_as::<_, TableDetails>(
         "
         SELECT
@@ -36,28 +38,113 @@ pub async fn select_all_table_details(pool: &Pool<Postgres>) -> Result<Vec<Table
         ORDER BY cls.relname
     ",
     )
-    .fetch_all(pool)
+    .fetch_all(&mut *conn)
     .await
 }
 
-pub async fn select_var(key: &str, pool: &Pool<Postgres>) -> Result<String, Error> {
-    sqlx::query("SELECT value FROM vars WHERE key = $1").bind(key).fetch_one(pool).await?.try_get(0)
+pub async fn select_index_definitions(table_name: &str, conn: &mut PgConnection) -> Result<Vec<String>, Error> {
+    sqlx::query_scalar("SELECT indexdef FROM pg_indexes WHERE schemaname = current_schema() AND tablename = $1")
+        .bind(table_name)
+        .fetch_all(&mut *conn)
+        .await
 }
 
-pub async fn select_subnetworks(pool: &Pool<Postgres>) -> Result<Vec<Subnetwork>, Error> {
-    let rows = sqlx::query("SELECT id, subnetwork_id FROM subnetworks").fetch_all(pool).await?;
+pub async fn select_var(key: &str, conn: &mut PgConnection) -> Result<String, Error> {
+    sqlx::query("SELECT value FROM vars WHERE key = $1").bind(key).fetch_one(&mut *conn).await?.try_get(0)
+}
+
+pub async fn select_subnetworks(conn: &mut PgConnection) -> Result<Vec<Subnetwork>, Error> {
+    let rows = sqlx::query("SELECT id, subnetwork_id FROM subnetworks").fetch_all(&mut *conn).await?;
     let subnetworks = rows.into_iter().map(|row| Subnetwork { id: row.get("id"), subnetwork_id: row.get("subnetwork_id") }).collect();
     Ok(subnetworks)
 }
 
-pub async fn select_tx_count(block_hash: &Hash, pool: &Pool<Postgres>) -> Result<i64, Error> {
-    sqlx::query("SELECT COUNT(*) FROM blocks_transactions WHERE block_hash = $1").bind(block_hash).fetch_one(pool).await?.try_get(0)
+pub async fn select_tx_count(block_hash: &Hash, conn: &mut PgConnection) -> Result<i64, Error> {
+    sqlx::query("SELECT COUNT(*) FROM blocks_transactions WHERE block_hash = $1")
+        .bind(block_hash)
+        .fetch_one(&mut *conn)
+        .await?
+        .try_get(0)
 }
 
-pub async fn select_is_chain_block(block_hash: &Hash, pool: &Pool<Postgres>) -> Result<bool, Error> {
+pub async fn select_is_chain_block(block_hash: &Hash, conn: &mut PgConnection) -> Result<bool, Error> {
     sqlx::query("SELECT EXISTS(SELECT 1 FROM transactions_acceptances WHERE block_hash = $1)")
         .bind(block_hash)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
+        .await?
+        .try_get(0)
+}
+
+pub async fn select_address_balance_at_daa_score(address: &str, daa_score: i64, conn: &mut PgConnection) -> Result<i64, Error> {
+    sqlx::query("SELECT COALESCE(SUM(amount), 0)::BIGINT FROM address_balance_changes WHERE address = $1 AND daa_score <= $2")
+        .bind(address)
+        .bind(daa_score)
+        .fetch_one(&mut *conn)
         .await?
         .try_get(0)
 }
+
+pub async fn select_address_balance_at_block_time(address: &str, block_time: i64, conn: &mut PgConnection) -> Result<i64, Error> {
+    sqlx::query("SELECT COALESCE(SUM(amount), 0)::BIGINT FROM address_balance_changes WHERE address = $1 AND block_time <= $2")
+        .bind(address)
+        .bind(block_time)
+        .fetch_one(&mut *conn)
+        .await?
+        .try_get(0)
+}
+
+pub async fn select_miner_statistics(
+    from_block_time: i64,
+    to_block_time: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<MinerStatistics>, Error> {
+    sqlx::query_as::<_, MinerStatistics>(
+        "
+        SELECT
+            miner_address,
+            COUNT(*) AS blocks,
+            SUM(subsidy)::BIGINT AS subsidy,
+            MIN(block_time) AS first_block_time,
+            MAX(block_time) AS last_block_time
+        FROM block_rewards
+        WHERE block_time >= $1 AND block_time < $2
+        GROUP BY miner_address
+        ORDER BY blocks DESC
+    ",
+    )
+    .bind(from_block_time)
+    .bind(to_block_time)
+    .fetch_all(&mut *conn)
+    .await
+}
+
+pub async fn select_krc20_operations_by_tick(
+    tick: &str,
+    limit: i64,
+    offset: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<Krc20OperationStatus>, Error> {
+    sqlx::query_as::<_, Krc20OperationStatus>(
+        "
+        SELECT
+            k.transaction_id,
+            k.index,
+            k.operation,
+            k.tick,
+            k.amount,
+            k.to_address,
+            k.block_time,
+            ta.block_hash AS accepting_block_hash
+        FROM krc20_operations k
+        LEFT JOIN transactions_acceptances ta ON ta.transaction_id = k.transaction_id
+        WHERE k.tick = $1
+        ORDER BY k.block_time DESC
+        LIMIT $2 OFFSET $3
+    ",
+    )
+    .bind(tick)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&mut *conn)
+    .await
+}
@@ -1,13 +1,13 @@
 use log::trace;
-use sqlx::{Error, Pool, Postgres};
+use sqlx::{Error, PgConnection};
 
-pub async fn upsert_var(key: &str, value: &String, pool: &Pool<Postgres>) -> Result<u64, Error> {
+pub async fn upsert_var(key: &str, value: &String, conn: &mut PgConnection) -> Result<u64, Error> {
     trace!("Saving database var with key '{}' value: {}", key, value);
     let rows_affected =
         sqlx::query("INSERT INTO vars (key, value) VALUES ($1, $2) ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value")
             .bind(key)
             .bind(value)
-            .execute(pool)
+            .execute(&mut *conn)
             .await?
             .rows_affected();
     Ok(rows_affected)
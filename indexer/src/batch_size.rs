@@ -0,0 +1,108 @@
+use crossbeam_queue::ArrayQueue;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+const MIN_FRACTION: usize = 10;
+const GROW_FACTOR: f64 = 1.25;
+
+/// Adjusts the batch size towards a target commit latency. The target is relaxed when the input queue fills up,
+/// trading latency for throughput when falling behind
+pub struct AdaptiveBatchSize {
+    min: usize,
+    max: usize,
+    current: usize,
+    target: Duration,
+}
+
+impl AdaptiveBatchSize {
+    pub fn new(max: usize, target: Duration) -> Self {
+        let max = max.max(1);
+        Self { min: (max / MIN_FRACTION).max(1), max, current: max, target }
+    }
+
+    pub fn get(&self) -> usize {
+        self.current
+    }
+
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    /// The current size relative to the upper bound, used to scale the queue capacities and per-table insert sizes alike
+    pub fn fraction(&self) -> f64 {
+        self.current as f64 / self.max as f64
+    }
+
+    /// Changes the upper bound (e.g. when batch-scale is reloaded), the current size is kept within the new bounds
+    pub fn set_max(&mut self, max: usize) {
+        let max = max.max(1);
+        self.min = (max / MIN_FRACTION).max(1);
+        self.max = max;
+        self.current = self.current.clamp(self.min, self.max);
+    }
+
+    /// Updates the batch size from the last commit, queue_fill is the input queue utilization (0.0 - 1.0)
+    pub fn update(&mut self, commit_time: Duration, committed: usize, queue_fill: f64) {
+        let target = self.target.mul_f64(1.0 + queue_fill.clamp(0.0, 1.0));
+        let next = if commit_time > target.mul_f64(1.2) {
+            (self.current as f64 * (target.as_secs_f64() / commit_time.as_secs_f64()).max(0.5)) as usize
+        } else if commit_time < target.mul_f64(0.8) && committed >= self.current {
+            (self.current as f64 * GROW_FACTOR).ceil() as usize
+        } else {
+            self.current
+        };
+        self.current = next.clamp(self.min, self.max);
+    }
+}
+
+/// A bounded queue with a capacity adjustable at runtime, the capacity it is created with is the upper bound
+pub struct AdaptiveQueue<T> {
+    queue: ArrayQueue<T>,
+    capacity: AtomicUsize,
+}
+
+impl<T> AdaptiveQueue<T> {
+    pub fn new(max_capacity: usize) -> Self {
+        let max_capacity = max_capacity.max(1);
+        Self { queue: ArrayQueue::new(max_capacity), capacity: AtomicUsize::new(max_capacity) }
+    }
+
+    /// Fails with the value if the queue is at its current capacity
+    pub fn push(&self, value: T) -> Result<(), T> {
+        if self.queue.len() >= self.capacity() {
+            return Err(value);
+        }
+        self.queue.push(value)
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        self.queue.pop()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    pub fn max_capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+
+    /// Scales the capacity to the fraction of the upper bound. Queued values above a reduced capacity are kept
+    pub fn set_fraction(&self, fraction: f64) {
+        let capacity = (self.max_capacity() as f64 * fraction).ceil() as usize;
+        self.capacity.store(capacity.clamp(1, self.max_capacity()), Ordering::Relaxed);
+    }
+
+    /// The queue utilization (0.0 - 1.0+) relative to the current capacity
+    pub fn fill(&self) -> f64 {
+        self.len() as f64 / self.capacity() as f64
+    }
+}
@@ -3,20 +3,24 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use crate::settings::Settings;
+use crate::batch_size::AdaptiveQueue;
+use crate::blocks::p2p_blocks::P2pBlockSource;
+use crate::cache::{new_cache, resize_cache};
+use crate::settings::{runtime_changed, RuntimeSettings, Settings};
+use crate::utxo_import::p2p_initializer::resolve_p2p_address;
 use crate::web::model::metrics::{Metrics, MetricsBlock};
 use chrono::{DateTime, Utc};
-use crossbeam_queue::ArrayQueue;
 use deadpool::managed::{Object, Pool};
 use kaspa_hashes::Hash as KaspaHash;
-use kaspa_rpc_core::api::rpc::RpcApi;
 use kaspa_rpc_core::{RpcBlock, RpcTransaction};
 use log::{debug, trace, warn};
 use log::{error, info};
 use moka::sync::Cache;
-use simply_kaspa_cli::cli_args::CliDisable;
+use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable};
+use simply_kaspa_kaspad::api::KaspadApi;
+use simply_kaspa_kaspad::network::NetworkParams;
 use simply_kaspa_kaspad::pool::manager::KaspadManager;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 use tokio::time::sleep;
 
 #[derive(Debug)]
@@ -35,19 +39,25 @@ pub struct TransactionData {
 }
 
 pub struct KaspaBlocksFetcher {
+    cli_args: CliArgs,
     disable_transaction_processing: bool,
     run: Arc<AtomicBool>,
     metrics: Arc<RwLock<Metrics>>,
     kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
-    blocks_queue: Arc<ArrayQueue<BlockData>>,
-    txs_queue: Arc<ArrayQueue<TransactionData>>,
+    blocks_queue: Arc<AdaptiveQueue<BlockData>>,
+    txs_queue: Arc<AdaptiveQueue<TransactionData>>,
     low_hash: KaspaHash,
     last_sync_check: Instant,
     synced: bool,
     lag_count: i32,
     tip_hashes: HashSet<KaspaHash>,
     block_cache: Cache<KaspaHash, ()>,
+    cache_ttl: u64,
+    runtime: watch::Receiver<RuntimeSettings>,
+    network: NetworkParams,
     net_bps: u8,
+    p2p_address: Option<String>,
+    p2p: Option<P2pBlockSource>,
 }
 
 impl KaspaBlocksFetcher {
@@ -58,15 +68,19 @@ impl KaspaBlocksFetcher {
         run: Arc<AtomicBool>,
         metrics: Arc<RwLock<Metrics>>,
         kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
-        blocks_queue: Arc<ArrayQueue<BlockData>>,
-        txs_queue: Arc<ArrayQueue<TransactionData>>,
+        blocks_queue: Arc<AdaptiveQueue<BlockData>>,
+        txs_queue: Arc<AdaptiveQueue<TransactionData>>,
     ) -> KaspaBlocksFetcher {
         let ttl = settings.cli_args.cache_ttl;
-        let cache_size = settings.net_bps as u64 * ttl * 2;
-        let block_cache: Cache<KaspaHash, ()> =
-            Cache::builder().time_to_live(Duration::from_secs(ttl)).max_capacity(cache_size).build();
+        // Sized for genesis, resized as soon as the fetched blocks are past a fork
+        let net_bps = settings.network.bps(0);
+        let disable_transaction_processing = settings.cli_args.is_disabled(CliDisable::TransactionProcessing);
+        let p2p_address = (settings.cli_args.is_enabled(CliEnable::P2pBlockDownload) && !disable_transaction_processing)
+            .then(|| resolve_p2p_address(&settings.cli_args, settings.network.network_id))
+            .flatten();
         KaspaBlocksFetcher {
-            disable_transaction_processing: settings.cli_args.is_disabled(CliDisable::TransactionProcessing),
+            cli_args: settings.cli_args.clone(),
+            disable_transaction_processing,
             run,
             metrics,
             kaspad_pool,
@@ -77,28 +91,62 @@ impl KaspaBlocksFetcher {
             synced: false,
             lag_count: 0,
             tip_hashes: HashSet::new(),
-            block_cache,
-            net_bps: settings.net_bps,
+            block_cache: new_cache(ttl, net_bps as u64 * ttl * 2),
+            cache_ttl: ttl,
+            runtime: settings.runtime,
+            network: settings.network,
+            net_bps,
+            p2p_address,
+            p2p: None,
         }
     }
 
     pub async fn start(&mut self) {
         self.run.store(true, Ordering::Relaxed);
+        if let Some(p2p_address) = self.p2p_address.clone() {
+            info!("Connecting P2P for block download using {}", p2p_address);
+            match P2pBlockSource::connect(&self.cli_args, self.network.network_id, p2p_address).await {
+                Ok(p2p) => self.p2p = Some(p2p),
+                Err(e) => warn!("P2P connection failed: {e}, downloading blocks using RPC"),
+            }
+        }
         self.fetch_blocks().await;
+        if let Some(p2p) = self.p2p.take() {
+            p2p.close().await;
+        }
     }
 
     async fn fetch_blocks(&mut self) {
         let start_time = Instant::now();
 
         while self.run.load(Ordering::Relaxed) {
+            if let Some(runtime_settings) = runtime_changed(&mut self.runtime).filter(|r| r.cache_ttl != self.cache_ttl) {
+                self.cache_ttl = runtime_settings.cache_ttl;
+                self.block_cache = resize_cache(&self.block_cache, self.cache_ttl, self.net_bps as u64 * self.cache_ttl * 2);
+                debug!("Block fetcher reloaded, cache ttl: {}s", self.cache_ttl);
+            }
+            if self.synced {
+                if let Some(p2p) = self.p2p.take() {
+                    info!("Block fetcher synced, switching block download from P2P to RPC");
+                    p2p.close().await;
+                }
+            }
             let last_fetch_time = Instant::now();
+            let include_transactions = !self.disable_transaction_processing && self.p2p.is_none();
             debug!("Getting blocks with low_hash {}", self.low_hash.to_string());
             match self.kaspad_pool.get().await {
-                Ok(kaspad) => match kaspad.get_blocks(Some(self.low_hash), true, !self.disable_transaction_processing).await {
+                Ok(kaspad) => match kaspad.get_blocks(Some(self.low_hash), true, include_transactions).await {
                     Ok(response) => {
                         debug!("Received {} blocks", response.blocks.len());
                         trace!("Block hashes: \n{:#?}", response.block_hashes);
-                        let blocks = response.blocks;
+                        let mut blocks = response.blocks;
+                        if let Some(p2p) = self.p2p.as_mut() {
+                            if let Err(e) = add_transactions(p2p, &self.block_cache, &mut blocks).await {
+                                warn!("P2P block download failed: {e}, downloading blocks using RPC");
+                                self.p2p.take().unwrap().close().await;
+                                continue;
+                            }
+                        }
                         let blocks_len = blocks.len();
                         if !self.synced
                             && blocks_len < 100
@@ -113,12 +161,14 @@ impl KaspaBlocksFetcher {
                         let mut txs_len = 0;
                         if blocks_len > 1 {
                             let last_block = blocks.last().unwrap().clone();
-                            crescendo(self.net_bps, last_block.header.daa_score);
+                            self.check_fork(last_block.header.daa_score);
                             txs_len = self.handle_blocks(start_time, blocks).await;
 
                             let mut metrics = self.metrics.write().await;
                             metrics.queues.blocks = self.blocks_queue.len() as u64;
+                            metrics.queues.blocks_capacity = self.blocks_queue.capacity() as u64;
                             metrics.queues.transactions = self.txs_queue.len() as u64;
+                            metrics.queues.transactions_capacity = self.txs_queue.capacity() as u64;
                             metrics.components.block_fetcher.last_block = Some(MetricsBlock {
                                 hash: last_block.verbose_data.unwrap().hash.to_string(),
                                 timestamp: last_block.header.timestamp,
@@ -211,6 +261,23 @@ impl KaspaBlocksFetcher {
         txs_len
     }
 
+    /// Resizes the block cache when a fork changing the block rate is active, warns ahead of the next fork once synced
+    fn check_fork(&mut self, daa_score: u64) {
+        let fork = self.network.fork(daa_score);
+        if fork.bps != self.net_bps {
+            info!("Fork {} is active at DAA score {}, resizing block cache for {} bps", fork.name, daa_score, fork.bps);
+            self.net_bps = fork.bps;
+            self.block_cache = resize_cache(&self.block_cache, self.cache_ttl, self.net_bps as u64 * self.cache_ttl * 2);
+        } else if let Some(next_fork) = self.network.next_fork(daa_score).filter(|_| self.synced) {
+            let countdown = (next_fork.activation_daa_score - daa_score) / self.net_bps as u64;
+            if countdown <= 600 {
+                warn!("\x1b[33mFork {} activation in \x1b[31m~{countdown}\x1b[33m seconds\x1b[0m", next_fork.name)
+            } else if countdown <= 86400 && countdown % 10 == 0 {
+                warn!("\x1b[33mFork {} activation in \x1b[31m{}\x1b[33m minutes\x1b[0m", next_fork.name, countdown / 60)
+            }
+        }
+    }
+
     fn check_lag(&self, synced: bool, lag_count: i32, newest_block_timestamp: u64) -> i32 {
         if synced {
             let skew_seconds = Utc::now().timestamp() - newest_block_timestamp as i64 / 1000;
@@ -227,22 +294,18 @@ impl KaspaBlocksFetcher {
     }
 }
 
-fn crescendo(net_bps: u8, daa_score: u64) {
-    if net_bps == 1 {
-        let crescendo_countdown = 110165000 - daa_score as i64;
-        if crescendo_countdown <= 0 {
-            panic!("\x1b[31mCrescendo activated, shutting down for automatic bps adjustment...\x1b[0m")
-        } else if crescendo_countdown <= 600 {
-            warn!(
-                "\x1b[33mCrescendo activation in \x1b[31m~{crescendo_countdown}\x1b[33m seconds. \
-            On activation indexer will shutdown for automatic bps adjustment\x1b[0m"
-            )
-        } else if crescendo_countdown <= 86400 && crescendo_countdown % 10 == 0 {
-            warn!(
-                "\x1b[33mCrescendo activation in \x1b[31m{}\x1b[33m minutes. \
-            On activation indexer will shutdown for automatic bps adjustment\x1b[0m",
-                crescendo_countdown / 60
-            )
-        }
+/// Adds the transactions downloaded over P2P to blocks fetched over RPC (without transactions).
+/// Known blocks are skipped by handle_blocks, so they are not downloaded
+async fn add_transactions(
+    p2p: &mut P2pBlockSource,
+    block_cache: &Cache<KaspaHash, ()>,
+    blocks: &mut [RpcBlock],
+) -> Result<(), String> {
+    let hashes: Vec<KaspaHash> = blocks.iter().map(|b| b.header.hash).filter(|h| !block_cache.contains_key(h)).collect();
+    let mut transactions = p2p.get_transactions(&hashes).await?;
+    for block in blocks.iter_mut().filter(|b| !block_cache.contains_key(&b.header.hash)) {
+        block.transactions =
+            transactions.remove(&block.header.hash).ok_or_else(|| format!("Block {} was not received", block.header.hash))?;
     }
+    Ok(())
 }
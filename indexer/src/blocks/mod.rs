@@ -1,2 +1,3 @@
 pub mod fetch_blocks;
+pub mod p2p_blocks;
 pub mod process_blocks;
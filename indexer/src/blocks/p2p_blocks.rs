@@ -0,0 +1,125 @@
+use crate::utxo_import::p2p_initializer::P2pInitializer;
+use kaspa_addresses::Prefix;
+use kaspa_consensus_core::block::Block;
+use kaspa_consensus_core::config::params::MAINNET_PARAMS;
+use kaspa_consensus_core::hashing::tx::hash;
+use kaspa_consensus_core::mass::MassCalculator;
+use kaspa_hashes::Hash as KaspaHash;
+use kaspa_p2p_lib::pb::kaspad_message::Payload;
+use kaspa_p2p_lib::pb::{AddressesMessage, KaspadMessage, PongMessage, RequestIbdBlocksMessage};
+use kaspa_p2p_lib::{make_message, Adaptor, Hub, PeerKey};
+use kaspa_rpc_core::{RpcTransaction, RpcTransactionOutputVerboseData, RpcTransactionVerboseData};
+use kaspa_txscript::extract_script_pub_key_address;
+use kaspa_txscript::script_class::ScriptClass;
+use kaspa_wrpc_client::prelude::NetworkId;
+use log::{debug, trace};
+use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_kaspad::network::consensus_params;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::Receiver;
+use tokio::time::timeout;
+
+const P2P_TIMEOUT: Duration = Duration::from_secs(30);
+/// Blocks requested per RequestIbdBlocks message
+const P2P_BLOCKS_CHUNK: usize = 100;
+
+/// Downloads block bodies from a kaspad peer, the transaction verbose fields are computed locally
+pub struct P2pBlockSource {
+    adaptor: Arc<Adaptor>,
+    peer_key: PeerKey,
+    receiver: Receiver<KaspadMessage>,
+    prefix: Prefix,
+    mass_calculator: MassCalculator,
+}
+
+impl P2pBlockSource {
+    pub async fn connect(cli_args: &CliArgs, network_id: NetworkId, address: String) -> Result<P2pBlockSource, String> {
+        let (sender, receiver) = mpsc::channel(10000);
+        let initializer = Arc::new(P2pInitializer::new(cli_args.clone(), sender));
+        let adaptor = Adaptor::client_only(Hub::new(), initializer, Default::default());
+        let peer_key = adaptor.connect_peer(address).await.map_err(|e| e.to_string())?;
+        let mass_calculator = p2p_mass_calculator(network_id);
+        Ok(P2pBlockSource { adaptor, peer_key, receiver, prefix: Prefix::from(network_id), mass_calculator })
+    }
+
+    pub async fn close(self) {
+        self.adaptor.terminate_all_peers().await;
+    }
+
+    /// Downloads the blocks and returns their transactions (with verbose data) by block hash
+    pub async fn get_transactions(&mut self, hashes: &[KaspaHash]) -> Result<HashMap<KaspaHash, Vec<RpcTransaction>>, String> {
+        let mut transactions = HashMap::with_capacity(hashes.len());
+        for chunk in hashes.chunks(P2P_BLOCKS_CHUNK) {
+            for block in self.get_blocks(chunk).await? {
+                transactions.insert(block.hash(), map_transactions(&block, self.prefix, &self.mass_calculator));
+            }
+        }
+        Ok(transactions)
+    }
+
+    async fn get_blocks(&mut self, hashes: &[KaspaHash]) -> Result<Vec<Block>, String> {
+        let request = RequestIbdBlocksMessage { hashes: hashes.iter().map(|h| (*h).into()).collect() };
+        self.send(make_message!(Payload::RequestIbdBlocks, request)).await?;
+        let mut blocks = Vec::with_capacity(hashes.len());
+        while blocks.len() < hashes.len() {
+            match timeout(P2P_TIMEOUT, self.receiver.recv()).await {
+                Ok(Some(msg)) => match msg.payload {
+                    Some(Payload::IbdBlock(msg)) => blocks.push(Block::try_from(msg).map_err(|e| e.to_string())?),
+                    Some(Payload::RequestAddresses(_)) => {
+                        debug!("Got addresses request, responding with empty list");
+                        self.send(make_message!(Payload::Addresses, AddressesMessage { address_list: vec![] })).await?;
+                    }
+                    Some(Payload::Ping(msg)) => {
+                        debug!("Got ping (nonce={}), responding with pong", msg.nonce);
+                        self.send(make_message!(Payload::Pong, PongMessage { nonce: msg.nonce })).await?;
+                    }
+                    Some(msg) => trace!("Ignoring message: {:?}", msg),
+                    None => trace!("Got message with empty payload"),
+                },
+                Ok(None) => return Err("P2P channel unexpectedly closed".to_string()),
+                Err(_) => return Err(format!("Peer timed out after {} seconds", P2P_TIMEOUT.as_secs())),
+            }
+        }
+        Ok(blocks)
+    }
+
+    async fn send(&self, msg: KaspadMessage) -> Result<(), String> {
+        self.adaptor.send(self.peer_key, msg).await.map_err(|e| e.to_string())
+    }
+}
+
+/// The mass calculator of the network, the mass parameters are the same for networks without consensus params
+pub fn p2p_mass_calculator(network_id: NetworkId) -> MassCalculator {
+    MassCalculator::new_with_consensus_params(&consensus_params(network_id).unwrap_or(MAINNET_PARAMS))
+}
+
+/// Computes the verbose fields which kaspad adds over RPC, including the compute mass.
+/// Outputs with a non-standard script get no verbose data as they have no address
+pub fn map_transactions(block: &Block, prefix: Prefix, mass_calculator: &MassCalculator) -> Vec<RpcTransaction> {
+    block
+        .transactions
+        .iter()
+        .map(|tx| {
+            let mut rpc_transaction = RpcTransaction::from(tx);
+            rpc_transaction.verbose_data = Some(RpcTransactionVerboseData {
+                transaction_id: tx.id(),
+                hash: hash(tx, false),
+                compute_mass: mass_calculator.calc_non_contextual_masses(tx).compute_mass,
+                block_hash: block.hash(),
+                block_time: block.header.timestamp,
+            });
+            for (rpc_output, output) in rpc_transaction.outputs.iter_mut().zip(tx.outputs.iter()) {
+                rpc_output.verbose_data = extract_script_pub_key_address(&output.script_public_key, prefix).ok().map(|address| {
+                    RpcTransactionOutputVerboseData {
+                        script_public_key_type: ScriptClass::from_script(&output.script_public_key),
+                        script_public_key_address: address,
+                    }
+                });
+            }
+            rpc_transaction
+        })
+        .collect()
+}
@@ -3,14 +3,17 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use crate::batch_size::{AdaptiveBatchSize, AdaptiveQueue};
 use crate::blocks::fetch_blocks::BlockData;
 use crate::checkpoint::{CheckpointBlock, CheckpointOrigin};
-use crate::settings::Settings;
+use crate::hooks::CommittedBatch;
+use crate::retry::retry_db;
+use crate::settings::{runtime_changed, Settings};
 use crate::web::model::metrics::Metrics;
 use chrono::DateTime;
 use crossbeam_queue::ArrayQueue;
 use log::{debug, info, warn};
-use simply_kaspa_cli::cli_args::CliDisable;
+use simply_kaspa_cli::cli_args::{CliDisable, CliEnable};
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_database::models::block::Block;
 use simply_kaspa_database::models::block_parent::BlockParent;
@@ -24,18 +27,21 @@ pub async fn process_blocks(
     run: Arc<AtomicBool>,
     metrics: Arc<RwLock<Metrics>>,
     start_vcp: Arc<AtomicBool>,
-    rpc_blocks_queue: Arc<ArrayQueue<BlockData>>,
+    rpc_blocks_queue: Arc<AdaptiveQueue<BlockData>>,
     checkpoint_queue: Arc<ArrayQueue<CheckpointBlock>>,
     database: KaspaDbClient,
     mapper: KaspaDbMapper,
 ) {
     const NOOP_DELETES_BEFORE_VCP: i32 = 10;
-    let batch_scale = settings.cli_args.batch_scale;
-    let batch_size = (800f64 * batch_scale) as usize;
+    let mut runtime = settings.runtime.clone();
+    let mut batch_scale = settings.cli_args.batch_scale;
+    let mut batch_size =
+        AdaptiveBatchSize::new((800f64 * batch_scale) as usize, Duration::from_millis(settings.cli_args.batch_commit_target as u64));
     let disable_virtual_chain_processing = settings.cli_args.is_disabled(CliDisable::VirtualChainProcessing);
     let disable_vcp_wait_for_sync = settings.disable_vcp_wait_for_sync;
     let disable_blocks = settings.cli_args.is_disabled(CliDisable::BlocksTable);
     let disable_block_relations = settings.cli_args.is_disabled(CliDisable::BlockParentTable);
+    let enable_balance_changes = settings.cli_args.is_enabled(CliEnable::AddressBalanceChangesTable);
     let mut first_block = true;
     let mut vcp_started = false;
     let mut blocks = vec![];
@@ -43,10 +49,22 @@ pub async fn process_blocks(
     let mut checkpoint_blocks = vec![];
     let mut last_commit_time = Instant::now();
     let mut noop_delete_count = 0;
+    let mut synced = false;
 
-    while run.load(Ordering::Relaxed) {
-        if let Some(block_data) = rpc_blocks_queue.pop() {
-            let synced = block_data.synced;
+    loop {
+        if let Some(runtime_settings) = runtime_changed(&mut runtime) {
+            batch_scale = runtime_settings.batch_scale;
+            batch_size.set_max((800f64 * batch_scale) as usize);
+            debug!("Block processor reloaded, max batch size: {}", batch_size.max());
+        }
+        let stopping = !run.load(Ordering::Relaxed);
+        let block_data = rpc_blocks_queue.pop();
+        let idle = block_data.is_none();
+        if let Some(block_data) = block_data {
+            if block_data.synced && !synced {
+                database.set_bulk_mode(false);
+            }
+            synced = block_data.synced;
             let block = mapper.map_block(&block_data.block);
             if !disable_block_relations {
                 blocks_parents.extend(mapper.map_block_parents(&block_data.block));
@@ -61,111 +79,180 @@ pub async fn process_blocks(
             if !disable_blocks {
                 blocks.push(block);
             }
+        }
 
-            if checkpoint_blocks.len() >= batch_size
-                || (!checkpoint_blocks.is_empty() && Instant::now().duration_since(last_commit_time).as_secs() > 2)
-            {
-                let start_commit_time = Instant::now();
-                debug!("Committing {} blocks ({} parents)", blocks.len(), blocks_parents.len());
-                let last_checkpoint_block = checkpoint_blocks.last().unwrap().clone();
-                let blocks_inserted = if !disable_blocks { insert_blocks(batch_scale, blocks, database.clone()).await } else { 0 };
-                let block_parents_inserted = if !disable_block_relations {
-                    insert_block_parents(batch_scale, blocks_parents, database.clone()).await
-                } else {
-                    0
-                };
-                let last_block_datetime = DateTime::from_timestamp_millis(last_checkpoint_block.timestamp as i64).unwrap();
+        if checkpoint_blocks.len() >= batch_size.get()
+            || (!checkpoint_blocks.is_empty() && ((idle && stopping) || Instant::now().duration_since(last_commit_time).as_secs() > 2))
+        {
+            let start_commit_time = Instant::now();
+            let insert_scale = batch_scale * batch_size.fraction();
+            debug!("Committing {} blocks ({} parents)", blocks.len(), blocks_parents.len());
+            let last_checkpoint_block = checkpoint_blocks.last().unwrap().clone();
+            let blocks_inserted =
+                if !disable_blocks { insert_blocks(insert_scale, blocks, metrics.clone(), database.clone()).await } else { 0 };
+            let block_parents_inserted = if !disable_block_relations {
+                insert_block_parents(insert_scale, blocks_parents, metrics.clone(), database.clone()).await
+            } else {
+                0
+            };
+            let last_block_datetime = DateTime::from_timestamp_millis(last_checkpoint_block.timestamp as i64).unwrap();
+            if enable_balance_changes && blocks_inserted > 0 {
+                // Chain blocks committed after the VCP accepted their transactions get their balance changes now
+                let block_hashes = checkpoint_blocks.iter().map(|c| c.hash.clone()).collect();
+                let balance_changes_inserted =
+                    insert_balance_changes(insert_scale, block_hashes, metrics.clone(), database.clone()).await;
+                if balance_changes_inserted > 0 {
+                    debug!("Committed {} address balance changes of already accepted txs", balance_changes_inserted);
+                }
+            }
 
-                if !vcp_started && !disable_virtual_chain_processing {
-                    let tas_deleted = delete_transaction_acceptances(
-                        batch_scale,
-                        // Skip deleting acceptance for first block hash, as it's not re-added by vcp:
-                        checkpoint_blocks.iter().skip(first_block as usize).map(|c| c.hash.clone()).collect(),
-                        database.clone(),
-                    )
-                    .await;
-                    first_block = false;
-                    if (disable_vcp_wait_for_sync || synced) && tas_deleted == 0 {
-                        noop_delete_count += 1;
-                    } else {
-                        noop_delete_count = 0;
-                    }
-                    let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
-                    let bps = checkpoint_blocks.len() as f64 / commit_time as f64 * 1000f64;
-                    info!(
-                        "Committed {} new blocks in {}ms ({:.1} bps, {} bp) [clr {} ta]. Last block: {}",
-                        blocks_inserted, commit_time, bps, block_parents_inserted, tas_deleted, last_block_datetime
-                    );
-                    if noop_delete_count >= NOOP_DELETES_BEFORE_VCP {
-                        info!("Notifying virtual chain processor");
-                        start_vcp.store(true, Ordering::Relaxed);
-                        vcp_started = true;
-                    }
-                } else if blocks_inserted > 0 || block_parents_inserted > 0 {
-                    let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
-                    let bps = checkpoint_blocks.len() as f64 / commit_time as f64 * 1000f64;
-                    info!(
-                        "Committed {} new blocks in {}ms ({:.1} bps, {} bp). Last block: {}",
-                        blocks_inserted, commit_time, bps, block_parents_inserted, last_block_datetime
-                    );
+            if !vcp_started && !disable_virtual_chain_processing {
+                let tas_deleted = delete_transaction_acceptances(
+                    insert_scale,
+                    enable_balance_changes,
+                    // Skip deleting acceptance for first block hash, as it's not re-added by vcp:
+                    checkpoint_blocks.iter().skip(first_block as usize).map(|c| c.hash.clone()).collect(),
+                    metrics.clone(),
+                    database.clone(),
+                )
+                .await;
+                first_block = false;
+                if (disable_vcp_wait_for_sync || synced) && tas_deleted == 0 {
+                    noop_delete_count += 1;
+                } else {
+                    noop_delete_count = 0;
                 }
+                let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
+                let bps = checkpoint_blocks.len() as f64 / commit_time as f64 * 1000f64;
+                info!(
+                    "Committed {} new blocks in {}ms ({:.1} bps, {} bp) [clr {} ta]. Last block: {}",
+                    blocks_inserted, commit_time, bps, block_parents_inserted, tas_deleted, last_block_datetime
+                );
+                if noop_delete_count >= NOOP_DELETES_BEFORE_VCP {
+                    info!("Notifying virtual chain processor");
+                    start_vcp.store(true, Ordering::Relaxed);
+                    vcp_started = true;
+                }
+            } else if blocks_inserted > 0 || block_parents_inserted > 0 {
+                let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
+                let bps = checkpoint_blocks.len() as f64 / commit_time as f64 * 1000f64;
+                info!(
+                    "Committed {} new blocks in {}ms ({:.1} bps, {} bp). Last block: {}",
+                    blocks_inserted, commit_time, bps, block_parents_inserted, last_block_datetime
+                );
+            }
 
-                let mut metrics = metrics.write().await;
-                metrics.components.block_processor.last_block = Some(last_checkpoint_block.into());
-                drop(metrics);
+            batch_size.update(start_commit_time.elapsed(), checkpoint_blocks.len(), rpc_blocks_queue.fill());
+            rpc_blocks_queue.set_fraction(batch_size.fraction());
 
-                for checkpoint_block in checkpoint_blocks {
-                    while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
-                        warn!("Checkpoint queue is full");
-                        sleep(Duration::from_secs(1)).await;
-                    }
+            let mut metrics = metrics.write().await;
+            metrics.components.block_processor.last_block = Some(last_checkpoint_block.into());
+            metrics.components.block_processor.batch_size = Some(batch_size.get() as u64);
+            drop(metrics);
+
+            if !settings.hooks.is_empty() {
+                settings.hooks.notify(CommittedBatch::Blocks {
+                    blocks: checkpoint_blocks.clone(),
+                    rows: blocks_inserted + block_parents_inserted,
+                    commit_time: start_commit_time.elapsed(),
+                });
+            }
+
+            for checkpoint_block in checkpoint_blocks {
+                while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
+                    warn!("Checkpoint queue is full");
+                    sleep(Duration::from_secs(1)).await;
                 }
-                blocks = vec![];
-                checkpoint_blocks = vec![];
-                blocks_parents = vec![];
-                last_commit_time = Instant::now();
             }
-        } else {
+            blocks = vec![];
+            checkpoint_blocks = vec![];
+            blocks_parents = vec![];
+            last_commit_time = Instant::now();
+        } else if idle {
+            if stopping {
+                break;
+            }
             sleep(Duration::from_millis(100)).await;
         }
     }
 }
 
-async fn insert_blocks(batch_scale: f64, values: Vec<Block>, database: KaspaDbClient) -> u64 {
+async fn insert_blocks(batch_scale: f64, values: Vec<Block>, metrics: Arc<RwLock<Metrics>>, database: KaspaDbClient) -> u64 {
     let batch_size = min((200f64 * batch_scale) as usize, 3500); // 2^16 / fields
     let key = "blocks";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected += database.insert_blocks(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+        rows_affected += retry_db(key, &metrics, || database.insert_blocks(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
     debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
 }
 
-async fn insert_block_parents(batch_scale: f64, values: Vec<BlockParent>, database: KaspaDbClient) -> u64 {
+async fn insert_block_parents(
+    batch_scale: f64,
+    values: Vec<BlockParent>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
     let batch_size = min((400f64 * batch_scale) as usize, 10000); // 2^16 / fields
     let key = "block_parents";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected += database.insert_block_parents(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+        rows_affected += retry_db(key, &metrics, || database.insert_block_parents(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
     debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
 }
 
-async fn delete_transaction_acceptances(batch_scale: f64, block_hashes: Vec<SqlHash>, db: KaspaDbClient) -> u64 {
+async fn insert_balance_changes(
+    batch_scale: f64,
+    values: Vec<SqlHash>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
+    let batch_size = min((500f64 * batch_scale) as usize, 30000);
+    let key = "address_balance_changes";
+    let start_time = Instant::now();
+    debug!("Processing {} blocks for {}", values.len(), key);
+    let mut rows_affected = 0;
+    for batch_values in values.chunks(batch_size) {
+        rows_affected += retry_db(key, &metrics, || database.insert_address_balance_changes(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+    }
+    debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
+    rows_affected
+}
+
+async fn delete_transaction_acceptances(
+    batch_scale: f64,
+    enable_balance_changes: bool,
+    block_hashes: Vec<SqlHash>,
+    metrics: Arc<RwLock<Metrics>>,
+    db: KaspaDbClient,
+) -> u64 {
     let batch_size = min((100f64 * batch_scale) as usize, 50000); // 2^16 / fields
     let key = "transaction_acceptances";
     let start_time = Instant::now();
     debug!("Clearing {} {}", block_hashes.len(), key);
     let mut rows_affected = 0;
     for batch_values in block_hashes.chunks(batch_size) {
-        rows_affected +=
-            db.delete_transaction_acceptances(batch_values).await.unwrap_or_else(|e| panic!("Deleting {key} FAILED: {e}"));
+        if enable_balance_changes {
+            retry_db("address_balance_changes", &metrics, || db.delete_address_balance_changes(batch_values))
+                .await
+                .unwrap_or_else(|e| panic!("Deleting address_balance_changes FAILED: {e}"));
+        }
+        rows_affected += retry_db(key, &metrics, || db.delete_transaction_acceptances(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Deleting {key} FAILED: {e}"));
     }
     debug!("Cleared {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
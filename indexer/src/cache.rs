@@ -0,0 +1,25 @@
+use moka::sync::Cache;
+use std::hash::Hash;
+use std::time::Duration;
+
+/// Creates an in-memory de-duplication cache, entries expire after ttl seconds
+pub fn new_cache<K, V>(ttl: u64, capacity: u64) -> Cache<K, V>
+where
+    K: Hash + Eq + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    Cache::builder().time_to_live(Duration::from_secs(ttl)).max_capacity(capacity).build()
+}
+
+/// Creates a cache with a new capacity holding the entries of the supplied cache (their ttl restarts)
+pub fn resize_cache<K, V>(cache: &Cache<K, V>, ttl: u64, capacity: u64) -> Cache<K, V>
+where
+    K: Hash + Eq + Clone + Send + Sync + 'static,
+    V: Clone + Send + Sync + 'static,
+{
+    let resized = new_cache(ttl, capacity);
+    for (key, value) in cache.iter() {
+        resized.insert(key.as_ref().clone(), value);
+    }
+    resized
+}
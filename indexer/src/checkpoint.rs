@@ -33,6 +33,7 @@ pub struct CheckpointBlock {
 pub async fn process_checkpoints(
     settings: Settings,
     run: Arc<AtomicBool>,
+    run_checkpoints: Arc<AtomicBool>,
     metrics: Arc<RwLock<Metrics>>,
     checkpoint_queue: Arc<ArrayQueue<CheckpointBlock>>,
     database: KaspaDbClient,
@@ -56,14 +57,16 @@ pub async fn process_checkpoints(
     let mut cp_ok_blocks: bool = false;
     let mut cp_ok_txs: bool = false;
 
-    while run.load(Ordering::Relaxed) {
+    while run_checkpoints.load(Ordering::Relaxed) || !checkpoint_queue.is_empty() {
+        // When stopping, checkpoints are saved as soon as possible to retain as much progress as possible
+        let stopping = !run.load(Ordering::Relaxed);
         if let Some(checkpoint_block) = checkpoint_queue.pop() {
             match checkpoint_block.origin {
                 CheckpointOrigin::Blocks => {
                     last_block_blue_score = checkpoint_block.blue_score;
                     if disable_virtual_chain_processing {
                         if checkpoint_candidate.is_none()
-                            && Instant::now().duration_since(checkpoint_last_saved).as_secs() > CHECKPOINT_SAVE_INTERVAL
+                            && (stopping || Instant::now().duration_since(checkpoint_last_saved).as_secs() > CHECKPOINT_SAVE_INTERVAL)
                         {
                             debug!("Selected block_checkpoint candidate {}", hex::encode(checkpoint_block.hash.as_bytes()));
                             checkpoint_candidate = Some(checkpoint_block);
@@ -81,7 +84,7 @@ pub async fn process_checkpoints(
                 }
                 CheckpointOrigin::Vcp => {
                     if checkpoint_candidate.is_none()
-                        && Instant::now().duration_since(checkpoint_last_saved).as_secs() > CHECKPOINT_SAVE_INTERVAL
+                        && (stopping || Instant::now().duration_since(checkpoint_last_saved).as_secs() > CHECKPOINT_SAVE_INTERVAL)
                     {
                         debug!("Selected block_checkpoint candidate {}", hex::encode(checkpoint_block.hash.as_bytes()));
                         checkpoint_candidate = Some(checkpoint_block);
@@ -94,6 +97,7 @@ pub async fn process_checkpoints(
             }
             if let Some(checkpoint) = checkpoint_candidate {
                 let checkpoint_string = hex::encode(checkpoint.hash.as_bytes());
+                let net_bps = settings.network.bps(checkpoint.daa_score) as u64;
                 if !cp_ok_blocks && blocks_processed.contains(&checkpoint.hash) {
                     cp_ok_blocks = true;
                 }
@@ -114,9 +118,9 @@ pub async fn process_checkpoints(
                     warn!("Still unable to save block_checkpoint {}", checkpoint_string);
                     checkpoint_last_warned = Instant::now();
                     checkpoint_candidate = Some(checkpoint);
-                } else if last_block_blue_score > checkpoint.blue_score + CHECKPOINT_FAILED_TIMEOUT * settings.net_bps as u64
+                } else if last_block_blue_score > checkpoint.blue_score + CHECKPOINT_FAILED_TIMEOUT * net_bps
                     && (disable_transaction_processing
-                        || last_tx_blue_score > checkpoint.blue_score + CHECKPOINT_FAILED_TIMEOUT * settings.net_bps as u64)
+                        || last_tx_blue_score > checkpoint.blue_score + CHECKPOINT_FAILED_TIMEOUT * net_bps)
                 {
                     error!("Failed to synchronize on block_checkpoint {}", checkpoint_string);
                     checkpoint_last_saved = Instant::now(); // Need to reset this to avoid a loop
@@ -129,4 +133,8 @@ pub async fn process_checkpoints(
             sleep(Duration::from_millis(100)).await;
         }
     }
+    if let Some(checkpoint) = checkpoint_candidate {
+        warn!("Unable to save final block_checkpoint {}, the previous checkpoint is kept", hex::encode(checkpoint.hash.as_bytes()));
+    }
+    info!("Checkpoint processor stopped");
 }
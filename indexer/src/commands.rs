@@ -0,0 +1,126 @@
+use crate::kaspad_sync::get_sync_progress;
+use crate::vars::{load_block_checkpoint, save_checkpoint, VAR_KEY_BLOCK_CHECKPOINT, VAR_KEY_LEGACY_CHECKPOINT};
+use chrono::{DateTime, Utc};
+use deadpool::managed::Pool;
+use humantime::format_duration;
+use kaspa_hashes::Hash as KaspaHash;
+use log::{info, warn};
+use simply_kaspa_cli::cli_args::CliCheckpointAction;
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_kaspad::pool::manager::KaspadManager;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Upgrades the schema (or creates it if missing), with dry_run the pending upgrade scripts are only printed
+pub async fn migrate(database: &KaspaDbClient, dry_run: bool) {
+    if !dry_run {
+        database.create_schema(true).await.expect("Unable to upgrade schema");
+        return;
+    }
+    match database.schema_version().await {
+        None => println!("No schema found, schema v{} would be applied", KaspaDbClient::SCHEMA_VERSION),
+        Some(version) if version > KaspaDbClient::SCHEMA_VERSION => panic!("Found newer & unsupported schema v{version}"),
+        Some(version) => {
+            let migrations = KaspaDbClient::pending_migrations(version);
+            if migrations.is_empty() {
+                println!("Schema v{version} is up to date");
+            }
+            for (from_version, ddl) in migrations {
+                println!("-- Upgrade from schema v{from_version} to v{}\n{ddl}", from_version + 1);
+            }
+        }
+    }
+}
+
+/// Returns true if the schema is up to date
+pub async fn verify(database: &KaspaDbClient) -> bool {
+    match database.schema_version().await {
+        Some(version) if version == KaspaDbClient::SCHEMA_VERSION => {
+            println!("Schema v{version} is up to date");
+            true
+        }
+        Some(version) => {
+            println!("Found schema v{version}, v{} is required", KaspaDbClient::SCHEMA_VERSION);
+            false
+        }
+        None => {
+            println!("No schema found, v{} is required", KaspaDbClient::SCHEMA_VERSION);
+            false
+        }
+    }
+}
+
+/// The confirmation is enforced by the argument parser, before connecting
+pub async fn init(database: &KaspaDbClient) {
+    warn!("Initializing database");
+    database.drop_schema().await.expect("Unable to drop schema");
+    database.create_schema(false).await.expect("Unable to create schema");
+}
+
+pub async fn checkpoint(action: CliCheckpointAction, database: &KaspaDbClient, kaspad_pool: &Pool<KaspadManager>) {
+    match action {
+        CliCheckpointAction::Show => match load_block_checkpoint(database).await {
+            Ok(block_hash) => println!("{block_hash}"),
+            Err(_) => println!("No checkpoint found"),
+        },
+        CliCheckpointAction::Set { hash } => {
+            let block_hash = KaspaHash::from_str(&hash).expect("Supplied block hash is invalid");
+            let kaspad = kaspad_pool.manager().connect_unsynced().await.expect("Kaspad connection FAILED");
+            let block = kaspad.get_block(block_hash, false).await.unwrap_or_else(|e| panic!("Block {block_hash} not found: {e}"));
+            save_checkpoint(&block_hash.to_string(), database).await.expect("Unable to save checkpoint");
+            info!("Saved checkpoint {} (DAA score {})", block_hash, block.header.daa_score);
+        }
+        CliCheckpointAction::Clear => {
+            let mut rows_removed = database.delete_var(VAR_KEY_BLOCK_CHECKPOINT).await.expect("Unable to clear checkpoint");
+            rows_removed += database.delete_var(VAR_KEY_LEGACY_CHECKPOINT).await.expect("Unable to clear checkpoint");
+            if rows_removed > 0 {
+                info!("Cleared checkpoint");
+            } else {
+                info!("No checkpoint found");
+            }
+        }
+    }
+}
+
+pub async fn status(database: &KaspaDbClient, kaspad_pool: &Pool<KaspadManager>) {
+    match database.schema_version().await {
+        Some(version) if version == KaspaDbClient::SCHEMA_VERSION => println!("Schema:            v{version}"),
+        Some(version) => println!("Schema:            v{version} (v{} is required)", KaspaDbClient::SCHEMA_VERSION),
+        None => println!("Schema:            not found"),
+    }
+    let checkpoint = load_block_checkpoint(database).await.ok();
+    println!("Checkpoint:        {}", checkpoint.as_deref().unwrap_or("not found"));
+
+    let kaspad = match kaspad_pool.manager().connect_unsynced().await {
+        Ok(kaspad) => kaspad,
+        Err(e) => {
+            println!("Kaspad:            connection failed: {e}");
+            return;
+        }
+    };
+    match get_sync_progress(&kaspad).await {
+        Ok(progress) => {
+            let state =
+                if progress.is_synced { "synced".to_string() } else { format!("syncing ({:.1}%)", progress.progress_percent()) };
+            println!("Kaspad:            {state}, {} behind", format_duration(progress.lag));
+            println!("Kaspad DAA score:  {}", progress.virtual_daa_score);
+            println!("Kaspad blocks:     {}/{}", progress.block_count, progress.header_count);
+            if let Some(checkpoint) = checkpoint.and_then(|c| KaspaHash::from_str(&c).ok()) {
+                match kaspad.get_block(checkpoint, false).await {
+                    Ok(block) => {
+                        let lag_ms = (Utc::now().timestamp_millis() as u64).saturating_sub(block.header.timestamp);
+                        println!(
+                            "Indexer:           DAA score {} ({} behind kaspad), checkpoint from {} ({} ago)",
+                            block.header.daa_score,
+                            progress.virtual_daa_score.saturating_sub(block.header.daa_score),
+                            DateTime::from_timestamp_millis(block.header.timestamp as i64).unwrap_or_default(),
+                            format_duration(Duration::from_secs(lag_ms / 1000))
+                        );
+                    }
+                    Err(_) => println!("Indexer:           checkpoint block not found in kaspad"),
+                }
+            }
+        }
+        Err(e) => println!("Kaspad:            failed getting sync status: {e}"),
+    }
+}
@@ -0,0 +1,41 @@
+use crate::checkpoint::CheckpointBlock;
+use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A batch committed to the database by one of the processors
+#[derive(Clone)]
+pub enum CommittedBatch {
+    /// Blocks (and block parents) committed by the block processor
+    Blocks { blocks: Vec<CheckpointBlock>, rows: u64, commit_time: Duration },
+    /// Transactions of the blocks committed by the transaction processor. Includes only transactions not seen before
+    Transactions { blocks: Vec<CheckpointBlock>, transaction_ids: Vec<SqlHash>, rows: u64, commit_time: Duration },
+    /// Chain blocks added and removed (reorged) by the virtual chain processor, up to the last accepting block
+    VirtualChain { added: Vec<SqlHash>, removed: Vec<SqlHash>, last_block: CheckpointBlock, commit_time: Duration },
+}
+
+pub type BatchCallback = Arc<dyn Fn(&CommittedBatch) + Send + Sync>;
+
+/// Callbacks invoked after every committed batch, see IndexerBuilder::on_batch_committed.
+/// Callbacks are called from the processor tasks, so they should return quickly (e.g. by sending to a channel)
+#[derive(Clone, Default)]
+pub struct BatchHooks {
+    callbacks: Vec<BatchCallback>,
+}
+
+impl BatchHooks {
+    pub fn add(&mut self, callback: BatchCallback) {
+        self.callbacks.push(callback);
+    }
+
+    /// Processors skip collecting batch details when there are no callbacks
+    pub fn is_empty(&self) -> bool {
+        self.callbacks.is_empty()
+    }
+
+    pub fn notify(&self, batch: CommittedBatch) {
+        for callback in &self.callbacks {
+            callback(&batch);
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use crate::hooks::CommittedBatch;
+use crate::processing::process;
+use crate::settings::{RuntimeSettings, Settings};
+use crate::web::model::metrics::Metrics;
+use deadpool::managed::Pool;
+use kaspa_wrpc_client::prelude::NetworkId;
+use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable, CliField};
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_database::connect::DbConnectOptions;
+use simply_kaspa_database::layout::{AddressMapping, ReadLayout};
+use simply_kaspa_kaspad::fixture::{FixtureRecorder, KaspadFixture, ReplayClient};
+use simply_kaspa_kaspad::pool::manager::KaspadManager;
+use simply_kaspa_kaspad::pool::protocol::KaspadProtocol;
+use simply_kaspa_kaspad::pool::tls::{load_tls_config, TlsTunnels};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{watch, RwLock};
+use tokio::task;
+use tokio::task::JoinHandle;
+
+/// Wires the indexer components, for running the indexer embedded in another service.
+/// The schema is expected to be up to date, see KaspaDbClient::create_schema and validation::validate
+pub struct IndexerBuilder {
+    pub(crate) settings: Settings,
+    pub(crate) database: KaspaDbClient,
+    pub(crate) kaspad_pool: Pool<KaspadManager>,
+    pub(crate) web_server: bool,
+    run: Arc<AtomicBool>,
+}
+
+impl IndexerBuilder {
+    /// See Settings::new, CliArgs::default has the defaults of all options
+    pub fn new(settings: Settings, database: KaspaDbClient, kaspad_pool: Pool<KaspadManager>) -> IndexerBuilder {
+        IndexerBuilder { settings, database, kaspad_pool, web_server: true, run: Arc::new(AtomicBool::new(true)) }
+    }
+
+    /// Settings changed while running (see reload::SettingsReloader), by default the settings stay as supplied
+    pub fn runtime(mut self, runtime: watch::Receiver<RuntimeSettings>) -> IndexerBuilder {
+        self.settings.runtime = runtime;
+        self
+    }
+
+    /// Processes the transactions of blocks, enabled unless disabled in the settings
+    pub fn transaction_processor(mut self, enabled: bool) -> IndexerBuilder {
+        self.settings.cli_args.set_disabled(CliDisable::TransactionProcessing, !enabled);
+        self
+    }
+
+    /// Processes the virtual chain (chain blocks and transaction acceptance), enabled unless disabled in the settings
+    pub fn virtual_chain_processor(mut self, enabled: bool) -> IndexerBuilder {
+        self.settings.cli_args.set_disabled(CliDisable::VirtualChainProcessing, !enabled);
+        self
+    }
+
+    /// Processes the mempool, disabled unless enabled in the settings
+    pub fn mempool_processor(mut self, enabled: bool) -> IndexerBuilder {
+        self.settings.cli_args.set_enabled(CliEnable::MempoolProcessing, enabled);
+        self
+    }
+
+    /// Imports the UTXO set when starting without a checkpoint, enabled unless disabled in the settings
+    pub fn initial_utxo_import(mut self, enabled: bool) -> IndexerBuilder {
+        self.settings.cli_args.set_disabled(CliDisable::InitialUtxoImport, !enabled);
+        self
+    }
+
+    /// Serves the REST API (health, metrics) on --listen, enabled by default
+    pub fn web_server(mut self, enabled: bool) -> IndexerBuilder {
+        self.web_server = enabled;
+        self
+    }
+
+    /// Shares the run flag, clearing it shuts the indexer down (e.g. from a signal handler)
+    pub fn run(mut self, run: Arc<AtomicBool>) -> IndexerBuilder {
+        self.run = run;
+        self
+    }
+
+    /// Called after every batch committed by the block, transaction and virtual chain processors
+    pub fn on_batch_committed(mut self, callback: impl Fn(&CommittedBatch) + Send + Sync + 'static) -> IndexerBuilder {
+        self.settings.hooks.add(Arc::new(callback));
+        self
+    }
+
+    pub fn build(self) -> Indexer {
+        let metrics = Arc::new(RwLock::new(Metrics::new(
+            env!("CARGO_PKG_NAME").to_string(),
+            self.settings.cli_args.version(),
+            self.settings.cli_args.commit_id(),
+        )));
+        Indexer { run: self.run.clone(), metrics, builder: Some(self), task: None }
+    }
+}
+
+/// Handle of an indexer built with IndexerBuilder
+pub struct Indexer {
+    run: Arc<AtomicBool>,
+    metrics: Arc<RwLock<Metrics>>,
+    builder: Option<IndexerBuilder>,
+    task: Option<JoinHandle<()>>,
+}
+
+impl Indexer {
+    /// Spawns the indexer on the current Tokio runtime
+    pub fn start(&mut self) {
+        let builder = self.builder.take().expect("Indexer was already started");
+        self.task = Some(task::spawn(process(builder, self.run.clone(), self.metrics.clone())));
+    }
+
+    /// Waits until the indexer has stopped, e.g. after the run flag was cleared
+    pub async fn wait(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.await.expect("Indexer task failed");
+        }
+    }
+
+    /// Stops fetching, drains the queues and saves the final checkpoint
+    pub async fn shutdown(&mut self) {
+        self.run.store(false, Ordering::Relaxed);
+        self.wait().await;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    pub fn run_flag(&self) -> Arc<AtomicBool> {
+        self.run.clone()
+    }
+
+    /// A snapshot of the component metrics, as served by the REST API (without the process and database details)
+    pub async fn metrics(&self) -> Metrics {
+        self.metrics.read().await.clone()
+    }
+}
+
+/// The kaspad pool for the rpc url(s), protocol and record/replay options. The node monitor is not started
+pub fn kaspad_pool(cli_args: &CliArgs) -> Pool<KaspadManager> {
+    let network_id = NetworkId::from_str(&cli_args.network).unwrap();
+    let rpc_protocol = KaspadProtocol::from_str(&cli_args.rpc_protocol).unwrap();
+    let mut kaspad_manager = KaspadManager::new(network_id, cli_args.rpc_url.clone(), rpc_protocol);
+    if cli_args.is_disabled(CliDisable::KaspadWaitForSync) {
+        kaspad_manager = kaspad_manager.allow_unsynced();
+    }
+    if let Some(rpc_ca_file) = &cli_args.rpc_ca_file {
+        let tls_config = load_tls_config(rpc_ca_file).unwrap_or_else(|e| panic!("{e}"));
+        kaspad_manager = kaspad_manager.with_tls_tunnels(TlsTunnels::new(tls_config));
+    }
+    if let Some(kaspad_replay) = &cli_args.kaspad_replay {
+        let replay_client = ReplayClient::open(kaspad_replay).expect("Unable to open kaspad replay file");
+        kaspad_manager = kaspad_manager.with_fixture(KaspadFixture::Replay(Arc::new(replay_client)));
+    } else if let Some(kaspad_record) = &cli_args.kaspad_record {
+        let recorder = FixtureRecorder::create(kaspad_record).expect("Unable to create kaspad record file");
+        kaspad_manager = kaspad_manager.with_fixture(KaspadFixture::Record(Arc::new(recorder)));
+    }
+    Pool::builder(kaspad_manager).max_size(10).build().unwrap()
+}
+
+/// The database connection and pool options
+pub fn db_connect_options(cli_args: &CliArgs) -> DbConnectOptions {
+    DbConnectOptions {
+        min_connections: cli_args.database_min_connections,
+        max_connections: cli_args.database_max_connections,
+        acquire_timeout: Duration::from_secs(cli_args.database_acquire_timeout),
+        idle_timeout: Some(cli_args.database_idle_timeout).filter(|t| *t > 0).map(Duration::from_secs),
+        statement_timeout: cli_args.database_statement_timeout.map(Duration::from_secs),
+        application_name: cli_args.database_application_name.clone(),
+        bulk_synchronous_commit: cli_args.database_bulk_synchronous_commit.clone(),
+        password_file: cli_args.database_password_file.clone(),
+        ssl_mode: cli_args.database_ssl_mode.clone(),
+        ssl_root_cert: cli_args.database_ssl_root_cert.clone(),
+        ssl_client_cert: cli_args.database_ssl_cert.clone(),
+        ssl_client_key: cli_args.database_ssl_key.clone(),
+    }
+}
+
+/// The tables and fields populated with the options, for the read queries of KaspaDbClient.
+/// The processors and validation use the same layout, so reads and writes can't diverge
+pub fn read_layout(cli_args: &CliArgs) -> ReadLayout {
+    let transactions = !cli_args.is_disabled(CliDisable::TransactionProcessing);
+    let chain_blocks = !cli_args.is_disabled(CliDisable::VirtualChainProcessing);
+    let transactions_inputs_table = transactions && !cli_args.is_disabled(CliDisable::TransactionsInputsTable);
+    let address_mapping = if !transactions || cli_args.is_disabled(CliDisable::AddressesTransactionsTable) {
+        AddressMapping::None
+    } else if !cli_args.is_excluded(CliField::TxOutScriptPublicKeyAddress) {
+        AddressMapping::Addresses
+    } else if !cli_args.is_excluded(CliField::TxOutScriptPublicKey) {
+        AddressMapping::Scripts
+    } else {
+        AddressMapping::None
+    };
+    ReadLayout {
+        blocks_table: !cli_args.is_disabled(CliDisable::BlocksTable),
+        blocks_transactions_table: transactions && !cli_args.is_disabled(CliDisable::BlocksTransactionsTable),
+        transactions_table: transactions && !cli_args.is_disabled(CliDisable::TransactionsTable),
+        transactions_inputs_table,
+        transactions_outputs_table: transactions && !cli_args.is_disabled(CliDisable::TransactionsOutputsTable),
+        chain_blocks,
+        transaction_acceptance: chain_blocks && !cli_args.is_disabled(CliDisable::TransactionAcceptance),
+        address_mapping,
+        inputs_resolved: transactions_inputs_table && cli_args.is_enabled(CliEnable::TransactionsInputsResolve),
+        block_blue_score: !cli_args.is_excluded(CliField::BlockBlueScore),
+        tx_in_previous_outpoint: !cli_args.is_excluded(CliField::TxInPreviousOutpoint),
+        tx_out_script_public_key: !cli_args.is_excluded(CliField::TxOutScriptPublicKey),
+        tx_out_script_public_key_address: !cli_args.is_excluded(CliField::TxOutScriptPublicKeyAddress),
+    }
+}
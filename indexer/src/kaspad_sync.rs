@@ -0,0 +1,148 @@
+use crate::web::model::metrics::Metrics;
+use chrono::{DateTime, Utc};
+use deadpool::managed::{Object, Pool};
+use humantime::format_duration;
+use log::{info, warn};
+use simply_kaspa_kaspad::api::KaspadApi;
+use simply_kaspa_kaspad::network::NetworkParams;
+use simply_kaspa_kaspad::pool::manager::{is_synced, KaspadClient, KaspadManager};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KaspadSyncState {
+    Connecting,
+    Syncing,
+    Synced,
+}
+
+impl fmt::Display for KaspadSyncState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+pub(crate) struct KaspadSyncProgress {
+    pub(crate) is_synced: bool,
+    pub(crate) header_count: u64,
+    pub(crate) block_count: u64,
+    pub(crate) virtual_daa_score: u64,
+    pub(crate) network_daa_score: u64,
+    pub(crate) lag: Duration,
+}
+
+impl KaspadSyncProgress {
+    pub(crate) fn progress_percent(&self) -> f64 {
+        if self.is_synced || self.network_daa_score == 0 {
+            100.0
+        } else {
+            (self.virtual_daa_score as f64 / self.network_daa_score as f64 * 100.0).min(99.9)
+        }
+    }
+}
+
+/// Waits for kaspad to be synced before the indexer is started, reporting kaspad's IBD progress in the meantime.
+/// Returns false if the indexer was stopped while waiting
+pub async fn wait_for_kaspad_sync(
+    wait_for_sync: bool,
+    run: Arc<AtomicBool>,
+    metrics: Arc<RwLock<Metrics>>,
+    kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
+) -> bool {
+    let mut kaspad: Option<KaspadClient> = None;
+    while run.load(Ordering::Relaxed) {
+        if kaspad.as_ref().is_some_and(|k| !k.is_connected()) {
+            kaspad = None;
+        }
+        if kaspad.is_none() {
+            match kaspad_pool.manager().connect_unsynced().await {
+                Ok(k) => kaspad = Some(k),
+                Err(e) => {
+                    warn!("Waiting for kaspad connection: {}", e);
+                    set_state(&metrics, KaspadSyncState::Connecting, None).await;
+                }
+            }
+        }
+        if let Some(k) = &kaspad {
+            match get_sync_progress(k).await {
+                Ok(progress) if progress.is_synced => {
+                    set_state(&metrics, KaspadSyncState::Synced, Some(&progress)).await;
+                    info!("Kaspad is synced, virtual DAA score: {}", progress.virtual_daa_score);
+                    return true;
+                }
+                Ok(progress) => {
+                    set_state(&metrics, KaspadSyncState::Syncing, Some(&progress)).await;
+                    info!(
+                        "Kaspad is syncing: {}/{} blocks, DAA score {} of ~{} ({:.1}%), {} behind",
+                        progress.block_count,
+                        progress.header_count,
+                        progress.virtual_daa_score,
+                        progress.network_daa_score,
+                        progress.progress_percent(),
+                        format_duration(progress.lag)
+                    );
+                    if !wait_for_sync {
+                        warn!("Kaspad is NOT synced, starting anyway as kaspad_wait_for_sync is disabled");
+                        return true;
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed getting kaspad sync status: {}", e);
+                    kaspad = None;
+                }
+            }
+        }
+        for _ in 0..POLL_INTERVAL.as_secs() {
+            if !run.load(Ordering::Relaxed) {
+                break;
+            }
+            sleep(Duration::from_secs(1)).await;
+        }
+    }
+    false
+}
+
+pub(crate) async fn get_sync_progress(kaspad: &KaspadClient) -> Result<KaspadSyncProgress, String> {
+    let server_info = kaspad.get_server_info().await.map_err(|e| e.to_string())?;
+    let sync_status = kaspad.get_sync_status().await.map_err(|e| e.to_string())?;
+    let block_dag_info = kaspad.get_block_dag_info().await.map_err(|e| e.to_string())?;
+    let lag_seconds = (Utc::now().timestamp_millis() as u64).saturating_sub(block_dag_info.past_median_time) / 1000;
+    let is_synced = sync_status && is_synced(&server_info);
+    // Used for estimating the network DAA score while kaspad is syncing
+    let estimated_bps = NetworkParams::new(block_dag_info.network).bps(block_dag_info.virtual_daa_score) as u64;
+    Ok(KaspadSyncProgress {
+        is_synced,
+        header_count: block_dag_info.header_count,
+        block_count: block_dag_info.block_count,
+        virtual_daa_score: server_info.virtual_daa_score,
+        network_daa_score: if is_synced {
+            server_info.virtual_daa_score
+        } else {
+            block_dag_info.virtual_daa_score + lag_seconds * estimated_bps
+        },
+        lag: Duration::from_secs(lag_seconds),
+    })
+}
+
+async fn set_state(metrics: &Arc<RwLock<Metrics>>, state: KaspadSyncState, progress: Option<&KaspadSyncProgress>) {
+    let now = Utc::now().timestamp_millis() as u64;
+    let mut metrics = metrics.write().await;
+    let kaspad_sync = &mut metrics.kaspad_sync;
+    kaspad_sync.state = state.to_string();
+    if let Some(progress) = progress {
+        kaspad_sync.is_synced = Some(progress.is_synced);
+        kaspad_sync.header_count = Some(progress.header_count);
+        kaspad_sync.block_count = Some(progress.block_count);
+        kaspad_sync.virtual_daa_score = Some(progress.virtual_daa_score);
+        kaspad_sync.network_daa_score = Some(progress.network_daa_score);
+        kaspad_sync.progress_percent = Some(progress.progress_percent());
+    }
+    kaspad_sync.last_updated = Some(now);
+    kaspad_sync.last_updated_date_time = DateTime::from_timestamp_millis(now as i64);
+}
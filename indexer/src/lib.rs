@@ -1,9 +1,21 @@
+pub mod batch_size;
 pub mod blocks;
+pub mod cache;
 pub mod checkpoint;
+pub mod commands;
+pub mod hooks;
+pub mod indexer;
+pub mod kaspad_sync;
+pub mod logger;
+pub mod mempool;
+pub mod processing;
+pub mod reload;
+pub mod retry;
 pub mod settings;
 pub mod signal;
 pub mod transactions;
 pub mod utxo_import;
+pub mod validation;
 pub mod vars;
 pub mod virtual_chain;
 pub mod web;
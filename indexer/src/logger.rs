@@ -0,0 +1,53 @@
+use env_logger::{Logger, Target, WriteStyle};
+use log::{Log, Metadata, Record};
+use std::sync::{OnceLock, RwLock};
+
+static LOGGER: OnceLock<ReloadableLogger> = OnceLock::new();
+
+/// Wraps env_logger to allow changing the log level while running
+struct ReloadableLogger {
+    no_color: bool,
+    stderr: bool,
+    logger: RwLock<Logger>,
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger.read().unwrap().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.logger.read().unwrap().log(record)
+    }
+
+    fn flush(&self) {
+        self.logger.read().unwrap().flush()
+    }
+}
+
+/// Initializes logging, log_level uses the RUST_LOG syntax (e.g. 'info,sqlx=warn')
+pub fn init_logger(log_level: &str, no_color: bool, stderr: bool) {
+    let logger = build_logger(log_level, no_color, stderr);
+    log::set_max_level(logger.filter());
+    let logger = LOGGER.get_or_init(|| ReloadableLogger { no_color, stderr, logger: RwLock::new(logger) });
+    log::set_logger(logger).expect("Logger already initialized");
+}
+
+/// Replaces the log level of the logger set up by init_logger
+pub fn set_log_level(log_level: &str) {
+    if let Some(reloadable) = LOGGER.get() {
+        let logger = build_logger(log_level, reloadable.no_color, reloadable.stderr);
+        log::set_max_level(logger.filter());
+        *reloadable.logger.write().unwrap() = logger;
+    }
+}
+
+fn build_logger(log_level: &str, no_color: bool, stderr: bool) -> Logger {
+    env_logger::Builder::new()
+        .parse_filters(log_level)
+        .write_style(if no_color { WriteStyle::Never } else { WriteStyle::Always })
+        .target(if stderr { Target::Stderr } else { Target::Stdout })
+        .format_target(false)
+        .format_timestamp_millis()
+        .build()
+}
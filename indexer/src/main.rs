@@ -1,235 +1,85 @@
-use clap::Parser;
-use crossbeam_queue::ArrayQueue;
-use deadpool::managed::{Object, Pool};
-use futures_util::future::try_join_all;
-use kaspa_hashes::Hash as KaspaHash;
-use kaspa_rpc_core::api::rpc::RpcApi;
-use kaspa_wrpc_client::prelude::{NetworkId, NetworkType};
 use log::{info, trace, warn};
-use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable};
+use simply_kaspa_cli::cli_args::CliCommand;
+use simply_kaspa_cli::config::{parse_cli_args, print_config};
 use simply_kaspa_database::client::KaspaDbClient;
-use simply_kaspa_indexer::blocks::fetch_blocks::KaspaBlocksFetcher;
-use simply_kaspa_indexer::blocks::process_blocks::process_blocks;
-use simply_kaspa_indexer::checkpoint::{process_checkpoints, CheckpointBlock, CheckpointOrigin};
+use simply_kaspa_indexer::commands;
+use simply_kaspa_indexer::indexer::{db_connect_options, kaspad_pool, IndexerBuilder};
+use simply_kaspa_indexer::logger::init_logger;
+use simply_kaspa_indexer::reload::SettingsReloader;
 use simply_kaspa_indexer::settings::Settings;
 use simply_kaspa_indexer::signal::signal_handler::notify_on_signals;
-use simply_kaspa_indexer::transactions::process_transactions::process_transactions;
-use simply_kaspa_indexer::utxo_import::utxo_set_importer::UtxoSetImporter;
-use simply_kaspa_indexer::vars::load_block_checkpoint;
-use simply_kaspa_indexer::virtual_chain::process_virtual_chain::process_virtual_chain;
-use simply_kaspa_indexer::web::model::metrics::Metrics;
-use simply_kaspa_indexer::web::web_server::WebServer;
-use simply_kaspa_kaspad::pool::manager::KaspadManager;
-use simply_kaspa_mapping::mapper::KaspaDbMapper;
-use std::env;
-use std::str::FromStr;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::RwLock;
+use simply_kaspa_indexer::validation::validate;
+use simply_kaspa_kaspad::pool::manager::monitor;
 use tokio::task;
 
 #[tokio::main]
 async fn main() {
-    println!();
-    println!("**************************************************************");
-    println!("******************** Simply Kaspa Indexer ********************");
-    println!("--------------------------------------------------------------");
-    println!("----- https://github.com/supertypo/simply-kaspa-indexer/ -----");
-    println!("--------------------------------------------------------------");
-    let cli_args = CliArgs::parse();
+    let cli_args = parse_cli_args();
+    let command = cli_args.command();
+    if command == CliCommand::Config {
+        print!("{}", print_config(&cli_args));
+        return;
+    }
 
-    env::set_var("RUST_LOG", &cli_args.log_level);
-    env::set_var("RUST_LOG_STYLE", if cli_args.log_no_color { "never" } else { "always" });
-    env_logger::builder().target(env_logger::Target::Stdout).format_target(false).format_timestamp_millis().init();
+    // Other commands print their results to stdout, so logging goes to stderr
+    let log_stderr = if command == CliCommand::Run {
+        println!();
+        println!("**************************************************************");
+        println!("******************** Simply Kaspa Indexer ********************");
+        println!("--------------------------------------------------------------");
+        println!("----- https://github.com/supertypo/simply-kaspa-indexer/ -----");
+        println!("--------------------------------------------------------------");
+        false
+    } else {
+        true
+    };
+    init_logger(&cli_args.log_level, cli_args.log_no_color, log_stderr);
 
-    trace!("{:?}", cli_args);
+    trace!("{:?}", cli_args.redacted());
     if cli_args.batch_scale < 0.1 || cli_args.batch_scale > 10.0 {
         panic!("Invalid batch-scale");
     }
     info!("{} {}", env!("CARGO_PKG_NAME"), cli_args.version());
 
-    let network_id = NetworkId::from_str(&cli_args.network).unwrap();
-    let kaspad_manager = KaspadManager { network_id, rpc_url: cli_args.rpc_url.clone() };
-    let kaspad_pool: Pool<KaspadManager> = Pool::builder(kaspad_manager).max_size(10).build().unwrap();
-
-    let database = KaspaDbClient::new(&cli_args.database_url).await.expect("Database connection FAILED");
-
-    if cli_args.initialize_db {
-        info!("Initializing database");
-        database.drop_schema().await.expect("Unable to drop schema");
+    let kaspad_pool = kaspad_pool(&cli_args);
+    if command == CliCommand::Run && cli_args.kaspad_replay.is_none() {
+        task::spawn(monitor(kaspad_pool.clone()));
     }
-    database.create_schema(cli_args.upgrade_db).await.expect("Unable to create schema");
-
-    start_processing(cli_args, kaspad_pool, database).await;
-}
-
-async fn start_processing(cli_args: CliArgs, kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>, database: KaspaDbClient) {
-    let run = Arc::new(AtomicBool::new(true));
-    task::spawn(notify_on_signals(run.clone()));
-
-    let mut block_dag_info = None;
-    while block_dag_info.is_none() {
-        if let Ok(kaspad) = kaspad_pool.get().await {
-            if let Ok(bdi) = kaspad.get_block_dag_info().await {
-                block_dag_info = Some(bdi);
+    let database = KaspaDbClient::new_with_options(&cli_args.database_url, &db_connect_options(&cli_args))
+        .await
+        .expect("Database connection FAILED");
+
+    match command {
+        CliCommand::Run => {
+            if cli_args.initialize_db {
+                warn!("Flag '--initialize-db' is deprecated, use the 'init' command instead");
+                info!("Initializing database");
+                database.drop_schema().await.expect("Unable to drop schema");
             }
-        }
-        if block_dag_info.is_none() {
-            tokio::time::sleep(Duration::from_secs(5)).await;
-        }
-        if !run.load(Ordering::Relaxed) {
-            return;
-        }
-    }
-    let block_dag_info = block_dag_info.unwrap();
-    let net_bps = match block_dag_info.network {
-        NetworkId { network_type: NetworkType::Mainnet, suffix: None } => {
-            if block_dag_info.virtual_daa_score >= 110165000 {
-                10
-            } else {
-                1
+            if cli_args.upgrade_db {
+                warn!("Flag '--upgrade-db' is deprecated, use the 'migrate' command instead");
+            }
+            database.create_schema(cli_args.upgrade_db).await.expect("Unable to create schema");
+            if !validate(&cli_args, &database).await {
+                std::process::exit(2);
             }
-        }
-        _ => 10,
-    };
-    let net_tps_max = net_bps as u16 * 300;
-    info!("Assuming {} block(s) per second for cache sizes", net_bps);
-
-    if let Some(enable) = &cli_args.enable {
-        info!("Enable functionality is set, the following functionality will be enabled: {:?}", enable);
-    }
-    if let Some(disable) = &cli_args.disable {
-        info!("Disable functionality is set, the following functionality will be disabled: {:?}", disable);
-    }
-    if let Some(exclude_fields) = &cli_args.exclude_fields {
-        info!("Exclude fields is set, the following fields will be excluded: {:?}", exclude_fields);
-    }
 
-    let mut utxo_set_import = cli_args.is_enabled(CliEnable::ForceUtxoImport);
-    let checkpoint: KaspaHash;
-    if let Some(ignore_checkpoint) = cli_args.ignore_checkpoint.clone() {
-        warn!("Checkpoint ignored due to user request (-i). This might lead to inconsistencies.");
-        if ignore_checkpoint == "p" {
-            checkpoint = block_dag_info.pruning_point_hash;
-            info!("Starting from pruning_point {}", checkpoint);
-        } else if ignore_checkpoint == "v" {
-            checkpoint = *block_dag_info.virtual_parent_hashes.first().expect("Virtual parent not found");
-            info!("Starting from virtual_parent {}", checkpoint);
-        } else {
-            checkpoint = KaspaHash::from_str(ignore_checkpoint.as_str()).expect("Supplied block hash is invalid");
-            info!("Starting from user supplied block {}", checkpoint);
-        }
-    } else if let Ok(saved_block_checkpoint) = load_block_checkpoint(&database).await {
-        checkpoint = KaspaHash::from_str(saved_block_checkpoint.as_str()).expect("Saved checkpoint is invalid!");
-        info!("Starting from checkpoint {}", checkpoint);
-    } else {
-        if cli_args.is_disabled(CliDisable::InitialUtxoImport) {
-            checkpoint = *block_dag_info.virtual_parent_hashes.first().expect("Virtual parent not found");
-            warn!("Checkpoint not found, starting from virtual_parent {}", checkpoint);
-        } else {
-            utxo_set_import = true;
-            checkpoint = block_dag_info.pruning_point_hash;
-            warn!("Checkpoint not found, starting from pruning_point {}", checkpoint);
+            let (reloader, runtime) = SettingsReloader::new(cli_args.clone());
+            let shutdown_timeout = cli_args.shutdown_timeout;
+            let mut indexer = IndexerBuilder::new(Settings::new(cli_args), database, kaspad_pool).runtime(runtime).build();
+            task::spawn(notify_on_signals(indexer.run_flag(), shutdown_timeout, reloader));
+            indexer.start();
+            indexer.wait().await;
         }
-    }
-
-    let checkpoint_block = match kaspad_pool.get().await.unwrap().get_block(checkpoint, false).await {
-        Ok(block) => Some(CheckpointBlock {
-            origin: CheckpointOrigin::Initial,
-            hash: block.header.hash.into(),
-            timestamp: block.header.timestamp,
-            daa_score: block.header.daa_score,
-            blue_score: block.header.blue_score,
-        }),
-        Err(_) => None,
-    };
-
-    let disable_vcp_wait_for_sync = cli_args.is_disabled(CliDisable::VcpWaitForSync) || utxo_set_import;
-
-    let queue_capacity = (cli_args.batch_scale * 1000f64) as usize;
-    let blocks_queue = Arc::new(ArrayQueue::new(queue_capacity));
-    let txs_queue = Arc::new(ArrayQueue::new(queue_capacity));
-    let checkpoint_queue = Arc::new(ArrayQueue::new(30000));
-
-    let mapper = KaspaDbMapper::new(cli_args.clone());
-
-    let settings = Settings { cli_args: cli_args.clone(), net_bps, net_tps_max, checkpoint, disable_vcp_wait_for_sync };
-    let start_vcp = Arc::new(AtomicBool::new(false));
-
-    let mut metrics = Metrics::new(env!("CARGO_PKG_NAME").to_string(), cli_args.version(), cli_args.commit_id());
-    let mut settings_clone = settings.clone();
-    settings_clone.cli_args.rpc_url = settings_clone.cli_args.rpc_url.map(|_| "**hidden**".to_string());
-    settings_clone.cli_args.p2p_url = settings_clone.cli_args.p2p_url.map(|_| "**hidden**".to_string());
-    settings_clone.cli_args.database_url = "**hidden**".to_string();
-    metrics.settings = Some(settings_clone);
-    metrics.queues.blocks_capacity = blocks_queue.capacity() as u64;
-    metrics.queues.transactions_capacity = txs_queue.capacity() as u64;
-    metrics.checkpoint.origin = checkpoint_block.as_ref().map(|c| format!("{:?}", c.origin));
-    metrics.checkpoint.block = checkpoint_block.map(|c| c.into());
-    metrics.components.transaction_processor.enabled = !settings.cli_args.is_disabled(CliDisable::TransactionProcessing);
-    metrics.components.virtual_chain_processor.enabled = !settings.cli_args.is_disabled(CliDisable::VirtualChainProcessing);
-    metrics.components.virtual_chain_processor.only_blocks = settings.cli_args.is_disabled(CliDisable::TransactionAcceptance);
-    let metrics = Arc::new(RwLock::new(metrics));
-
-    let webserver = Arc::new(WebServer::new(settings.clone(), run.clone(), metrics.clone(), kaspad_pool.clone(), database.clone()));
-    let webserver_task = task::spawn(async move { webserver.run().await.unwrap() });
-
-    if utxo_set_import {
-        let importer =
-            UtxoSetImporter::new(cli_args.clone(), run.clone(), metrics.clone(), block_dag_info.pruning_point_hash, database.clone());
-        if !importer.start().await {
-            warn!("UTXO set import aborted");
-            webserver_task.await.unwrap();
-            return;
+        CliCommand::Migrate { dry_run } => commands::migrate(&database, dry_run).await,
+        CliCommand::Verify => {
+            if !commands::verify(&database).await {
+                std::process::exit(1);
+            }
         }
+        CliCommand::Checkpoint { action } => commands::checkpoint(action, &database, &kaspad_pool).await,
+        CliCommand::Status => commands::status(&database, &kaspad_pool).await,
+        CliCommand::Init { .. } => commands::init(&database).await,
+        CliCommand::Config => unreachable!("Printed before connecting"),
     }
-
-    let mut block_fetcher = KaspaBlocksFetcher::new(
-        settings.clone(),
-        run.clone(),
-        metrics.clone(),
-        kaspad_pool.clone(),
-        blocks_queue.clone(),
-        txs_queue.clone(),
-    );
-
-    let mut tasks = vec![
-        webserver_task,
-        task::spawn(async move { block_fetcher.start().await }),
-        task::spawn(process_blocks(
-            settings.clone(),
-            run.clone(),
-            metrics.clone(),
-            start_vcp.clone(),
-            blocks_queue.clone(),
-            checkpoint_queue.clone(),
-            database.clone(),
-            mapper.clone(),
-        )),
-        task::spawn(process_checkpoints(settings.clone(), run.clone(), metrics.clone(), checkpoint_queue.clone(), database.clone())),
-    ];
-    if !settings.cli_args.is_disabled(CliDisable::TransactionProcessing) {
-        tasks.push(task::spawn(process_transactions(
-            settings.clone(),
-            run.clone(),
-            metrics.clone(),
-            txs_queue.clone(),
-            checkpoint_queue.clone(),
-            database.clone(),
-            mapper.clone(),
-        )))
-    }
-    if !settings.cli_args.is_disabled(CliDisable::VirtualChainProcessing) {
-        tasks.push(task::spawn(process_virtual_chain(
-            settings.clone(),
-            run.clone(),
-            metrics.clone(),
-            start_vcp.clone(),
-            checkpoint_queue.clone(),
-            kaspad_pool.clone(),
-            database.clone(),
-        )))
-    }
-    try_join_all(tasks).await.unwrap();
 }
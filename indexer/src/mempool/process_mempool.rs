@@ -0,0 +1,209 @@
+use crate::retry::retry_db;
+use crate::settings::{runtime_changed, Settings};
+use crate::web::model::metrics::Metrics;
+use chrono::{DateTime, Utc};
+use deadpool::managed::{Object, Pool};
+use kaspa_hashes::Hash as KaspaHash;
+use kaspa_rpc_core::RpcMempoolEntry;
+use log::{debug, error, info, warn};
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_database::error::Error;
+use simply_kaspa_database::models::mempool_input::MempoolInput;
+use simply_kaspa_database::models::mempool_output::MempoolOutput;
+use simply_kaspa_database::models::mempool_transaction::MempoolTransaction;
+use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use simply_kaspa_kaspad::api::KaspadApi;
+use simply_kaspa_kaspad::pool::manager::KaspadManager;
+use simply_kaspa_mapping::mapper::KaspaDbMapper;
+use std::cmp::min;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+pub async fn process_mempool(
+    settings: Settings,
+    run: Arc<AtomicBool>,
+    metrics: Arc<RwLock<Metrics>>,
+    kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
+    database: KaspaDbClient,
+    mapper: KaspaDbMapper,
+) {
+    let mut runtime = settings.runtime.clone();
+    let mut batch_scale = settings.cli_args.batch_scale;
+    let poll_interval = Duration::from_secs(settings.cli_args.mempool_interval as u64);
+    let err_delay = Duration::from_secs(5);
+
+    let mut subnetwork_map = HashMap::new();
+    let mut known_transaction_ids: HashSet<KaspaHash> = HashSet::new();
+
+    while run.load(Ordering::Relaxed) {
+        if let Some(runtime_settings) = runtime_changed(&mut runtime) {
+            batch_scale = runtime_settings.batch_scale;
+        }
+        let start_request_time = Instant::now();
+        match kaspad_pool.get().await {
+            Ok(kaspad) => match kaspad.get_mempool_entries(true, false).await {
+                Ok(entries) => {
+                    let first_seen = Utc::now().timestamp_millis();
+                    let mut mempool_transaction_ids = HashSet::with_capacity(entries.len());
+                    let mut new_entries = vec![];
+                    for entry in entries.iter() {
+                        let Some(verbose_data) = entry.transaction.verbose_data.as_ref() else {
+                            warn!("Skipping mempool entry without verbose data");
+                            continue;
+                        };
+                        mempool_transaction_ids.insert(verbose_data.transaction_id);
+                        if !known_transaction_ids.contains(&verbose_data.transaction_id) {
+                            new_entries.push(entry);
+                        }
+                    }
+                    let start_commit_time = Instant::now();
+                    let commit = commit_mempool(
+                        batch_scale,
+                        first_seen,
+                        &new_entries,
+                        &mempool_transaction_ids,
+                        &mut subnetwork_map,
+                        &metrics,
+                        &database,
+                        &mapper,
+                    )
+                    .await;
+                    match commit {
+                        Ok((rows_added, rows_removed, rows_added_inputs, rows_added_outputs)) => {
+                            if rows_added > 0 || rows_removed > 0 {
+                                debug!(
+                                    "Committed {} new and {} evicted mempool txs in {}ms ({} in, {} out). Mempool size: {}",
+                                    rows_added,
+                                    rows_removed,
+                                    Instant::now().duration_since(start_commit_time).as_millis(),
+                                    rows_added_inputs,
+                                    rows_added_outputs,
+                                    mempool_transaction_ids.len()
+                                );
+                            }
+                            known_transaction_ids = mempool_transaction_ids;
+
+                            let mut metrics = metrics.write().await;
+                            metrics.components.mempool_processor.transactions = Some(known_transaction_ids.len() as u64);
+                            metrics.components.mempool_processor.last_updated = Some(first_seen as u64);
+                            metrics.components.mempool_processor.last_updated_date_time = DateTime::from_timestamp_millis(first_seen);
+                        }
+                        Err(e) => {
+                            // Best effort, the next poll stores the complete mempool again
+                            error!("Committing mempool failed, skipping this poll: {}", e);
+                            sleep(err_delay).await;
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Failed getting mempool entries: {}", e);
+                    sleep(err_delay).await;
+                }
+            },
+            Err(e) => {
+                error!("Failed getting kaspad connection from pool: {}", e);
+                sleep(err_delay).await
+            }
+        }
+        sleep(poll_interval.saturating_sub(Instant::now().duration_since(start_request_time))).await;
+    }
+}
+
+/// Evicts the transactions no longer in the mempool and inserts the new entries,
+/// returns the rows added and removed and the input and output rows added
+async fn commit_mempool(
+    batch_scale: f64,
+    first_seen: i64,
+    entries: &[&RpcMempoolEntry],
+    mempool_transaction_ids: &HashSet<KaspaHash>,
+    subnetwork_map: &mut HashMap<String, i32>,
+    metrics: &RwLock<Metrics>,
+    database: &KaspaDbClient,
+    mapper: &KaspaDbMapper,
+) -> Result<(u64, u64, u64, u64), Error> {
+    let mut transactions = vec![];
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    for entry in entries {
+        let subnetwork_id = entry.transaction.subnetwork_id.to_string();
+        let subnetwork_key = match subnetwork_map.get(&subnetwork_id) {
+            Some(&subnetwork_key) => subnetwork_key,
+            None => {
+                *subnetwork_map = load_subnetworks(metrics, database).await?;
+                match subnetwork_map.get(&subnetwork_id) {
+                    Some(&subnetwork_key) => subnetwork_key,
+                    None => {
+                        let subnetwork_key = retry_db("subnetworks", metrics, || database.insert_subnetwork(&subnetwork_id)).await?;
+                        subnetwork_map.insert(subnetwork_id.clone(), subnetwork_key);
+                        info!("Committed new subnetwork, id: {} subnetwork_id: {}", subnetwork_key, subnetwork_id);
+                        subnetwork_key
+                    }
+                }
+            }
+        };
+        transactions.push(mapper.map_mempool_transaction(entry, subnetwork_key, first_seen));
+        inputs.extend(mapper.map_mempool_inputs(entry));
+        outputs.extend(mapper.map_mempool_outputs(entry));
+    }
+    let keep_ids: Vec<SqlHash> = mempool_transaction_ids.iter().map(|&t| t.into()).collect();
+    let rows_removed = retry_db("mempool_transactions", metrics, || database.delete_mempool_transactions_except(&keep_ids)).await?;
+    let rows_added_inputs = insert_mempool_inputs(batch_scale, inputs, metrics, database).await?;
+    let rows_added_outputs = insert_mempool_outputs(batch_scale, outputs, metrics, database).await?;
+    // Transactions last, so that a listed mempool transaction always has its inputs and outputs in place:
+    let rows_added = insert_mempool_transactions(batch_scale, transactions, metrics, database).await?;
+    Ok((rows_added, rows_removed, rows_added_inputs, rows_added_outputs))
+}
+
+async fn load_subnetworks(metrics: &RwLock<Metrics>, database: &KaspaDbClient) -> Result<HashMap<String, i32>, Error> {
+    let results = retry_db("subnetworks", metrics, || database.select_subnetworks()).await?;
+    Ok(results.into_iter().map(|s| (s.subnetwork_id, s.id)).collect())
+}
+
+async fn insert_mempool_transactions(
+    batch_scale: f64,
+    values: Vec<MempoolTransaction>,
+    metrics: &RwLock<Metrics>,
+    database: &KaspaDbClient,
+) -> Result<u64, Error> {
+    let batch_size = min((250f64 * batch_scale) as usize, 8000); // 2^16 / fields
+    let key = "mempool_transactions";
+    let mut rows_affected = 0;
+    for batch_values in values.chunks(batch_size) {
+        rows_affected += retry_db(key, metrics, || database.insert_mempool_transactions(batch_values)).await?;
+    }
+    Ok(rows_affected)
+}
+
+async fn insert_mempool_inputs(
+    batch_scale: f64,
+    values: Vec<MempoolInput>,
+    metrics: &RwLock<Metrics>,
+    database: &KaspaDbClient,
+) -> Result<u64, Error> {
+    let batch_size = min((250f64 * batch_scale) as usize, 10000); // 2^16 / fields
+    let key = "mempool_inputs";
+    let mut rows_affected = 0;
+    for batch_values in values.chunks(batch_size) {
+        rows_affected += retry_db(key, metrics, || database.insert_mempool_inputs(batch_values)).await?;
+    }
+    Ok(rows_affected)
+}
+
+async fn insert_mempool_outputs(
+    batch_scale: f64,
+    values: Vec<MempoolOutput>,
+    metrics: &RwLock<Metrics>,
+    database: &KaspaDbClient,
+) -> Result<u64, Error> {
+    let batch_size = min((250f64 * batch_scale) as usize, 13000); // 2^16 / fields
+    let key = "mempool_outputs";
+    let mut rows_affected = 0;
+    for batch_values in values.chunks(batch_size) {
+        rows_affected += retry_db(key, metrics, || database.insert_mempool_outputs(batch_values)).await?;
+    }
+    Ok(rows_affected)
+}
@@ -0,0 +1,264 @@
+use crate::batch_size::AdaptiveQueue;
+use crate::blocks::fetch_blocks::KaspaBlocksFetcher;
+use crate::blocks::process_blocks::process_blocks;
+use crate::checkpoint::{process_checkpoints, CheckpointBlock, CheckpointOrigin};
+use crate::indexer::IndexerBuilder;
+use crate::kaspad_sync::wait_for_kaspad_sync;
+use crate::mempool::process_mempool::process_mempool;
+use crate::settings::Settings;
+use crate::transactions::process_transactions::process_transactions;
+use crate::utxo_import::utxo_set_importer::UtxoSetImporter;
+use crate::vars::load_block_checkpoint;
+use crate::virtual_chain::process_virtual_chain::process_virtual_chain;
+use crate::web::model::metrics::Metrics;
+use crate::web::web_server::WebServer;
+use crossbeam_queue::ArrayQueue;
+use deadpool::managed::{Object, Pool};
+use futures_util::future::try_join_all;
+use kaspa_hashes::Hash as KaspaHash;
+use log::{info, warn};
+use simply_kaspa_cli::cli_args::{CliDisable, CliEnable};
+use simply_kaspa_cli::profiles::profile_definition;
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_kaspad::api::KaspadApi;
+use simply_kaspa_kaspad::network::NetworkParams;
+use simply_kaspa_kaspad::pool::manager::KaspadManager;
+use simply_kaspa_mapping::mapper::KaspaDbMapper;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task;
+use tokio::task::JoinHandle;
+
+/// Runs the indexer until `run` is cleared (e.g. by a signal), then shuts down gracefully. See IndexerBuilder for more options
+pub async fn start_processing(
+    settings: Settings,
+    run: Arc<AtomicBool>,
+    kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
+    database: KaspaDbClient,
+) {
+    let mut indexer = IndexerBuilder::new(settings, database, kaspad_pool).run(run).build();
+    indexer.start();
+    indexer.wait().await;
+}
+
+pub(crate) async fn process(builder: IndexerBuilder, run: Arc<AtomicBool>, metrics: Arc<RwLock<Metrics>>) {
+    let IndexerBuilder { settings, database, kaspad_pool, web_server, .. } = builder;
+    let cli_args = settings.cli_args.clone();
+
+    // Shutdown happens in phases: fetching stops first, then processors drain their queues, then checkpoints, then the web server
+    let run_processors = Arc::new(AtomicBool::new(true));
+    let run_checkpoints = Arc::new(AtomicBool::new(true));
+    let run_web = Arc::new(AtomicBool::new(true));
+
+    let webserver_task = web_server.then(|| {
+        let webserver =
+            Arc::new(WebServer::new(cli_args.clone(), run_web.clone(), metrics.clone(), kaspad_pool.clone(), database.clone()));
+        task::spawn(async move { webserver.run().await.unwrap() })
+    });
+
+    let wait_for_sync = !cli_args.is_disabled(CliDisable::KaspadWaitForSync);
+    if !wait_for_kaspad_sync(wait_for_sync, run.clone(), metrics.clone(), kaspad_pool.clone()).await {
+        stop_web_server(&run_web, webserver_task).await;
+        return;
+    }
+
+    let mut block_dag_info = None;
+    while block_dag_info.is_none() {
+        if let Ok(kaspad) = kaspad_pool.get().await {
+            if let Ok(bdi) = kaspad.get_block_dag_info().await {
+                block_dag_info = Some(bdi);
+            }
+        }
+        if block_dag_info.is_none() {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+        if !run.load(Ordering::Relaxed) {
+            stop_web_server(&run_web, webserver_task).await;
+            return;
+        }
+    }
+    let block_dag_info = block_dag_info.unwrap();
+    let network = NetworkParams::new(block_dag_info.network);
+    info!("Assuming {} block(s) per second for cache sizes", network.bps(block_dag_info.virtual_daa_score));
+    if let Some(fork) = network.next_fork(block_dag_info.virtual_daa_score) {
+        info!("Fork {} activates at DAA score {}, caches will be resized to {} bps", fork.name, fork.activation_daa_score, fork.bps);
+    }
+
+    if let Some(profile) = &cli_args.profile {
+        let definition = profile_definition(profile);
+        info!(
+            "Profile {:?} is set, expanding to enable: {:?}, disable: {:?}, exclude fields: {:?} (merged with explicitly supplied values)",
+            profile,
+            definition.enable(),
+            definition.disable(),
+            definition.exclude_fields()
+        );
+    }
+    if let Some(enable) = &cli_args.enable {
+        info!("Enable functionality is set, the following functionality will be enabled: {:?}", enable);
+    }
+    if let Some(disable) = &cli_args.disable {
+        info!("Disable functionality is set, the following functionality will be disabled: {:?}", disable);
+    }
+    if let Some(include_fields) = &cli_args.include_fields {
+        info!("Include fields is set, only the following fields will be included: {:?}", include_fields);
+    } else if let Some(exclude_fields) = &cli_args.exclude_fields {
+        info!("Exclude fields is set, the following fields will be excluded: {:?}", exclude_fields);
+    }
+    let mut utxo_set_import = cli_args.is_enabled(CliEnable::ForceUtxoImport);
+    let checkpoint: KaspaHash;
+    if let Some(ignore_checkpoint) = cli_args.ignore_checkpoint.clone() {
+        warn!("Checkpoint ignored due to user request (-i). This might lead to inconsistencies.");
+        if ignore_checkpoint == "p" {
+            checkpoint = block_dag_info.pruning_point_hash;
+            info!("Starting from pruning_point {}", checkpoint);
+        } else if ignore_checkpoint == "v" {
+            checkpoint = *block_dag_info.virtual_parent_hashes.first().expect("Virtual parent not found");
+            info!("Starting from virtual_parent {}", checkpoint);
+        } else {
+            checkpoint = KaspaHash::from_str(ignore_checkpoint.as_str()).expect("Supplied block hash is invalid");
+            info!("Starting from user supplied block {}", checkpoint);
+        }
+    } else if let Ok(saved_block_checkpoint) = load_block_checkpoint(&database).await {
+        checkpoint = KaspaHash::from_str(saved_block_checkpoint.as_str()).expect("Saved checkpoint is invalid!");
+        info!("Starting from checkpoint {}", checkpoint);
+    } else {
+        if cli_args.is_disabled(CliDisable::InitialUtxoImport) {
+            checkpoint = *block_dag_info.virtual_parent_hashes.first().expect("Virtual parent not found");
+            warn!("Checkpoint not found, starting from virtual_parent {}", checkpoint);
+        } else {
+            utxo_set_import = true;
+            checkpoint = block_dag_info.pruning_point_hash;
+            warn!("Checkpoint not found, starting from pruning_point {}", checkpoint);
+        }
+    }
+
+    let checkpoint_block = match kaspad_pool.get().await.unwrap().get_block(checkpoint, false).await {
+        Ok(block) => Some(CheckpointBlock {
+            origin: CheckpointOrigin::Initial,
+            hash: block.header.hash.into(),
+            timestamp: block.header.timestamp,
+            daa_score: block.header.daa_score,
+            blue_score: block.header.blue_score,
+        }),
+        Err(_) => None,
+    };
+
+    let disable_vcp_wait_for_sync = cli_args.is_disabled(CliDisable::VcpWaitForSync) || utxo_set_import;
+
+    let queue_capacity = (cli_args.batch_scale * 1000f64) as usize;
+    let blocks_queue = Arc::new(AdaptiveQueue::new(queue_capacity));
+    let txs_queue = Arc::new(AdaptiveQueue::new(queue_capacity));
+    let checkpoint_queue = Arc::new(ArrayQueue::new(30000));
+
+    let mapper = KaspaDbMapper::new(cli_args.clone());
+
+    let settings = Settings { network, checkpoint, disable_vcp_wait_for_sync, ..settings };
+    let start_vcp = Arc::new(AtomicBool::new(false));
+
+    let mut metrics_guard = metrics.write().await;
+    let mut settings_clone = settings.clone();
+    settings_clone.cli_args = settings_clone.cli_args.hidden();
+    metrics_guard.settings = Some(settings_clone);
+    metrics_guard.queues.blocks_capacity = blocks_queue.capacity() as u64;
+    metrics_guard.queues.transactions_capacity = txs_queue.capacity() as u64;
+    metrics_guard.checkpoint.origin = checkpoint_block.as_ref().map(|c| format!("{:?}", c.origin));
+    metrics_guard.checkpoint.block = checkpoint_block.map(|c| c.into());
+    metrics_guard.components.transaction_processor.enabled = !settings.cli_args.is_disabled(CliDisable::TransactionProcessing);
+    metrics_guard.components.virtual_chain_processor.enabled = !settings.cli_args.is_disabled(CliDisable::VirtualChainProcessing);
+    metrics_guard.components.virtual_chain_processor.only_blocks = settings.cli_args.is_disabled(CliDisable::TransactionAcceptance);
+    metrics_guard.components.mempool_processor.enabled = settings.cli_args.is_enabled(CliEnable::MempoolProcessing);
+    drop(metrics_guard);
+
+    // Relaxed synchronous_commit (if configured) until the block processor is synced
+    database.set_bulk_mode(true);
+    if utxo_set_import {
+        let importer =
+            UtxoSetImporter::new(cli_args.clone(), run.clone(), metrics.clone(), block_dag_info.pruning_point_hash, database.clone());
+        if !importer.start().await {
+            warn!("UTXO set import aborted");
+            stop_web_server(&run_web, webserver_task).await;
+            return;
+        }
+    }
+
+    let mut block_fetcher = KaspaBlocksFetcher::new(
+        settings.clone(),
+        run.clone(),
+        metrics.clone(),
+        kaspad_pool.clone(),
+        blocks_queue.clone(),
+        txs_queue.clone(),
+    );
+
+    let checkpoint_task = task::spawn(process_checkpoints(
+        settings.clone(),
+        run.clone(),
+        run_checkpoints.clone(),
+        metrics.clone(),
+        checkpoint_queue.clone(),
+        database.clone(),
+    ));
+    let mut tasks = vec![task::spawn(async move { block_fetcher.start().await })];
+    let mut processor_tasks = vec![task::spawn(process_blocks(
+        settings.clone(),
+        run_processors.clone(),
+        metrics.clone(),
+        start_vcp.clone(),
+        blocks_queue.clone(),
+        checkpoint_queue.clone(),
+        database.clone(),
+        mapper.clone(),
+    ))];
+    if !settings.cli_args.is_disabled(CliDisable::TransactionProcessing) {
+        processor_tasks.push(task::spawn(process_transactions(
+            settings.clone(),
+            run_processors.clone(),
+            metrics.clone(),
+            txs_queue.clone(),
+            checkpoint_queue.clone(),
+            database.clone(),
+            mapper.clone(),
+        )))
+    }
+    if !settings.cli_args.is_disabled(CliDisable::VirtualChainProcessing) {
+        tasks.push(task::spawn(process_virtual_chain(
+            settings.clone(),
+            run.clone(),
+            metrics.clone(),
+            start_vcp.clone(),
+            checkpoint_queue.clone(),
+            kaspad_pool.clone(),
+            database.clone(),
+        )))
+    }
+    if settings.cli_args.is_enabled(CliEnable::MempoolProcessing) {
+        tasks.push(task::spawn(process_mempool(
+            settings.clone(),
+            run.clone(),
+            metrics.clone(),
+            kaspad_pool.clone(),
+            database.clone(),
+            mapper.clone(),
+        )))
+    }
+    try_join_all(tasks).await.unwrap();
+    info!("Fetching stopped, draining queues");
+    run_processors.store(false, Ordering::Relaxed);
+    try_join_all(processor_tasks).await.unwrap();
+    info!("Processors stopped, saving final checkpoint");
+    run_checkpoints.store(false, Ordering::Relaxed);
+    checkpoint_task.await.unwrap();
+    stop_web_server(&run_web, webserver_task).await;
+    info!("Shutdown completed");
+}
+
+async fn stop_web_server(run_web: &AtomicBool, webserver_task: Option<JoinHandle<()>>) {
+    run_web.store(false, Ordering::Relaxed);
+    if let Some(webserver_task) = webserver_task {
+        webserver_task.await.unwrap();
+    }
+}
@@ -0,0 +1,97 @@
+use crate::logger::set_log_level;
+use crate::settings::RuntimeSettings;
+use log::{error, info, warn};
+use serde_json::Value;
+use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_cli::config::try_parse_cli_args;
+use tokio::sync::watch;
+
+/// The options which are applied when reloading, as named in the serialized CliArgs
+pub const RELOADABLE_OPTIONS: [&str; 5] = ["logLevel", "vcpInterval", "vcpWindow", "cacheTtl", "batchScale"];
+
+/// Re-reads the config file, environment and command line, and publishes the reloadable options to the running components
+pub struct SettingsReloader {
+    cli_args: CliArgs,
+    sender: watch::Sender<RuntimeSettings>,
+}
+
+impl SettingsReloader {
+    pub fn new(cli_args: CliArgs) -> (SettingsReloader, watch::Receiver<RuntimeSettings>) {
+        let (sender, receiver) = watch::channel(RuntimeSettings::new(&cli_args));
+        (SettingsReloader { cli_args, sender }, receiver)
+    }
+
+    /// The options as last accepted, the reloadable options are updated by successful reloads
+    pub fn cli_args(&self) -> &CliArgs {
+        &self.cli_args
+    }
+
+    pub fn reload(&mut self) {
+        match try_parse_cli_args() {
+            Ok(cli_args) => self.apply(&cli_args),
+            Err(e) => error!("Reloading settings failed, keeping the current settings: {}", e.render()),
+        }
+    }
+
+    pub fn apply(&mut self, cli_args: &CliArgs) {
+        let rejected = changed_options(&self.cli_args, cli_args)
+            .into_iter()
+            .filter(|o| !RELOADABLE_OPTIONS.contains(&o.as_str()))
+            .map(|o| to_kebab_case(&o))
+            .collect::<Vec<_>>();
+        if !rejected.is_empty() {
+            error!("Option(s) {} can not be reloaded and were ignored, restart the indexer to apply", rejected.join(", "));
+        }
+        let mut runtime = RuntimeSettings::new(cli_args);
+        if runtime.batch_scale < 0.1 || runtime.batch_scale > 10.0 {
+            error!("Invalid batch-scale {}, ignored", runtime.batch_scale);
+            runtime.batch_scale = self.sender.borrow().batch_scale;
+        }
+        // Only the reloadable options were applied, the others keep their value from startup
+        runtime.apply_to(&mut self.cli_args);
+        self.sender.send_if_modified(|current| {
+            if *current == runtime {
+                info!("No reloadable settings were changed");
+                return false;
+            }
+            if current.log_level != runtime.log_level {
+                warn!("Changing log-level from '{}' to '{}'", current.log_level, runtime.log_level);
+                set_log_level(&runtime.log_level);
+            }
+            if current.vcp_interval != runtime.vcp_interval {
+                warn!("Changing vcp-interval from {}s to {}s", current.vcp_interval, runtime.vcp_interval);
+            }
+            if current.vcp_window != runtime.vcp_window {
+                warn!("Changing vcp-window from {}s to {}s", current.vcp_window, runtime.vcp_window);
+            }
+            if current.cache_ttl != runtime.cache_ttl {
+                warn!("Changing cache-ttl from {}s to {}s", current.cache_ttl, runtime.cache_ttl);
+            }
+            if current.batch_scale != runtime.batch_scale {
+                warn!("Changing batch-scale from {} to {}", current.batch_scale, runtime.batch_scale);
+            }
+            *current = runtime;
+            true
+        });
+    }
+}
+
+/// The (serialized) names of the options with a different value
+fn changed_options(old: &CliArgs, new: &CliArgs) -> Vec<String> {
+    let old = serde_json::to_value(old).expect("Unable to serialize settings");
+    let new = serde_json::to_value(new).expect("Unable to serialize settings");
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => new.into_iter().filter(|(k, v)| old.get(k) != Some(v)).map(|(k, _)| k).collect(),
+        _ => vec![],
+    }
+}
+
+fn to_kebab_case(key: &str) -> String {
+    key.chars().fold(String::with_capacity(key.len() + 4), |mut kebab, c| {
+        if c.is_ascii_uppercase() {
+            kebab.push('-');
+        }
+        kebab.push(c.to_ascii_lowercase());
+        kebab
+    })
+}
@@ -0,0 +1,47 @@
+use crate::web::model::metrics::Metrics;
+use chrono::Utc;
+use log::{error, warn};
+use simply_kaspa_database::error::{is_transient, Error};
+use std::cmp::min;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::time::sleep;
+
+const MAX_RETRIES: u32 = 10;
+const INITIAL_DELAY: Duration = Duration::from_millis(250);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Retries transient database errors with bounded exponential backoff, fatal errors are returned immediately.
+/// Retries, failures and the last error are reported in the database metrics
+pub async fn retry_db<T, F, Fut>(key: &str, metrics: &RwLock<Metrics>, mut f: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match f().await {
+            Ok(result) => return Ok(result),
+            Err(e) => {
+                let mut metrics_guard = metrics.write().await;
+                metrics_guard.database.last_error = Some(e.to_string());
+                if attempt >= MAX_RETRIES || !is_transient(&e) {
+                    metrics_guard.database.failures += 1;
+                    drop(metrics_guard);
+                    error!("Database operation on {key} failed after {attempt} retries: {e}");
+                    return Err(e);
+                }
+                let now = Utc::now();
+                metrics_guard.database.retries += 1;
+                metrics_guard.database.last_retry = Some(now.timestamp_millis() as u64);
+                metrics_guard.database.last_retry_date_time = Some(now);
+                drop(metrics_guard);
+                attempt += 1;
+                let delay = min(INITIAL_DELAY * 2u32.pow(attempt - 1), MAX_DELAY);
+                warn!("Transient database error on {key}, retry {attempt}/{MAX_RETRIES} in {}ms: {e}", delay.as_millis());
+                sleep(delay).await;
+            }
+        }
+    }
+}
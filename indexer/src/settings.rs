@@ -1,14 +1,93 @@
+use crate::hooks::BatchHooks;
+use crate::indexer::read_layout;
 use kaspa_hashes::Hash as KaspaHash;
+use kaspa_wrpc_client::prelude::NetworkId;
 use serde::{Deserialize, Serialize};
-use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_cli::cli_args::{CliArgs, CliDisable};
+use simply_kaspa_database::layout::ReadLayout;
+use simply_kaspa_kaspad::network::NetworkParams;
+use std::str::FromStr;
+use tokio::sync::watch;
 use utoipa::ToSchema;
 
 #[derive(ToSchema, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub cli_args: CliArgs,
-    pub net_bps: u8,
-    pub net_tps_max: u16,
+    #[schema(value_type = Object)]
+    pub network: NetworkParams,
     #[schema(value_type = String)]
     pub checkpoint: KaspaHash,
     pub disable_vcp_wait_for_sync: bool,
+    /// Updated when the settings are reloaded (SIGHUP), read the reloadable options from here rather than from cli_args
+    #[serde(skip, default = "detached_runtime")]
+    pub runtime: watch::Receiver<RuntimeSettings>,
+    #[serde(skip)]
+    pub hooks: BatchHooks,
+}
+
+impl Settings {
+    /// Settings for the options, with fixed runtime settings and no hooks.
+    /// The network, checkpoint and disable_vcp_wait_for_sync are resolved again when the indexer starts
+    pub fn new(cli_args: CliArgs) -> Settings {
+        let network_id = NetworkId::from_str(&cli_args.network).expect("Invalid network");
+        Settings {
+            network: NetworkParams::new(network_id),
+            checkpoint: KaspaHash::from_bytes([0; 32]),
+            disable_vcp_wait_for_sync: cli_args.is_disabled(CliDisable::VcpWaitForSync),
+            runtime: RuntimeSettings::fixed(&cli_args),
+            hooks: BatchHooks::default(),
+            cli_args,
+        }
+    }
+
+    /// The tables and fields populated with these settings, see indexer::read_layout
+    pub fn read_layout(&self) -> ReadLayout {
+        read_layout(&self.cli_args)
+    }
+}
+
+/// The subset of the settings which can be changed while running
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeSettings {
+    pub log_level: String,
+    pub vcp_interval: u8,
+    pub vcp_window: u16,
+    pub cache_ttl: u64,
+    pub batch_scale: f64,
+}
+
+impl RuntimeSettings {
+    pub fn new(cli_args: &CliArgs) -> RuntimeSettings {
+        RuntimeSettings {
+            log_level: cli_args.log_level.clone(),
+            vcp_interval: cli_args.vcp_interval,
+            vcp_window: cli_args.vcp_window,
+            cache_ttl: cli_args.cache_ttl,
+            batch_scale: cli_args.batch_scale,
+        }
+    }
+
+    /// Sets the reloadable options of the CliArgs to these values
+    pub fn apply_to(&self, cli_args: &mut CliArgs) {
+        cli_args.log_level = self.log_level.clone();
+        cli_args.vcp_interval = self.vcp_interval;
+        cli_args.vcp_window = self.vcp_window;
+        cli_args.cache_ttl = self.cache_ttl;
+        cli_args.batch_scale = self.batch_scale;
+    }
+
+    /// A channel without reloading, the settings stay as supplied
+    pub fn fixed(cli_args: &CliArgs) -> watch::Receiver<RuntimeSettings> {
+        watch::channel(RuntimeSettings::new(cli_args)).1
+    }
+}
+
+/// Returns the runtime settings if they were changed since the last call
+pub fn runtime_changed(runtime: &mut watch::Receiver<RuntimeSettings>) -> Option<RuntimeSettings> {
+    runtime.has_changed().unwrap_or(false).then(|| runtime.borrow_and_update().clone())
+}
+
+fn detached_runtime() -> watch::Receiver<RuntimeSettings> {
+    watch::channel(RuntimeSettings::default()).1
 }
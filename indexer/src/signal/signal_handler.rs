@@ -1,47 +1,61 @@
 use std::process;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
-use log::warn;
+use crate::reload::SettingsReloader;
+use log::{error, warn};
 #[cfg(windows)]
 use tokio::signal::ctrl_c;
 #[cfg(unix)]
 use tokio::signal::unix::{signal, SignalKind};
 
-pub async fn notify_on_signals(run: Arc<AtomicBool>) {
+/// Stops the indexer on SIGINT/SIGTERM (Ctrl+C on Windows), reloads the settings on SIGHUP (not available on Windows)
+pub async fn notify_on_signals(run: Arc<AtomicBool>, shutdown_timeout: u16, mut reloader: SettingsReloader) {
     #[cfg(unix)]
     {
         let mut sigterm = signal(SignalKind::terminate()).expect("Failed to set up SIGTERM handler");
         let mut sigint = signal(SignalKind::interrupt()).expect("Failed to set up SIGINT handler");
+        let mut sighup = signal(SignalKind::hangup()).expect("Failed to set up SIGHUP handler");
         loop {
             tokio::select! {
                 _ = sigint.recv() => {
-                    exit(run.clone(), "SIGINT");
+                    exit(run.clone(), "SIGINT", shutdown_timeout);
                 },
                 _ = sigterm.recv() => {
-                    exit(run.clone(), "SIGTERM");
+                    exit(run.clone(), "SIGTERM", shutdown_timeout);
+                },
+                _ = sighup.recv() => {
+                    warn!("SIGHUP received, reloading settings");
+                    reloader.reload();
                 },
             }
         }
     }
     #[cfg(windows)]
     {
+        drop(reloader);
         let ctrl_c = ctrl_c();
         loop {
             tokio::select! {
                 _ = ctrl_c => {
-                    exit(run, "Ctrl+C");
+                    exit(run.clone(), "Ctrl+C", shutdown_timeout);
                 },
             }
         }
     }
 }
 
-fn exit(run: Arc<AtomicBool>, signal: &str) {
+fn exit(run: Arc<AtomicBool>, signal: &str, shutdown_timeout: u16) {
     if !run.load(Ordering::Relaxed) {
         warn!("{} received, terminating...", signal);
         process::exit(1);
     }
     warn!("{} received, stopping... (repeat for forced close)", signal);
     run.store(false, Ordering::Relaxed);
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(shutdown_timeout as u64)).await;
+        error!("Graceful shutdown did not complete within {}s, terminating...", shutdown_timeout);
+        process::exit(1);
+    });
 }
@@ -1,6 +1,10 @@
+use crate::batch_size::{AdaptiveBatchSize, AdaptiveQueue};
 use crate::blocks::fetch_blocks::TransactionData;
+use crate::cache::{new_cache, resize_cache};
 use crate::checkpoint::{CheckpointBlock, CheckpointOrigin};
-use crate::settings::Settings;
+use crate::hooks::CommittedBatch;
+use crate::retry::retry_db;
+use crate::settings::{runtime_changed, Settings};
 use crate::web::model::metrics::Metrics;
 use crossbeam_queue::ArrayQueue;
 use kaspa_hashes::Hash as KaspaHash;
@@ -8,8 +12,11 @@ use log::{debug, info, trace, warn};
 use moka::sync::Cache;
 use simply_kaspa_cli::cli_args::{CliDisable, CliEnable, CliField};
 use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_database::layout::AddressMapping;
 use simply_kaspa_database::models::address_transaction::AddressTransaction;
+use simply_kaspa_database::models::block_reward::BlockReward;
 use simply_kaspa_database::models::block_transaction::BlockTransaction;
+use simply_kaspa_database::models::krc20_operation::Krc20Operation;
 use simply_kaspa_database::models::script_transaction::ScriptTransaction;
 use simply_kaspa_database::models::transaction::Transaction;
 use simply_kaspa_database::models::transaction_input::TransactionInput;
@@ -31,26 +38,31 @@ pub async fn process_transactions(
     settings: Settings,
     run: Arc<AtomicBool>,
     metrics: Arc<RwLock<Metrics>>,
-    txs_queue: Arc<ArrayQueue<TransactionData>>,
+    txs_queue: Arc<AdaptiveQueue<TransactionData>>,
     checkpoint_queue: Arc<ArrayQueue<CheckpointBlock>>,
     database: KaspaDbClient,
     mapper: KaspaDbMapper,
 ) {
-    let ttl = settings.cli_args.cache_ttl;
-    let cache_size = settings.net_tps_max as u64 * ttl * 2;
-    let tx_id_cache: Cache<KaspaHash, ()> = Cache::builder().time_to_live(Duration::from_secs(ttl)).max_capacity(cache_size).build();
+    let mut runtime = settings.runtime.clone();
+    let mut ttl = settings.cli_args.cache_ttl;
+    // Sized for genesis, resized as soon as the processed blocks are past a fork
+    let mut net_tps_max = settings.network.tps_max(0);
+    let mut tx_id_cache: Cache<KaspaHash, ()> = new_cache(ttl, net_tps_max as u64 * ttl * 2);
 
-    let batch_scale = settings.cli_args.batch_scale;
-    let batch_size = (5000f64 * batch_scale) as usize;
+    let mut batch_scale = settings.cli_args.batch_scale;
+    let mut batch_size =
+        AdaptiveBatchSize::new((5000f64 * batch_scale) as usize, Duration::from_millis(settings.cli_args.batch_commit_target as u64));
 
     let enable_transactions_inputs_resolve = settings.cli_args.is_enabled(CliEnable::TransactionsInputsResolve);
     let disable_transactions = settings.cli_args.is_disabled(CliDisable::TransactionsTable);
     let disable_transactions_inputs = settings.cli_args.is_disabled(CliDisable::TransactionsInputsTable);
     let disable_transactions_outputs = settings.cli_args.is_disabled(CliDisable::TransactionsOutputsTable);
     let disable_blocks_transactions = settings.cli_args.is_disabled(CliDisable::BlocksTransactionsTable);
-    let disable_address_transactions = settings.cli_args.is_disabled(CliDisable::AddressesTransactionsTable);
-    let exclude_tx_out_script_public_key_address = settings.cli_args.is_excluded(CliField::TxOutScriptPublicKeyAddress);
-    let exclude_tx_out_script_public_key = settings.cli_args.is_excluded(CliField::TxOutScriptPublicKey);
+    let address_mapping = settings.read_layout().address_mapping;
+    let enable_mempool = settings.cli_args.is_enabled(CliEnable::MempoolProcessing);
+    let enable_block_rewards = settings.cli_args.is_enabled(CliEnable::BlockRewardsTable);
+    let enable_krc20_operations = settings.cli_args.is_enabled(CliEnable::Krc20OperationsTable);
+    let enable_balance_changes = settings.cli_args.is_enabled(CliEnable::AddressBalanceChangesTable);
 
     let mut transactions = vec![];
     let mut block_tx = vec![];
@@ -58,6 +70,8 @@ pub async fn process_transactions(
     let mut tx_outputs = vec![];
     let mut tx_address_transactions = vec![];
     let mut tx_script_transactions = vec![];
+    let mut block_rewards = vec![];
+    let mut krc20_operations = vec![];
     let mut checkpoint_blocks = vec![];
     let mut last_commit_time = Instant::now();
 
@@ -71,20 +85,38 @@ pub async fn process_transactions(
     if enable_transactions_inputs_resolve {
         info!("Resolving previous outpoints for inputs");
     }
-    if !disable_address_transactions {
-        if !exclude_tx_out_script_public_key_address {
-            info!("Using addresses_transactions for address transaction mapping");
-        } else if !exclude_tx_out_script_public_key {
-            info!("Using scripts_transactions for address transaction mapping");
-        } else {
-            info!("Address transaction mapping disabled");
-        }
-    } else {
-        info!("Address transaction mapping disabled");
+    if enable_block_rewards {
+        info!("Decoding coinbase payloads into block_rewards");
+    }
+    if enable_krc20_operations {
+        info!("Decoding KRC-20 operations into krc20_operations");
+    }
+    match address_mapping.table() {
+        Some(table) => info!("Using {table} for address transaction mapping"),
+        None => info!("Address transaction mapping disabled"),
     }
 
-    while run.load(Ordering::Relaxed) {
-        if let Some(transaction_data) = txs_queue.pop() {
+    loop {
+        if let Some(runtime_settings) = runtime_changed(&mut runtime) {
+            batch_scale = runtime_settings.batch_scale;
+            batch_size.set_max((5000f64 * batch_scale) as usize);
+            if runtime_settings.cache_ttl != ttl {
+                ttl = runtime_settings.cache_ttl;
+                tx_id_cache = resize_cache(&tx_id_cache, ttl, net_tps_max as u64 * ttl * 2);
+            }
+            debug!("Transaction processor reloaded, max batch size: {}, cache ttl: {}s", batch_size.max(), ttl);
+        }
+        let stopping = !run.load(Ordering::Relaxed);
+        let transaction_data = txs_queue.pop();
+        let idle = transaction_data.is_none();
+        if let Some(transaction_data) = transaction_data {
+            let tps_max = settings.network.tps_max(transaction_data.block_daa_score);
+            if tps_max != net_tps_max {
+                let fork = settings.network.fork(transaction_data.block_daa_score);
+                info!("Fork {} is active, resizing transaction cache for {} bps", fork.name, fork.bps);
+                net_tps_max = tps_max;
+                tx_id_cache = resize_cache(&tx_id_cache, ttl, net_tps_max as u64 * ttl * 2);
+            }
             checkpoint_blocks.push(CheckpointBlock {
                 origin: CheckpointOrigin::Transactions,
                 hash: transaction_data.block_hash.into(),
@@ -111,137 +143,211 @@ pub async fn process_transactions(
                     transactions.push(transaction);
                     tx_inputs.extend(mapper.map_transaction_inputs(&rpc_transaction));
                     tx_outputs.extend(mapper.map_transaction_outputs(&rpc_transaction));
-                    if !disable_address_transactions {
-                        if !exclude_tx_out_script_public_key_address {
-                            tx_address_transactions.extend(mapper.map_transaction_outputs_address(&rpc_transaction));
-                        } else if !exclude_tx_out_script_public_key {
-                            tx_script_transactions.extend(mapper.map_transaction_outputs_script(&rpc_transaction));
+                    match address_mapping {
+                        AddressMapping::Addresses => {
+                            tx_address_transactions.extend(mapper.map_transaction_outputs_address(&rpc_transaction))
                         }
+                        AddressMapping::Scripts => {
+                            tx_script_transactions.extend(mapper.map_transaction_outputs_script(&rpc_transaction))
+                        }
+                        AddressMapping::None => {}
+                    }
+                    if enable_krc20_operations {
+                        krc20_operations.extend(mapper.map_krc20_operations(&rpc_transaction));
                     }
                     tx_id_cache.insert(transaction_id, ());
                 }
+                if enable_block_rewards {
+                    block_rewards.extend(mapper.map_block_reward(&rpc_transaction));
+                }
                 block_tx.push(mapper.map_block_transaction(&rpc_transaction));
             }
+        }
 
-            if block_tx.len() >= batch_size || (!block_tx.is_empty() && Instant::now().duration_since(last_commit_time).as_secs() > 2)
-            {
-                let start_commit_time = Instant::now();
-                let transactions_len = transactions.len();
-                let transaction_ids: Vec<SqlHash> = transactions.iter().map(|t| t.transaction_id.clone()).collect();
+        if block_tx.len() >= batch_size.get()
+            || (!block_tx.is_empty() && ((idle && stopping) || Instant::now().duration_since(last_commit_time).as_secs() > 2))
+        {
+            let start_commit_time = Instant::now();
+            let insert_scale = batch_scale * batch_size.fraction();
+            let transactions_len = transactions.len();
+            let block_tx_len = block_tx.len();
+            let transaction_ids: Vec<SqlHash> = transactions.iter().map(|t| t.transaction_id.clone()).collect();
+            let committed_transaction_ids = if !settings.hooks.is_empty() { transaction_ids.clone() } else { vec![] };
 
-                let tx_handle = if !disable_transactions {
-                    task::spawn(insert_txs(batch_scale, transactions, database.clone()))
-                } else {
-                    task::spawn(async { 0 })
-                };
-                let blocks_txs_handle = if !disable_blocks_transactions {
-                    task::spawn(insert_block_txs(batch_scale, block_tx, database.clone()))
-                } else {
-                    task::spawn(async { 0 })
-                };
-                let tx_output_addr_handle = if !disable_address_transactions {
-                    if !exclude_tx_out_script_public_key_address {
-                        task::spawn(insert_output_tx_addr(batch_scale, tx_address_transactions, database.clone()))
-                    } else if !exclude_tx_out_script_public_key {
-                        task::spawn(insert_output_tx_script(batch_scale, tx_script_transactions, database.clone()))
-                    } else {
-                        task::spawn(async { 0 })
-                    }
-                } else {
-                    task::spawn(async { 0 })
-                };
-                let tx_inputs_handle = if !disable_transactions_inputs {
-                    if enable_transactions_inputs_resolve {
-                        let tx_outputs_map: HashMap<_, _> =
-                            tx_outputs.iter().map(|tx| ((tx.transaction_id.clone(), tx.index), tx)).collect();
-                        let mut previous_from_outputs_count = 0;
-                        for tx_input in tx_inputs.iter_mut() {
-                            let key = (tx_input.previous_outpoint_hash.clone().unwrap(), tx_input.previous_outpoint_index.unwrap());
-                            if let Some(tx_output) = tx_outputs_map.get(&key) {
-                                tx_input.previous_outpoint_script = tx_output.script_public_key.clone();
-                                tx_input.previous_outpoint_amount = tx_output.amount;
-                                previous_from_outputs_count += 1;
-                            }
-                        }
-                        if previous_from_outputs_count > 0 {
-                            trace!("Pre-resolved {previous_from_outputs_count} tx_inputs from tx_outputs");
+            let tx_handle = if !disable_transactions {
+                task::spawn(insert_txs(insert_scale, transactions, metrics.clone(), database.clone()))
+            } else {
+                task::spawn(async { 0 })
+            };
+            let blocks_txs_handle = if !disable_blocks_transactions {
+                task::spawn(insert_block_txs(insert_scale, block_tx, metrics.clone(), database.clone()))
+            } else {
+                task::spawn(async { 0 })
+            };
+            let tx_output_addr_handle = match address_mapping {
+                AddressMapping::Addresses => {
+                    task::spawn(insert_output_tx_addr(insert_scale, tx_address_transactions, metrics.clone(), database.clone()))
+                }
+                AddressMapping::Scripts => {
+                    task::spawn(insert_output_tx_script(insert_scale, tx_script_transactions, metrics.clone(), database.clone()))
+                }
+                AddressMapping::None => task::spawn(async { 0 }),
+            };
+            let block_rewards_handle = if enable_block_rewards {
+                task::spawn(insert_block_rewards(insert_scale, block_rewards, metrics.clone(), database.clone()))
+            } else {
+                task::spawn(async { 0 })
+            };
+            let krc20_operations_handle = if enable_krc20_operations {
+                task::spawn(insert_krc20_operations(insert_scale, krc20_operations, metrics.clone(), database.clone()))
+            } else {
+                task::spawn(async { 0 })
+            };
+            let tx_inputs_handle = if !disable_transactions_inputs {
+                if enable_transactions_inputs_resolve {
+                    let tx_outputs_map: HashMap<_, _> =
+                        tx_outputs.iter().map(|tx| ((tx.transaction_id.clone(), tx.index), tx)).collect();
+                    let mut previous_from_outputs_count = 0;
+                    for tx_input in tx_inputs.iter_mut() {
+                        let key = (tx_input.previous_outpoint_hash.clone().unwrap(), tx_input.previous_outpoint_index.unwrap());
+                        if let Some(tx_output) = tx_outputs_map.get(&key) {
+                            tx_input.previous_outpoint_script = tx_output.script_public_key.clone();
+                            tx_input.previous_outpoint_amount = tx_output.amount;
+                            previous_from_outputs_count += 1;
                         }
                     }
-                    task::spawn(insert_tx_inputs(batch_scale, enable_transactions_inputs_resolve, tx_inputs, database.clone()))
-                } else {
-                    task::spawn(async { 0 })
-                };
-                let tx_outputs_handle = if !disable_transactions_outputs {
-                    task::spawn(insert_tx_outputs(batch_scale, tx_outputs, database.clone()))
-                } else {
-                    task::spawn(async { 0 })
-                };
-                let rows_affected_tx = tx_handle.await.unwrap();
-                let rows_affected_tx_inputs = tx_inputs_handle.await.unwrap();
-                let rows_affected_tx_outputs = tx_outputs_handle.await.unwrap();
-                let rows_affected_block_tx = blocks_txs_handle.await.unwrap();
-                let mut rows_affected_tx_addresses = tx_output_addr_handle.await.unwrap();
+                    if previous_from_outputs_count > 0 {
+                        trace!("Pre-resolved {previous_from_outputs_count} tx_inputs from tx_outputs");
+                    }
+                }
+                task::spawn(insert_tx_inputs(
+                    insert_scale,
+                    enable_transactions_inputs_resolve,
+                    tx_inputs,
+                    metrics.clone(),
+                    database.clone(),
+                ))
+            } else {
+                task::spawn(async { 0 })
+            };
+            let tx_outputs_handle = if !disable_transactions_outputs {
+                task::spawn(insert_tx_outputs(insert_scale, tx_outputs, metrics.clone(), database.clone()))
+            } else {
+                task::spawn(async { 0 })
+            };
+            let rows_affected_tx = tx_handle.await.unwrap();
+            let rows_affected_tx_inputs = tx_inputs_handle.await.unwrap();
+            let rows_affected_tx_outputs = tx_outputs_handle.await.unwrap();
+            let rows_affected_block_tx = blocks_txs_handle.await.unwrap();
+            let mut rows_affected_tx_addresses = tx_output_addr_handle.await.unwrap();
+            let rows_affected_block_rewards = block_rewards_handle.await.unwrap();
+            let rows_affected_krc20_operations = krc20_operations_handle.await.unwrap();
 
-                // ^Input address resolving can only happen after inputs + outputs are committed
-                if !disable_address_transactions {
-                    let use_tx_for_time = settings.cli_args.is_excluded(CliField::TxInBlockTime);
-                    rows_affected_tx_addresses += if !exclude_tx_out_script_public_key_address {
-                        insert_input_tx_addr(batch_scale, use_tx_for_time, transaction_ids, database.clone()).await
-                    } else if !exclude_tx_out_script_public_key {
-                        insert_input_tx_script(batch_scale, use_tx_for_time, transaction_ids, database.clone()).await
-                    } else {
-                        0
-                    };
+            let rows_evicted_mempool = if enable_mempool {
+                evict_mempool_txs(insert_scale, &transaction_ids, metrics.clone(), database.clone()).await
+            } else {
+                0
+            };
+
+            // ^Input address resolving can only happen after inputs + outputs are committed
+            let use_tx_for_time = settings.cli_args.is_excluded(CliField::TxInBlockTime);
+            rows_affected_tx_addresses += match address_mapping {
+                AddressMapping::Addresses => {
+                    insert_input_tx_addr(insert_scale, use_tx_for_time, transaction_ids.clone(), metrics.clone(), database.clone())
+                        .await
+                }
+                AddressMapping::Scripts => {
+                    insert_input_tx_script(insert_scale, use_tx_for_time, transaction_ids.clone(), metrics.clone(), database.clone())
+                        .await
                 }
-                let last_checkpoint = checkpoint_blocks.last().unwrap().clone();
-                let last_block_time = last_checkpoint.timestamp;
+                AddressMapping::None => 0,
+            };
+            // Transactions accepted before their rows were committed get their balance changes now
+            let rows_affected_balance_changes = if enable_balance_changes {
+                insert_balance_changes(insert_scale, transaction_ids, metrics.clone(), database.clone()).await
+            } else {
+                0
+            };
+            let last_checkpoint = checkpoint_blocks.last().unwrap().clone();
+            let last_block_time = last_checkpoint.timestamp;
 
-                let mut metrics = metrics.write().await;
-                metrics.components.transaction_processor.last_block = Some(last_checkpoint.into());
-                drop(metrics);
+            batch_size.update(start_commit_time.elapsed(), block_tx_len, txs_queue.fill());
+            txs_queue.set_fraction(batch_size.fraction());
 
-                for checkpoint_block in checkpoint_blocks {
-                    while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
-                        warn!("Checkpoint queue is full");
-                        sleep(Duration::from_secs(1)).await;
-                    }
+            let mut metrics = metrics.write().await;
+            metrics.components.transaction_processor.last_block = Some(last_checkpoint.into());
+            metrics.components.transaction_processor.batch_size = Some(batch_size.get() as u64);
+            drop(metrics);
+
+            if !settings.hooks.is_empty() {
+                settings.hooks.notify(CommittedBatch::Transactions {
+                    blocks: checkpoint_blocks.clone(),
+                    transaction_ids: committed_transaction_ids,
+                    rows: rows_affected_tx + rows_affected_tx_inputs + rows_affected_tx_outputs + rows_affected_block_tx,
+                    commit_time: start_commit_time.elapsed(),
+                });
+            }
+
+            for checkpoint_block in checkpoint_blocks {
+                while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
+                    warn!("Checkpoint queue is full");
+                    sleep(Duration::from_secs(1)).await;
                 }
-                let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
-                let tps = transactions_len as f64 / commit_time as f64 * 1000f64;
-                info!(
-                    "Committed {} new txs in {}ms ({:.1} tps, {} blk_tx, {} tx_in, {} tx_out, {} adr_tx). Last tx: {}",
-                    rows_affected_tx,
-                    commit_time,
-                    tps,
-                    rows_affected_block_tx,
-                    rows_affected_tx_inputs,
-                    rows_affected_tx_outputs,
-                    rows_affected_tx_addresses,
-                    chrono::DateTime::from_timestamp_millis(last_block_time as i64 / 1000 * 1000).unwrap()
-                );
-                transactions = vec![];
-                block_tx = vec![];
-                tx_inputs = vec![];
-                tx_outputs = vec![];
-                tx_address_transactions = vec![];
-                tx_script_transactions = vec![];
-                checkpoint_blocks = vec![];
-                last_commit_time = Instant::now();
             }
-        } else {
+            let commit_time = Instant::now().duration_since(start_commit_time).as_millis();
+            let tps = transactions_len as f64 / commit_time as f64 * 1000f64;
+            info!(
+                "Committed {} new txs in {}ms ({:.1} tps, {} blk_tx, {} tx_in, {} tx_out, {} adr_tx). Last tx: {}",
+                rows_affected_tx,
+                commit_time,
+                tps,
+                rows_affected_block_tx,
+                rows_affected_tx_inputs,
+                rows_affected_tx_outputs,
+                rows_affected_tx_addresses,
+                chrono::DateTime::from_timestamp_millis(last_block_time as i64 / 1000 * 1000).unwrap()
+            );
+            if rows_affected_block_rewards > 0 {
+                debug!("Committed {} new block rewards", rows_affected_block_rewards);
+            }
+            if rows_affected_krc20_operations > 0 {
+                debug!("Committed {} new KRC-20 operations", rows_affected_krc20_operations);
+            }
+            if rows_affected_balance_changes > 0 {
+                debug!("Committed {} address balance changes of already accepted txs", rows_affected_balance_changes);
+            }
+            if rows_evicted_mempool > 0 {
+                debug!("Evicted {} confirmed txs from mempool", rows_evicted_mempool);
+            }
+            transactions = vec![];
+            block_tx = vec![];
+            tx_inputs = vec![];
+            tx_outputs = vec![];
+            tx_address_transactions = vec![];
+            tx_script_transactions = vec![];
+            block_rewards = vec![];
+            krc20_operations = vec![];
+            checkpoint_blocks = vec![];
+            last_commit_time = Instant::now();
+        } else if idle {
+            if stopping {
+                break;
+            }
             sleep(Duration::from_millis(100)).await;
         }
     }
 }
 
-async fn insert_txs(batch_scale: f64, values: Vec<Transaction>, database: KaspaDbClient) -> u64 {
+async fn insert_txs(batch_scale: f64, values: Vec<Transaction>, metrics: Arc<RwLock<Metrics>>, database: KaspaDbClient) -> u64 {
     let batch_size = min((250f64 * batch_scale) as u16, 8000) as usize; // 2^16 / fields
     let key = "transactions";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected += database.insert_transactions(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+        rows_affected += retry_db(key, &metrics, || database.insert_transactions(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
     debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
@@ -251,6 +357,7 @@ async fn insert_tx_inputs(
     batch_scale: f64,
     resolve_previous_outpoints: bool,
     values: Vec<TransactionInput>,
+    metrics: Arc<RwLock<Metrics>>,
     database: KaspaDbClient,
 ) -> u64 {
     let batch_size = min((250f64 * batch_scale) as u16, 8000) as usize; // 2^16 / fields
@@ -259,8 +366,7 @@ async fn insert_tx_inputs(
     debug!("Processing {} {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected += database
-            .insert_transaction_inputs(resolve_previous_outpoints, batch_values)
+        rows_affected += retry_db(key, &metrics, || database.insert_transaction_inputs(resolve_previous_outpoints, batch_values))
             .await
             .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
@@ -268,29 +374,40 @@ async fn insert_tx_inputs(
     rows_affected
 }
 
-async fn insert_tx_outputs(batch_scale: f64, values: Vec<TransactionOutput>, database: KaspaDbClient) -> u64 {
+async fn insert_tx_outputs(
+    batch_scale: f64,
+    values: Vec<TransactionOutput>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
     let batch_size = min((250f64 * batch_scale) as u16, 10000) as usize; // 2^16 / fields
     let key = "transactions_outputs";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected +=
-            database.insert_transaction_outputs(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+        rows_affected += retry_db(key, &metrics, || database.insert_transaction_outputs(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
     debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
 }
 
-async fn insert_input_tx_addr(batch_scale: f64, use_tx: bool, values: Vec<SqlHash>, database: KaspaDbClient) -> u64 {
+async fn insert_input_tx_addr(
+    batch_scale: f64,
+    use_tx: bool,
+    values: Vec<SqlHash>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
     let batch_size = min((100f64 * batch_scale) as u16, 8000) as usize;
     let key = "input addresses_transactions";
     let start_time = Instant::now();
     debug!("Processing {} transactions for {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected += database
-            .insert_address_transactions_from_inputs(use_tx, batch_values)
+        rows_affected += retry_db(key, &metrics, || database.insert_address_transactions_from_inputs(use_tx, batch_values))
             .await
             .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
@@ -298,15 +415,20 @@ async fn insert_input_tx_addr(batch_scale: f64, use_tx: bool, values: Vec<SqlHas
     rows_affected
 }
 
-async fn insert_input_tx_script(batch_scale: f64, use_tx: bool, values: Vec<SqlHash>, database: KaspaDbClient) -> u64 {
+async fn insert_input_tx_script(
+    batch_scale: f64,
+    use_tx: bool,
+    values: Vec<SqlHash>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
     let batch_size = min((100f64 * batch_scale) as u16, 8000) as usize;
     let key = "input scripts_transactions";
     let start_time = Instant::now();
     debug!("Processing {} transactions for {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected += database
-            .insert_script_transactions_from_inputs(use_tx, batch_values)
+        rows_affected += retry_db(key, &metrics, || database.insert_script_transactions_from_inputs(use_tx, batch_values))
             .await
             .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
@@ -314,43 +436,137 @@ async fn insert_input_tx_script(batch_scale: f64, use_tx: bool, values: Vec<SqlH
     rows_affected
 }
 
-async fn insert_output_tx_addr(batch_scale: f64, values: Vec<AddressTransaction>, database: KaspaDbClient) -> u64 {
+async fn insert_output_tx_addr(
+    batch_scale: f64,
+    values: Vec<AddressTransaction>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
     let batch_size = min((250f64 * batch_scale) as u16, 20000) as usize; // 2^16 / fields
     let key = "output addresses_transactions";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected +=
-            database.insert_address_transactions(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+        rows_affected += retry_db(key, &metrics, || database.insert_address_transactions(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
     debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
 }
 
-async fn insert_output_tx_script(batch_scale: f64, values: Vec<ScriptTransaction>, database: KaspaDbClient) -> u64 {
+async fn insert_output_tx_script(
+    batch_scale: f64,
+    values: Vec<ScriptTransaction>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
     let batch_size = min((250f64 * batch_scale) as u16, 20000) as usize; // 2^16 / fields
     let key = "output scripts_transactions";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected +=
-            database.insert_script_transactions(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+        rows_affected += retry_db(key, &metrics, || database.insert_script_transactions(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+    }
+    debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
+    rows_affected
+}
+
+async fn insert_balance_changes(
+    batch_scale: f64,
+    values: Vec<SqlHash>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
+    let batch_size = min((100f64 * batch_scale) as u16, 8000) as usize;
+    let key = "address_balance_changes";
+    let start_time = Instant::now();
+    debug!("Processing {} transactions for {}", values.len(), key);
+    let mut rows_affected = 0;
+    for batch_values in values.chunks(batch_size) {
+        rows_affected += retry_db(key, &metrics, || database.insert_address_balance_changes_for_transactions(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
     debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
 }
 
-async fn insert_block_txs(batch_scale: f64, values: Vec<BlockTransaction>, database: KaspaDbClient) -> u64 {
+async fn insert_block_txs(
+    batch_scale: f64,
+    values: Vec<BlockTransaction>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
     let batch_size = min((500f64 * batch_scale) as u16, 30000) as usize; // 2^16 / fields
     let key = "block/transaction mappings";
     let start_time = Instant::now();
     debug!("Processing {} {}", values.len(), key);
     let mut rows_affected = 0;
     for batch_values in values.chunks(batch_size) {
-        rows_affected += database.insert_block_transactions(batch_values).await.unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+        rows_affected += retry_db(key, &metrics, || database.insert_block_transactions(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
     }
     debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
     rows_affected
 }
+
+async fn insert_block_rewards(
+    batch_scale: f64,
+    values: Vec<BlockReward>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
+    let batch_size = min((250f64 * batch_scale) as u16, 9000) as usize; // 2^16 / fields
+    let key = "block_rewards";
+    let start_time = Instant::now();
+    debug!("Processing {} {}", values.len(), key);
+    let mut rows_affected = 0;
+    for batch_values in values.chunks(batch_size) {
+        rows_affected += retry_db(key, &metrics, || database.insert_block_rewards(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+    }
+    debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
+    rows_affected
+}
+
+async fn insert_krc20_operations(
+    batch_scale: f64,
+    values: Vec<Krc20Operation>,
+    metrics: Arc<RwLock<Metrics>>,
+    database: KaspaDbClient,
+) -> u64 {
+    let batch_size = min((250f64 * batch_scale) as u16, 5000) as usize; // 2^16 / fields
+    let key = "krc20_operations";
+    let start_time = Instant::now();
+    debug!("Processing {} {}", values.len(), key);
+    let mut rows_affected = 0;
+    for batch_values in values.chunks(batch_size) {
+        rows_affected += retry_db(key, &metrics, || database.insert_krc20_operations(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Insert {key} FAILED: {e}"));
+    }
+    debug!("Committed {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
+    rows_affected
+}
+
+async fn evict_mempool_txs(batch_scale: f64, values: &[SqlHash], metrics: Arc<RwLock<Metrics>>, database: KaspaDbClient) -> u64 {
+    let batch_size = min((500f64 * batch_scale) as u16, 30000) as usize;
+    let key = "mempool_transactions";
+    let start_time = Instant::now();
+    debug!("Evicting {} {}", values.len(), key);
+    let mut rows_affected = 0;
+    for batch_values in values.chunks(batch_size) {
+        rows_affected += retry_db(key, &metrics, || database.delete_mempool_transactions(batch_values))
+            .await
+            .unwrap_or_else(|e| panic!("Evict {key} FAILED: {e}"));
+    }
+    debug!("Evicted {} {} in {}ms", rows_affected, key, Instant::now().duration_since(start_time).as_millis());
+    rows_affected
+}
@@ -2,11 +2,18 @@ use kaspa_p2p_lib::common::ProtocolError;
 use kaspa_p2p_lib::pb::kaspad_message::Payload;
 use kaspa_p2p_lib::pb::{KaspadMessage, VersionMessage};
 use kaspa_p2p_lib::{ConnectionInitializer, IncomingRoute, KaspadHandshake, KaspadMessagePayloadType, Router};
+use kaspa_wrpc_client::prelude::NetworkId;
+use rand::prelude::IndexedRandom;
+use rand::rng;
 use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_kaspad::network::NetworkParams;
+use simply_kaspa_kaspad::pool::protocol::KaspadProtocol;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc::Sender;
 use tonic::async_trait;
+use url::Url;
 use uuid::Uuid;
 
 pub struct P2pInitializer {
@@ -55,6 +62,22 @@ impl P2pInitializer {
     }
 }
 
+/// The P2P address of --p2p-url, or else the host of the first rpc url (or a DNS seeder) with the default P2P port of the network
+pub fn resolve_p2p_address(cli_args: &CliArgs, network_id: NetworkId) -> Option<String> {
+    if let Some(p2p_url) = &cli_args.p2p_url {
+        return Some(p2p_url.clone());
+    }
+    let params = NetworkParams::new(network_id);
+    let p2p_port = params.default_p2p_port?;
+    if let Some(rpc_url) = cli_args.rpc_url.as_ref().and_then(|urls| urls.first()) {
+        let default_protocol = KaspadProtocol::from_str(&cli_args.rpc_protocol).unwrap();
+        let (_, rpc_url) = KaspadProtocol::from_url(rpc_url, default_protocol);
+        Some(format!("{}:{}", Url::parse(&rpc_url).unwrap().host().unwrap().to_string(), p2p_port))
+    } else {
+        params.dns_seeders.choose(&mut rng()).map(|seeder| format!("{}:{}", seeder, p2p_port))
+    }
+}
+
 fn subscribe_all(router: &Arc<Router>) -> IncomingRoute {
     router.subscribe(vec![
         KaspadMessagePayloadType::Addresses,
@@ -1,8 +1,7 @@
-use crate::utxo_import::p2p_initializer::P2pInitializer;
+use crate::utxo_import::p2p_initializer::{resolve_p2p_address, P2pInitializer};
 use crate::web::model::metrics::Metrics;
 use bigdecimal::ToPrimitive;
 use kaspa_addresses::Prefix;
-use kaspa_consensus_core::config::params::{MAINNET_PARAMS, TESTNET_PARAMS};
 use kaspa_consensus_core::tx::ScriptPublicKey;
 use kaspa_hashes::Hash as KaspaHash;
 use kaspa_p2p_lib::common::ProtocolError;
@@ -13,10 +12,8 @@ use kaspa_p2p_lib::pb::{
 };
 use kaspa_p2p_lib::{make_message, Adaptor, Hub, PeerKey};
 use kaspa_txscript::extract_script_pub_key_address;
-use kaspa_wrpc_client::prelude::{NetworkId, NetworkType};
+use kaspa_wrpc_client::prelude::NetworkId;
 use log::{debug, error, info, trace, warn};
-use rand::prelude::IndexedRandom;
-use rand::rng;
 use simply_kaspa_cli::cli_args::{CliArgs, CliField};
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_database::models::transaction_output::TransactionOutput;
@@ -27,7 +24,6 @@ use std::time::Duration;
 use tokio::sync::mpsc::Receiver;
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::timeout;
-use url::Url;
 
 pub const IBD_BATCH_SIZE: u32 = 99;
 pub const IBD_TIMEOUT_SECONDS: u64 = 30;
@@ -80,24 +76,7 @@ impl UtxoSetImporter {
         let mut attempts = 0;
         let mut completed = false;
         while self.run.load(Ordering::Relaxed) && !completed {
-            let address = if let Some(p2p_url) = &self.cli_args.p2p_url {
-                Some(p2p_url.clone())
-            } else {
-                let params = match self.network_id {
-                    NetworkId { network_type: NetworkType::Mainnet, suffix: None } => Some(MAINNET_PARAMS),
-                    NetworkId { network_type: NetworkType::Testnet, suffix: Some(10) } => Some(TESTNET_PARAMS),
-                    _ => None,
-                };
-                if let Some(params) = params {
-                    if let Some(rpc_url) = &self.cli_args.rpc_url {
-                        Some(format!("{}:{}", Url::parse(rpc_url).unwrap().host().unwrap().to_string(), params.default_p2p_port()))
-                    } else {
-                        Some(format!("{}:{}", params.dns_seeders.choose(&mut rng()).unwrap().to_string(), params.default_p2p_port()))
-                    }
-                } else {
-                    None
-                }
-            };
+            let address = resolve_p2p_address(&self.cli_args, self.network_id);
             if let Some(address) = address {
                 info!("Connecting P2P for UTXO set import using {}", address);
                 let (sender, receiver) = mpsc::channel(10000);
@@ -0,0 +1,221 @@
+use crate::indexer::{db_connect_options, read_layout};
+use log::{error, warn};
+use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable, CliField};
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_database::layout::AddressMapping;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Issue {
+    /// The indexer would silently produce wrong or missing data
+    Fatal(String),
+    /// Works, but some functionality has no effect or is degraded
+    Degraded(String),
+}
+
+/// Validates the options and checks them against the database schema. Degraded combinations are logged as warnings,
+/// fatal contradictions as errors. Returns false if any fatal contradiction is found
+pub async fn validate(cli_args: &CliArgs, database: &KaspaDbClient) -> bool {
+    let mut issues = validate_cli_args(cli_args);
+    issues.extend(validate_schema(cli_args, database).await);
+    let mut fatal = 0;
+    for issue in issues {
+        match issue {
+            Issue::Fatal(msg) => {
+                error!("{msg}");
+                fatal += 1;
+            }
+            Issue::Degraded(msg) => warn!("{msg}"),
+        }
+    }
+    if fatal > 0 {
+        error!("Found {fatal} invalid option combination(s), see above");
+    }
+    fatal == 0
+}
+
+pub fn validate_cli_args(cli_args: &CliArgs) -> Vec<Issue> {
+    let mut issues = vec![];
+    let resolve_inputs = cli_args.is_enabled(CliEnable::TransactionsInputsResolve);
+    let disable_vcp = cli_args.is_disabled(CliDisable::VirtualChainProcessing);
+    let disable_transactions = cli_args.is_disabled(CliDisable::TransactionProcessing);
+
+    if resolve_inputs && cli_args.is_excluded(CliField::TxInPreviousOutpoint) {
+        issues.push(Issue::Fatal(
+            "transactions_inputs_resolve requires tx_in_previous_outpoint, which is excluded. Include the field or disable resolving"
+                .to_string(),
+        ));
+    }
+    if resolve_inputs && cli_args.is_disabled(CliDisable::TransactionsOutputsTable) {
+        issues.push(Issue::Fatal(
+            "transactions_inputs_resolve looks up previous outpoints in transactions_outputs, which is disabled".to_string(),
+        ));
+    }
+    if resolve_inputs && (cli_args.is_excluded(CliField::TxOutAmount) || cli_args.is_excluded(CliField::TxOutScriptPublicKey)) {
+        issues.push(Issue::Degraded(
+            "tx_out_amount and/or tx_out_script_public_key is excluded, inputs will be resolved without them".to_string(),
+        ));
+    }
+    if resolve_inputs && cli_args.is_disabled(CliDisable::TransactionsInputsTable) {
+        issues.push(Issue::Degraded("transactions_inputs_resolve has no effect, transactions_inputs_table is disabled".to_string()));
+    }
+    if !disable_transactions
+        && !cli_args.is_disabled(CliDisable::AddressesTransactionsTable)
+        && read_layout(cli_args).address_mapping == AddressMapping::None
+    {
+        issues.push(Issue::Degraded(
+            "Both tx_out_script_public_key and tx_out_script_public_key_address are excluded, addresses_transactions will not be populated. \
+            Disable addresses_transactions_table to make this explicit"
+                .to_string(),
+        ));
+    }
+    if disable_vcp && cli_args.is_disabled(CliDisable::VcpWaitForSync) {
+        issues.push(Issue::Degraded("vcp_wait_for_sync is disabled, but has no effect as VCP is disabled".to_string()));
+    }
+    if disable_vcp && cli_args.is_disabled(CliDisable::TransactionAcceptance) {
+        issues.push(Issue::Degraded("transaction_acceptance is disabled, but has no effect as VCP is disabled".to_string()));
+    }
+    if cli_args.is_enabled(CliEnable::AddressBalanceChangesTable)
+        && [
+            CliDisable::VirtualChainProcessing,
+            CliDisable::TransactionAcceptance,
+            CliDisable::TransactionProcessing,
+            CliDisable::BlocksTable,
+            CliDisable::TransactionsInputsTable,
+            CliDisable::TransactionsOutputsTable,
+        ]
+        .into_iter()
+        .any(|d| cli_args.is_disabled(d))
+    {
+        issues.push(Issue::Fatal(
+            "address_balance_changes_table requires VCP, transaction acceptance, transaction processing and the blocks, inputs and outputs tables".to_string(),
+        ));
+    }
+    if cli_args.is_enabled(CliEnable::AddressBalanceChangesTable) {
+        let excluded: Vec<&str> = [
+            (CliField::TxOutScriptPublicKeyAddress, "tx_out_script_public_key_address"),
+            (CliField::TxOutAmount, "tx_out_amount"),
+            (CliField::TxInPreviousOutpoint, "tx_in_previous_outpoint"),
+        ]
+        .into_iter()
+        .filter(|(field, _)| cli_args.is_excluded(field.clone()))
+        .map(|(_, name)| name)
+        .collect();
+        if !excluded.is_empty() {
+            issues.push(Issue::Fatal(format!(
+                "address_balance_changes_table is computed from the output addresses and amounts and the spent outpoints, \
+                but {} is excluded",
+                excluded.join(", ")
+            )));
+        }
+    }
+    if disable_transactions {
+        for (feature, name) in [
+            (CliEnable::TransactionsInputsResolve, "transactions_inputs_resolve"),
+            (CliEnable::BlockRewardsTable, "block_rewards_table"),
+            (CliEnable::Krc20OperationsTable, "krc20_operations_table"),
+            (CliEnable::P2pBlockDownload, "p2p_block_download"),
+        ] {
+            if cli_args.is_enabled(feature) {
+                issues.push(Issue::Degraded(format!("{name} is enabled, but has no effect as transaction processing is disabled")));
+            }
+        }
+    }
+    if cli_args.database_min_connections > cli_args.database_max_connections {
+        issues.push(Issue::Fatal(format!(
+            "database_min_connections ({}) exceeds database_max_connections ({})",
+            cli_args.database_min_connections, cli_args.database_max_connections
+        )));
+    }
+    if cli_args.database_ssl_root_cert.is_some()
+        && db_connect_options(cli_args).verifies_server_certificate(&cli_args.database_url).is_ok_and(|verifies| !verifies)
+    {
+        issues.push(Issue::Degraded(
+            "database_ssl_root_cert is set, but the server certificate is only verified with sslmode verify-ca or verify-full"
+                .to_string(),
+        ));
+    }
+    if cli_args.include_fields.is_some() && cli_args.exclude_fields.is_some() {
+        issues.push(Issue::Degraded("Both include_fields and exclude_fields are set, exclude_fields is ignored".to_string()));
+    }
+    issues
+}
+
+/// Checks that the tables needed by the options exist, and that the optional indexes for the chosen layout are present
+pub async fn validate_schema(cli_args: &CliArgs, database: &KaspaDbClient) -> Vec<Issue> {
+    let mut issues = vec![];
+    let tables: HashSet<String> = match database.select_all_table_details().await {
+        Ok(tables) => tables.into_iter().map(|t| t.name).collect(),
+        Err(e) => return vec![Issue::Degraded(format!("Unable to validate the database schema: {e}"))],
+    };
+    for table in required_tables(cli_args) {
+        if !tables.contains(table) {
+            issues.push(Issue::Fatal(format!("Table {table} is required by the options, but does not exist")));
+        }
+    }
+    // Without a mapping table, addresses are looked up directly in the (resolved) inputs and outputs, see README
+    if cli_args.is_disabled(CliDisable::AddressesTransactionsTable)
+        && cli_args.is_enabled(CliEnable::TransactionsInputsResolve)
+        && !cli_args.is_disabled(CliDisable::TransactionProcessing)
+    {
+        for (table, column) in [("transactions_inputs", "previous_outpoint_script"), ("transactions_outputs", "script_public_key")] {
+            if !tables.contains(table) {
+                continue;
+            }
+            match database.select_index_definitions(table).await {
+                Ok(definitions) if !definitions.iter().any(|d| d.contains(&format!("({column}"))) => {
+                    issues.push(Issue::Degraded(format!(
+                        "addresses_transactions_table is disabled, but {table} has no index on {column}, address lookups will be slow"
+                    )));
+                }
+                Ok(_) => {}
+                Err(e) => issues.push(Issue::Degraded(format!("Unable to check the indexes of {table}: {e}"))),
+            }
+        }
+    }
+    issues
+}
+
+fn required_tables(cli_args: &CliArgs) -> Vec<&'static str> {
+    let mut tables = vec!["vars"];
+    if !cli_args.is_disabled(CliDisable::BlocksTable) {
+        tables.push("blocks");
+    }
+    if !cli_args.is_disabled(CliDisable::BlockParentTable) {
+        tables.push("block_parent");
+    }
+    if !cli_args.is_disabled(CliDisable::VirtualChainProcessing) && !cli_args.is_disabled(CliDisable::TransactionAcceptance) {
+        tables.push("transactions_acceptances");
+    }
+    if !cli_args.is_disabled(CliDisable::TransactionProcessing) {
+        tables.push("subnetworks");
+        if !cli_args.is_disabled(CliDisable::TransactionsTable) {
+            tables.push("transactions");
+        }
+        if !cli_args.is_disabled(CliDisable::BlocksTransactionsTable) {
+            tables.push("blocks_transactions");
+        }
+        if !cli_args.is_disabled(CliDisable::TransactionsInputsTable) {
+            tables.push("transactions_inputs");
+        }
+        if !cli_args.is_disabled(CliDisable::TransactionsOutputsTable) {
+            tables.push("transactions_outputs");
+        }
+        if let Some(table) = read_layout(cli_args).address_mapping.table() {
+            tables.push(table);
+        }
+        if cli_args.is_enabled(CliEnable::BlockRewardsTable) {
+            tables.push("block_rewards");
+        }
+        if cli_args.is_enabled(CliEnable::Krc20OperationsTable) {
+            tables.push("krc20_operations");
+        }
+    }
+    if cli_args.is_enabled(CliEnable::AddressBalanceChangesTable) {
+        tables.push("address_balance_changes");
+    }
+    if cli_args.is_enabled(CliEnable::MempoolProcessing) {
+        tables.extend(["mempool_transactions", "mempool_inputs", "mempool_outputs"]);
+    }
+    tables
+}
@@ -1,15 +1,21 @@
 use std::cmp::min;
 
+use crate::retry::retry_db;
+use crate::web::model::metrics::Metrics;
 use kaspa_rpc_core::RpcAcceptedTransactionIds;
 use log::{debug, trace};
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_database::models::transaction_acceptance::TransactionAcceptance;
+use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use tokio::sync::RwLock;
 
 pub async fn accept_transactions(
     batch_scale: f64,
+    enable_balance_changes: bool,
     accepted_transaction_ids: &[RpcAcceptedTransactionIds],
+    metrics: &RwLock<Metrics>,
     database: &KaspaDbClient,
-) -> u64 {
+) -> (u64, u64) {
     let batch_size = min((500f64 * batch_scale) as usize, 7500);
     if log::log_enabled!(log::Level::Debug) {
         let accepted_count = accepted_transaction_ids.iter().map(|t| t.accepted_transaction_ids.len()).sum::<usize>();
@@ -17,7 +23,9 @@ pub async fn accept_transactions(
         trace!("Accepted transaction ids: \n{:#?}", accepted_transaction_ids);
     }
     let mut rows_added = 0;
+    let mut balance_changes_added = 0;
     let mut accepted_transactions = vec![];
+    let mut accepting_block_hashes: Vec<SqlHash> = vec![];
     for accepted_id in accepted_transaction_ids {
         // Commit all transactions for an accepting block in the same go to avoid incomplete checkpoints:
         accepted_transactions.extend(
@@ -30,13 +38,33 @@ pub async fn accept_transactions(
                 })
                 .collect::<Vec<_>>(),
         );
+        accepting_block_hashes.push(accepted_id.accepting_block_hash.into());
         if accepted_transactions.len() >= batch_size {
-            rows_added += database.insert_transaction_acceptances(&accepted_transactions).await.unwrap();
+            rows_added +=
+                retry_db("transactions_acceptances", metrics, || database.insert_transaction_acceptances(&accepted_transactions))
+                    .await
+                    .unwrap_or_else(|e| panic!("Insert transactions_acceptances FAILED: {e}"));
+            if enable_balance_changes {
+                balance_changes_added +=
+                    retry_db("address_balance_changes", metrics, || database.insert_address_balance_changes(&accepting_block_hashes))
+                        .await
+                        .unwrap_or_else(|e| panic!("Insert address_balance_changes FAILED: {e}"));
+            }
             accepted_transactions = vec![];
+            accepting_block_hashes = vec![];
         }
     }
     if !accepted_transactions.is_empty() {
-        rows_added += database.insert_transaction_acceptances(&accepted_transactions).await.unwrap();
+        rows_added +=
+            retry_db("transactions_acceptances", metrics, || database.insert_transaction_acceptances(&accepted_transactions))
+                .await
+                .unwrap_or_else(|e| panic!("Insert transactions_acceptances FAILED: {e}"));
+        if enable_balance_changes {
+            balance_changes_added +=
+                retry_db("address_balance_changes", metrics, || database.insert_address_balance_changes(&accepting_block_hashes))
+                    .await
+                    .unwrap_or_else(|e| panic!("Insert address_balance_changes FAILED: {e}"));
+        }
     }
-    rows_added
+    (rows_added, balance_changes_added)
 }
@@ -1,11 +1,14 @@
 use std::cmp::min;
 
+use crate::retry::retry_db;
+use crate::web::model::metrics::Metrics;
 use kaspa_rpc_core::RpcHash;
 use log::{debug, trace};
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_database::models::transaction_acceptance::TransactionAcceptance;
+use tokio::sync::RwLock;
 
-pub async fn add_chain_blocks(batch_scale: f64, added_hashes: &[RpcHash], database: &KaspaDbClient) -> u64 {
+pub async fn add_chain_blocks(batch_scale: f64, added_hashes: &[RpcHash], metrics: &RwLock<Metrics>, database: &KaspaDbClient) -> u64 {
     let batch_size = min((500f64 * batch_scale) as usize, 7500);
     if log::log_enabled!(log::Level::Debug) {
         let accepting_blocks = added_hashes.len();
@@ -16,7 +19,10 @@ pub async fn add_chain_blocks(batch_scale: f64, added_hashes: &[RpcHash], databa
     for added_hashes_chunk in added_hashes.chunks(batch_size) {
         let accepted_transactions: Vec<_> =
             added_hashes_chunk.iter().map(|b| TransactionAcceptance { transaction_id: None, block_hash: (*b).into() }).collect();
-        rows_added += database.insert_transaction_acceptances(&accepted_transactions).await.unwrap();
+        rows_added +=
+            retry_db("transactions_acceptances", metrics, || database.insert_transaction_acceptances(&accepted_transactions))
+                .await
+                .unwrap_or_else(|e| panic!("Insert transactions_acceptances FAILED: {e}"));
     }
     rows_added
 }
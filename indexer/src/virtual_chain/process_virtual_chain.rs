@@ -1,5 +1,6 @@
 use crate::checkpoint::{CheckpointBlock, CheckpointOrigin};
-use crate::settings::Settings;
+use crate::hooks::CommittedBatch;
+use crate::settings::{runtime_changed, Settings};
 use crate::virtual_chain::accept_transactions::accept_transactions;
 use crate::virtual_chain::add_chain_blocks::add_chain_blocks;
 use crate::virtual_chain::remove_chain_blocks::remove_chain_blocks;
@@ -7,10 +8,10 @@ use crate::web::model::metrics::Metrics;
 use chrono::DateTime;
 use crossbeam_queue::ArrayQueue;
 use deadpool::managed::{Object, Pool};
-use kaspa_rpc_core::api::rpc::RpcApi;
 use log::{debug, error, info, warn};
 use simply_kaspa_cli::cli_args::{CliDisable, CliEnable};
 use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_kaspad::api::KaspadApi;
 use simply_kaspa_kaspad::pool::manager::KaspadManager;
 use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -28,10 +29,13 @@ pub async fn process_virtual_chain(
     kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
     database: KaspaDbClient,
 ) {
-    let batch_scale = settings.cli_args.batch_scale;
+    let mut runtime = settings.runtime.clone();
+    let mut batch_scale = settings.cli_args.batch_scale;
     let disable_transaction_acceptance = settings.cli_args.is_disabled(CliDisable::TransactionAcceptance);
+    let enable_balance_changes =
+        !disable_transaction_acceptance && settings.cli_args.is_enabled(CliEnable::AddressBalanceChangesTable);
 
-    let poll_interval = Duration::from_secs(settings.cli_args.vcp_interval as u64);
+    let mut poll_interval = Duration::from_secs(settings.cli_args.vcp_interval as u64);
     let err_delay = Duration::from_secs(5);
 
     let mut start_hash = settings.checkpoint;
@@ -42,9 +46,20 @@ pub async fn process_virtual_chain(
     let mut tip_distance = if dynamic_tip_distance { 10 } else { 0 };
     let mut tip_distance_timestamp = 0;
     let mut tip_distance_history = VecDeque::new();
-    let tip_distance_window = settings.cli_args.vcp_window.saturating_div(settings.cli_args.vcp_interval as u16).max(1) as usize;
+    let mut tip_distance_window = settings.cli_args.vcp_window.saturating_div(settings.cli_args.vcp_interval as u16).max(1) as usize;
 
     while run.load(Ordering::Relaxed) {
+        if let Some(runtime_settings) = runtime_changed(&mut runtime) {
+            batch_scale = runtime_settings.batch_scale;
+            poll_interval = Duration::from_secs(runtime_settings.vcp_interval as u64);
+            tip_distance_window = runtime_settings.vcp_window.saturating_div(runtime_settings.vcp_interval as u16).max(1) as usize;
+            tip_distance_history.truncate(tip_distance_window);
+            debug!(
+                "Virtual chain processor reloaded, poll interval: {}s, tip distance window: {}",
+                poll_interval.as_secs(),
+                tip_distance_window
+            );
+        }
         if !start_vcp.load(Ordering::Relaxed) {
             debug!("Virtual chain processor waiting for start notification");
             sleep(err_delay).await;
@@ -71,18 +86,33 @@ pub async fn process_virtual_chain(
                                 blue_score: last_accepting_block.header.blue_score,
                             };
                             let start_commit_time = Instant::now();
-                            let rows_removed = remove_chain_blocks(batch_scale, removed_chain_block_hashes, &database).await;
+                            let rows_removed = remove_chain_blocks(
+                                batch_scale,
+                                enable_balance_changes,
+                                removed_chain_block_hashes,
+                                &metrics,
+                                &database,
+                            )
+                            .await;
                             if !disable_transaction_acceptance {
-                                let rows_added = accept_transactions(batch_scale, accepted_transaction_ids, &database).await;
+                                let (rows_added, balance_changes_added) = accept_transactions(
+                                    batch_scale,
+                                    enable_balance_changes,
+                                    accepted_transaction_ids,
+                                    &metrics,
+                                    &database,
+                                )
+                                .await;
                                 info!(
-                                    "Committed {} accepted and {} rejected transactions in {}ms. Last accepted: {}",
+                                    "Committed {} accepted and {} rejected transactions ({} bal_chg) in {}ms. Last accepted: {}",
                                     rows_added,
                                     rows_removed,
+                                    balance_changes_added,
                                     Instant::now().duration_since(start_commit_time).as_millis(),
                                     chrono::DateTime::from_timestamp_millis(checkpoint_block.timestamp as i64 / 1000 * 1000).unwrap()
                                 );
                             } else {
-                                let rows_added = add_chain_blocks(batch_scale, added_chain_block_hashes, &database).await;
+                                let rows_added = add_chain_blocks(batch_scale, added_chain_block_hashes, &metrics, &database).await;
                                 info!(
                                     "Committed {} added and {} removed chain blocks in {}ms. Last added: {}",
                                     rows_added,
@@ -126,6 +156,15 @@ pub async fn process_virtual_chain(
                                 .flatten();
                             drop(metrics);
 
+                            if !settings.hooks.is_empty() {
+                                settings.hooks.notify(CommittedBatch::VirtualChain {
+                                    added: added_chain_block_hashes.iter().map(|h| (*h).into()).collect(),
+                                    removed: removed_chain_block_hashes.iter().map(|h| (*h).into()).collect(),
+                                    last_block: checkpoint_block.clone(),
+                                    commit_time: start_commit_time.elapsed(),
+                                });
+                            }
+
                             while checkpoint_queue.push(checkpoint_block.clone()).is_err() {
                                 warn!("Checkpoint queue is full");
                                 sleep(Duration::from_secs(1)).await;
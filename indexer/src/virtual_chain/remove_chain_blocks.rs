@@ -1,10 +1,19 @@
 use std::cmp::min;
 
+use crate::retry::retry_db;
+use crate::web::model::metrics::Metrics;
 use kaspa_rpc_core::RpcHash;
 use log::{debug, trace};
 use simply_kaspa_database::client::KaspaDbClient;
+use tokio::sync::RwLock;
 
-pub async fn remove_chain_blocks(batch_scale: f64, removed_hashes: &[RpcHash], database: &KaspaDbClient) -> u64 {
+pub async fn remove_chain_blocks(
+    batch_scale: f64,
+    enable_balance_changes: bool,
+    removed_hashes: &[RpcHash],
+    metrics: &RwLock<Metrics>,
+    database: &KaspaDbClient,
+) -> u64 {
     let batch_size = min((500f64 * batch_scale) as usize, 7500);
     if log::log_enabled!(log::Level::Debug) {
         debug!("Received {} removed chain blocks", removed_hashes.len());
@@ -13,7 +22,15 @@ pub async fn remove_chain_blocks(batch_scale: f64, removed_hashes: &[RpcHash], d
     let mut rows_removed = 0;
     let removed_blocks = removed_hashes.iter().map(|h| h.to_owned().into()).collect::<Vec<_>>();
     for removed_blocks_chunk in removed_blocks.chunks(batch_size) {
-        rows_removed += database.delete_transaction_acceptances(removed_blocks_chunk).await.unwrap();
+        if enable_balance_changes {
+            retry_db("address_balance_changes", metrics, || database.delete_address_balance_changes(removed_blocks_chunk))
+                .await
+                .unwrap_or_else(|e| panic!("Delete address_balance_changes FAILED: {e}"));
+        }
+        rows_removed +=
+            retry_db("transactions_acceptances", metrics, || database.delete_transaction_acceptances(removed_blocks_chunk))
+                .await
+                .unwrap_or_else(|e| panic!("Delete transactions_acceptances FAILED: {e}"));
     }
     rows_removed
 }
@@ -1,3 +1,4 @@
+use crate::kaspad_sync::KaspadSyncState;
 use crate::web::endpoint::metrics::update_metrics;
 use crate::web::model::health::{Health, HealthIndexer, HealthIndexerDetails, HealthIndexerInfo, HealthKaspad, HealthStatus};
 use crate::web::model::metrics::{Metrics, MetricsBlock};
@@ -7,8 +8,8 @@ use axum::response::IntoResponse;
 use axum::{Extension, Json};
 use chrono::Utc;
 use deadpool::managed::Pool;
-use kaspa_rpc_core::api::rpc::RpcApi;
 use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_kaspad::api::KaspadApi;
 use simply_kaspa_kaspad::pool::manager::KaspadManager;
 use std::sync::Arc;
 use std::time::Duration;
@@ -33,14 +34,18 @@ pub async fn get_health(
     Extension(system): Extension<Arc<RwLock<System>>>,
     Extension(database_client): Extension<KaspaDbClient>,
 ) -> impl IntoResponse {
-    let health_kaspad: HealthKaspad = match kaspad_pool.get().await {
+    let mut health_kaspad: HealthKaspad = match kaspad_pool.get().await {
         Ok(kaspad_client) => match kaspad_client.get_server_info().await {
             Ok(server_info) => server_info.into(),
             Err(e) => (HealthStatus::DOWN, e.to_string()).into(),
         },
         Err(e) => (HealthStatus::DOWN, e.to_string()).into(),
     };
+    health_kaspad.nodes = Some(kaspad_pool.manager().nodes.statuses().into_iter().map(|n| n.into()).collect());
     let metrics = update_metrics(metrics, system, database_client).await;
+    if metrics.kaspad_sync.state != KaspadSyncState::Synced.to_string() {
+        health_kaspad.sync = Some(metrics.kaspad_sync.clone());
+    }
 
     let health_indexer = indexer_health(metrics, health_kaspad.virtual_daa_score).await;
 
@@ -86,7 +91,26 @@ async fn indexer_health(metrics: Metrics, current_daa: Option<u64>) -> HealthInd
         reason: format!("Utilization: {}%", queue_utilization),
     });
 
-    let net_bps = metrics.settings.as_ref().map(|s| s.net_bps as u64).unwrap_or(10);
+    let recently_retried =
+        metrics.database.last_retry.is_some_and(|t| (Utc::now().timestamp_millis() as u64).saturating_sub(t) < 300_000);
+    health_details.push(HealthIndexerDetails {
+        name: "database".to_string(),
+        status: if metrics.database.failures > 0 {
+            HealthStatus::DOWN
+        } else if recently_retried {
+            HealthStatus::WARN
+        } else {
+            HealthStatus::UP
+        },
+        reason: format!(
+            "{} retries, {} failures{}",
+            metrics.database.retries,
+            metrics.database.failures,
+            metrics.database.last_error.as_ref().map(|e| format!(", last error: {e}")).unwrap_or_default()
+        ),
+    });
+
+    let net_bps = metrics.settings.as_ref().map(|s| s.network.bps(current_daa.unwrap_or_default()) as u64).unwrap_or(10);
     health_details.push(indexer_details("checkpoint".to_string(), net_bps, current_daa, 120, 600, metrics.checkpoint.block.as_ref()));
 
     if metrics.components.utxo_importer.enabled {
@@ -0,0 +1,43 @@
+use crate::web::model::krc20::{Krc20Operation, Krc20OperationsQuery};
+use crate::web::web_server;
+use axum::extract::{Path, Query};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use log::warn;
+use simply_kaspa_database::client::KaspaDbClient;
+
+pub const PATH: &str = "/api/krc20/{tick}/operations";
+
+const MAX_LIMIT: i64 = 500;
+
+#[utoipa::path(
+    method(get),
+    path = PATH,
+    tag = web_server::DATA_TAG,
+    description = "Get KRC-20 operations for a tick, newest first",
+    params(("tick" = String, Path, description = "Token tick, case insensitive"), Krc20OperationsQuery),
+    responses(
+        (status = StatusCode::OK, description = "Success", body = Vec<Krc20Operation>, content_type = "application/json"),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid limit or offset"),
+        (status = StatusCode::SERVICE_UNAVAILABLE, description = "Database unavailable")
+    )
+)]
+pub async fn get_krc20_operations(
+    Path(tick): Path<String>,
+    Query(query): Query<Krc20OperationsQuery>,
+    Extension(database_client): Extension<KaspaDbClient>,
+) -> impl IntoResponse {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+    if !(1..=MAX_LIMIT).contains(&limit) || offset < 0 {
+        return (StatusCode::BAD_REQUEST, format!("limit must be 1-{MAX_LIMIT} and offset non-negative")).into_response();
+    }
+    match database_client.select_krc20_operations_by_tick(&tick.to_uppercase(), limit, offset).await {
+        Ok(operations) => Json(operations.into_iter().map(Krc20Operation::from).collect::<Vec<_>>()).into_response(),
+        Err(e) => {
+            warn!("Failed to select KRC-20 operations for {tick}: {e}");
+            StatusCode::SERVICE_UNAVAILABLE.into_response()
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::web::model::metrics::Metrics;
+use crate::web::model::metrics::{Metrics, MetricsDb};
 use crate::web::web_server;
 use axum::response::IntoResponse;
 use axum::{Extension, Json};
@@ -54,13 +54,30 @@ pub async fn update_metrics(metrics: Arc<RwLock<Metrics>>, system: Arc<RwLock<Sy
         metrics.process.uptime_pretty = Some(humantime::format_duration(Duration::from_secs(uptime_seconds)).to_string());
     }
     match database_client.select_database_details().await {
-        Ok(database_details) => metrics.database = database_details.into(),
+        Ok(database_details) => {
+            // The retry statistics are reported by the processors, keep them
+            let previous = std::mem::take(&mut metrics.database);
+            metrics.database = MetricsDb {
+                retries: previous.retries,
+                failures: previous.failures,
+                last_retry: previous.last_retry,
+                last_retry_date_time: previous.last_retry_date_time,
+                last_error: previous.last_error,
+                ..database_details.into()
+            };
+        }
         Err(e) => warn!("Failed to select database details: {:?}", e),
     }
     match database_client.select_all_table_details().await {
         Ok(all_table_details) => metrics.database.tables = Some(all_table_details.into_iter().map(|td| td.into()).collect()),
         Err(e) => warn!("Failed to select all table details: {:?}", e),
     }
+    metrics.database.pool = Some(database_client.pool_status().into());
+    if let Some(settings) = metrics.settings.as_mut() {
+        // Reflect reloaded options
+        let runtime = settings.runtime.borrow().clone();
+        runtime.apply_to(&mut settings.cli_args);
+    }
     metrics.clone()
 }
 
@@ -0,0 +1,41 @@
+use crate::web::model::miners::{Miner, MinersQuery};
+use crate::web::web_server;
+use axum::extract::Query;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::{Extension, Json};
+use chrono::Utc;
+use log::warn;
+use simply_kaspa_database::client::KaspaDbClient;
+
+pub const PATH: &str = "/api/miners";
+
+const DEFAULT_RANGE_MS: i64 = 24 * 3600 * 1000;
+const MAX_RANGE_MS: i64 = 31 * DEFAULT_RANGE_MS;
+
+#[utoipa::path(
+    method(get),
+    path = PATH,
+    tag = web_server::DATA_TAG,
+    description = "Get per miner block and subsidy statistics for a time range (max 31 days), requires the block_rewards table",
+    params(MinersQuery),
+    responses(
+        (status = StatusCode::OK, description = "Success", body = Vec<Miner>, content_type = "application/json"),
+        (status = StatusCode::BAD_REQUEST, description = "Invalid time range"),
+        (status = StatusCode::SERVICE_UNAVAILABLE, description = "Database unavailable")
+    )
+)]
+pub async fn get_miners(Query(query): Query<MinersQuery>, Extension(database_client): Extension<KaspaDbClient>) -> impl IntoResponse {
+    let to = query.to.unwrap_or_else(|| Utc::now().timestamp_millis());
+    let from = query.from.unwrap_or(to - DEFAULT_RANGE_MS);
+    if from >= to || to - from > MAX_RANGE_MS {
+        return (StatusCode::BAD_REQUEST, "from must be before to, with a range of at most 31 days").into_response();
+    }
+    match database_client.select_miner_statistics(from, to).await {
+        Ok(miners) => Json(miners.into_iter().map(Miner::from).collect::<Vec<_>>()).into_response(),
+        Err(e) => {
+            warn!("Failed to select miner statistics: {e}");
+            StatusCode::SERVICE_UNAVAILABLE.into_response()
+        }
+    }
+}
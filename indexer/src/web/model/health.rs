@@ -1,6 +1,8 @@
+use crate::web::model::metrics::MetricsKaspadSync;
 use kaspa_rpc_core::GetServerInfoResponse;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
+use simply_kaspa_kaspad::pool::nodes::KaspadNodeStatus;
 use std::fmt;
 use utoipa::ToSchema;
 
@@ -64,12 +66,65 @@ pub struct HealthKaspad {
     #[schema(example = "102253066")]
     pub virtual_daa_score: Option<u64>,
     pub error: Option<String>,
+    /// Present while the indexer waits for kaspad to be synced
+    pub sync: Option<MetricsKaspadSync>,
+    pub nodes: Option<Vec<HealthKaspadNode>>,
+}
+
+#[skip_serializing_none]
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthKaspadNode {
+    #[schema(example = "localhost:17110")]
+    pub name: String,
+    pub status: HealthStatus,
+    #[schema(example = "true")]
+    pub is_synced: bool,
+    #[schema(example = "0.16.0")]
+    pub server_version: Option<String>,
+    #[schema(example = "102253066")]
+    pub virtual_daa_score: Option<u64>,
+    #[schema(example = "12")]
+    pub latency_ms: Option<u64>,
+    #[schema(example = "0.05")]
+    pub error_rate: f64,
+    pub error: Option<String>,
+}
+
+impl From<KaspadNodeStatus> for HealthKaspadNode {
+    fn from(node_status: KaspadNodeStatus) -> Self {
+        HealthKaspadNode {
+            status: if node_status.is_healthy() {
+                HealthStatus::UP
+            } else if node_status.connected {
+                HealthStatus::WARN
+            } else {
+                HealthStatus::DOWN
+            },
+            name: node_status.name,
+            is_synced: node_status.synced,
+            server_version: node_status.server_version,
+            virtual_daa_score: node_status.virtual_daa_score,
+            latency_ms: node_status.latency_ms,
+            error_rate: node_status.error_rate,
+            error: node_status.last_error,
+        }
+    }
 }
 
 impl From<(HealthStatus, String)> for HealthKaspad {
     fn from(status_error: (HealthStatus, String)) -> Self {
         let (status, error) = status_error;
-        HealthKaspad { status, server_version: None, network_id: None, virtual_daa_score: None, is_synced: None, error: Some(error) }
+        HealthKaspad {
+            status,
+            server_version: None,
+            network_id: None,
+            virtual_daa_score: None,
+            is_synced: None,
+            error: Some(error),
+            sync: None,
+            nodes: None,
+        }
     }
 }
 
@@ -83,6 +138,8 @@ impl From<GetServerInfoResponse> for HealthKaspad {
             network_id: Some(get_server_info_response.network_id.to_string()),
             virtual_daa_score: Some(get_server_info_response.virtual_daa_score),
             error: if !is_synced { Some("Kaspad is not synced".to_string()) } else { None },
+            sync: None,
+            nodes: None,
         }
     }
 }
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use simply_kaspa_database::models::query::krc20_operation_status::Krc20OperationStatus;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct Krc20OperationsQuery {
+    /// Maximum number of operations to return (1-500)
+    #[param(default = 50)]
+    pub limit: Option<i64>,
+    #[param(default = 0)]
+    pub offset: Option<i64>,
+}
+
+#[skip_serializing_none]
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Krc20Operation {
+    pub transaction_id: String,
+    pub index: i16,
+    #[schema(example = "mint")]
+    pub operation: String,
+    #[schema(example = "KASP")]
+    pub tick: Option<String>,
+    pub amount: Option<String>,
+    pub to_address: Option<String>,
+    #[schema(example = "1738706345528")]
+    pub block_time: i64,
+    pub accepting_block_hash: Option<String>,
+}
+
+impl From<Krc20OperationStatus> for Krc20Operation {
+    fn from(operation: Krc20OperationStatus) -> Self {
+        Krc20Operation {
+            transaction_id: operation.transaction_id.to_string(),
+            index: operation.index,
+            operation: operation.operation,
+            tick: operation.tick,
+            amount: operation.amount,
+            to_address: operation.to_address,
+            block_time: operation.block_time,
+            accepting_block_hash: operation.accepting_block_hash.map(|h| h.to_string()),
+        }
+    }
+}
@@ -5,6 +5,7 @@ use bytesize::ByteSize;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use simply_kaspa_database::models::query::database_details::DatabaseDetails;
+use simply_kaspa_database::models::query::pool_status::PoolStatus;
 use simply_kaspa_database::models::query::table_details::TableDetails;
 use utoipa::ToSchema;
 
@@ -17,6 +18,7 @@ pub struct Metrics {
     pub settings: Option<Settings>,
     pub process: MetricsProcess,
     pub queues: MetricsQueues,
+    pub kaspad_sync: MetricsKaspadSync,
     pub checkpoint: MetricsCheckpoint,
     pub components: MetricsComponent,
     pub database: MetricsDb,
@@ -31,6 +33,7 @@ impl Metrics {
             settings: None,
             process: MetricsProcess::new(),
             queues: MetricsQueues::new(),
+            kaspad_sync: MetricsKaspadSync::new(),
             checkpoint: MetricsCheckpoint::new(),
             components: MetricsComponent::new(),
             database: MetricsDb::new(),
@@ -101,6 +104,52 @@ impl MetricsQueues {
     }
 }
 
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsKaspadSync {
+    #[schema(example = "Synced")]
+    pub state: String,
+    #[schema(example = "true")]
+    pub is_synced: Option<bool>,
+    #[schema(example = "98544183")]
+    pub header_count: Option<u64>,
+    #[schema(example = "98544171")]
+    pub block_count: Option<u64>,
+    #[schema(example = "102253066")]
+    pub virtual_daa_score: Option<u64>,
+    /// Estimated from the time passed since kaspad's past median time
+    #[schema(example = "102253066")]
+    pub network_daa_score: Option<u64>,
+    #[schema(example = "100.0")]
+    pub progress_percent: Option<f64>,
+    #[schema(example = "1738706345528")]
+    pub last_updated: Option<u64>,
+    #[schema(example = "2025-02-04T21:59:05.528Z")]
+    pub last_updated_date_time: Option<DateTime<Utc>>,
+}
+
+impl Default for MetricsKaspadSync {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsKaspadSync {
+    pub fn new() -> Self {
+        Self {
+            state: "Connecting".to_string(),
+            is_synced: None,
+            header_count: None,
+            block_count: None,
+            virtual_daa_score: None,
+            network_daa_score: None,
+            progress_percent: None,
+            last_updated: None,
+            last_updated_date_time: None,
+        }
+    }
+}
+
 #[derive(ToSchema, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MetricsCheckpoint {
@@ -129,6 +178,7 @@ pub struct MetricsComponent {
     pub block_processor: MetricsComponentBlockProcessor,
     pub transaction_processor: MetricsComponentTransactionProcessor,
     pub virtual_chain_processor: MetricsComponentVirtualChainProcessor,
+    pub mempool_processor: MetricsComponentMempoolProcessor,
 }
 
 impl Default for MetricsComponent {
@@ -145,6 +195,7 @@ impl MetricsComponent {
             block_processor: MetricsComponentBlockProcessor::new(),
             transaction_processor: MetricsComponentTransactionProcessor::new(),
             virtual_chain_processor: MetricsComponentVirtualChainProcessor::new(),
+            mempool_processor: MetricsComponentMempoolProcessor::new(),
         }
     }
 }
@@ -192,6 +243,8 @@ impl MetricsComponentBlockFetcher {
 #[derive(ToSchema, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MetricsComponentBlockProcessor {
+    #[schema(example = "800")]
+    pub batch_size: Option<u64>,
     pub last_block: Option<MetricsBlock>,
 }
 
@@ -203,7 +256,7 @@ impl Default for MetricsComponentBlockProcessor {
 
 impl MetricsComponentBlockProcessor {
     pub fn new() -> Self {
-        Self { last_block: None }
+        Self { batch_size: None, last_block: None }
     }
 }
 
@@ -211,6 +264,8 @@ impl MetricsComponentBlockProcessor {
 #[serde(rename_all = "camelCase")]
 pub struct MetricsComponentTransactionProcessor {
     pub enabled: bool,
+    #[schema(example = "5000")]
+    pub batch_size: Option<u64>,
     pub last_block: Option<MetricsBlock>,
 }
 
@@ -222,7 +277,7 @@ impl Default for MetricsComponentTransactionProcessor {
 
 impl MetricsComponentTransactionProcessor {
     pub fn new() -> Self {
-        Self { enabled: false, last_block: None }
+        Self { enabled: false, batch_size: None, last_block: None }
     }
 }
 
@@ -259,6 +314,30 @@ impl MetricsComponentVirtualChainProcessor {
     }
 }
 
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsComponentMempoolProcessor {
+    pub enabled: bool,
+    #[schema(example = "152")]
+    pub transactions: Option<u64>,
+    #[schema(example = "1738706345528")]
+    pub last_updated: Option<u64>,
+    #[schema(example = "2025-02-04T21:59:05.528Z")]
+    pub last_updated_date_time: Option<DateTime<Utc>>,
+}
+
+impl Default for MetricsComponentMempoolProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsComponentMempoolProcessor {
+    pub fn new() -> Self {
+        Self { enabled: false, transactions: None, last_updated: None, last_updated_date_time: None }
+    }
+}
+
 #[derive(ToSchema, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MetricsDb {
@@ -278,6 +357,16 @@ pub struct MetricsDb {
     pub active_connections: Option<u64>,
     #[schema(example = "100")]
     pub max_connections: Option<u64>,
+    #[schema(example = "3")]
+    pub retries: u64,
+    #[schema(example = "0")]
+    pub failures: u64,
+    #[schema(example = "1738706345528")]
+    pub last_retry: Option<u64>,
+    #[schema(example = "2025-02-04T21:59:05.528Z")]
+    pub last_retry_date_time: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub pool: Option<MetricsDbPool>,
     pub tables: Option<Vec<MetricsDbTable>>,
 }
 
@@ -298,6 +387,12 @@ impl MetricsDb {
             blocked_queries: None,
             active_connections: None,
             max_connections: None,
+            retries: 0,
+            failures: 0,
+            last_retry: None,
+            last_retry_date_time: None,
+            last_error: None,
+            pool: None,
             tables: None,
         }
     }
@@ -314,11 +409,46 @@ impl From<DatabaseDetails> for MetricsDb {
             blocked_queries: Some(database_details.blocked_queries.to_u64().unwrap_or(0)),
             active_connections: Some(database_details.active_connections.to_u64().unwrap_or(0)),
             max_connections: Some(database_details.max_connections.to_u64().unwrap_or(0)),
+            retries: 0,
+            failures: 0,
+            last_retry: None,
+            last_retry_date_time: None,
+            last_error: None,
+            pool: None,
             tables: None,
         }
     }
 }
 
+/// The connection pool of the indexer
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsDbPool {
+    #[schema(example = "12")]
+    pub in_use: u32,
+    #[schema(example = "3")]
+    pub idle: u32,
+    #[schema(example = "0")]
+    pub min_connections: u32,
+    #[schema(example = "10")]
+    pub max_connections: u32,
+    /// Waiting for a connection, a steady number of waiters suggests raising database-max-connections
+    #[schema(example = "0")]
+    pub waiters: u32,
+}
+
+impl From<PoolStatus> for MetricsDbPool {
+    fn from(pool_status: PoolStatus) -> Self {
+        Self {
+            in_use: pool_status.size.saturating_sub(pool_status.idle),
+            idle: pool_status.idle,
+            min_connections: pool_status.min_connections,
+            max_connections: pool_status.max_connections,
+            waiters: pool_status.waiters,
+        }
+    }
+}
+
 #[derive(ToSchema, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MetricsDbTable {
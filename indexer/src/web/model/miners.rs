@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use simply_kaspa_database::models::query::miner_statistics::MinerStatistics;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(IntoParams, Deserialize)]
+#[into_params(parameter_in = Query)]
+pub struct MinersQuery {
+    /// Start of the range (inclusive) in epoch milliseconds, defaults to 24 hours before `to`
+    pub from: Option<i64>,
+    /// End of the range (exclusive) in epoch milliseconds, defaults to now
+    pub to: Option<i64>,
+}
+
+#[skip_serializing_none]
+#[derive(ToSchema, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Miner {
+    /// None for non-standard miner scripts
+    pub miner_address: Option<String>,
+    pub blocks: i64,
+    pub subsidy: i64,
+    #[schema(example = "1738706345528")]
+    pub first_block_time: i64,
+    #[schema(example = "1738706345528")]
+    pub last_block_time: i64,
+}
+
+impl From<MinerStatistics> for Miner {
+    fn from(statistics: MinerStatistics) -> Self {
+        Miner {
+            miner_address: statistics.miner_address,
+            blocks: statistics.blocks,
+            subsidy: statistics.subsidy,
+            first_block_time: statistics.first_block_time,
+            last_block_time: statistics.last_block_time,
+        }
+    }
+}
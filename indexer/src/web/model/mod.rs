@@ -1,2 +1,4 @@
 pub mod health;
+pub mod krc20;
 pub mod metrics;
+pub mod miners;
@@ -1,6 +1,5 @@
-use crate::settings::Settings;
 use crate::web::endpoint;
-use crate::web::endpoint::{health, metrics};
+use crate::web::endpoint::{health, krc20, metrics, miners};
 use crate::web::model::metrics::Metrics;
 use axum::body::{to_bytes, Body};
 use axum::http::{header, HeaderValue, Request};
@@ -9,6 +8,7 @@ use axum::response::Response;
 use axum::{middleware, routing::get, Extension, Router};
 use deadpool::managed::{Object, Pool};
 use log::{info, trace, Level};
+use simply_kaspa_cli::cli_args::CliArgs;
 use simply_kaspa_database::client::KaspaDbClient;
 use simply_kaspa_kaspad::pool::manager::KaspadManager;
 use std::io::Error;
@@ -27,6 +27,7 @@ use utoipa_axum::router::OpenApiRouter;
 use utoipa_swagger_ui::{Config, SwaggerUi};
 
 pub const INFO_TAG: &str = "info";
+pub const DATA_TAG: &str = "data";
 
 #[derive(OpenApi)]
 #[openapi(
@@ -37,15 +38,18 @@ pub const INFO_TAG: &str = "info";
     paths(
         endpoint::health::get_health,
         endpoint::metrics::get_metrics,
+        endpoint::krc20::get_krc20_operations,
+        endpoint::miners::get_miners,
     ),
     tags(
         (name = INFO_TAG, description = "Info API endpoints"),
+        (name = DATA_TAG, description = "Indexed data API endpoints"),
     ),
 )]
 struct ApiDoc;
 
 pub struct WebServer {
-    settings: Settings,
+    cli_args: CliArgs,
     run: Arc<AtomicBool>,
     metrics: Arc<RwLock<Metrics>>,
     kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
@@ -55,22 +59,24 @@ pub struct WebServer {
 
 impl WebServer {
     pub fn new(
-        settings: Settings,
+        cli_args: CliArgs,
         run: Arc<AtomicBool>,
         metrics: Arc<RwLock<Metrics>>,
         kaspad_pool: Pool<KaspadManager, Object<KaspadManager>>,
         database_client: KaspaDbClient,
     ) -> Self {
-        WebServer { settings, run, metrics, kaspad_pool, database_client, system: Arc::new(RwLock::new(System::new())) }
+        WebServer { cli_args, run, metrics, kaspad_pool, database_client, system: Arc::new(RwLock::new(System::new())) }
     }
 
     pub async fn run(self: Arc<Self>) -> Result<(), Error> {
-        let listen = &self.settings.cli_args.listen;
-        let base_path = &self.settings.cli_args.base_path.trim_end_matches("/");
+        let listen = &self.cli_args.listen;
+        let base_path = &self.cli_args.base_path.trim_end_matches("/");
 
         let (api_router, api) = OpenApiRouter::with_openapi(set_server_path(base_path))
             .route(&format!("{}{}", base_path, health::PATH), get(health::get_health))
             .route(&format!("{}{}", base_path, metrics::PATH), get(metrics::get_metrics))
+            .route(&format!("{}{}", base_path, krc20::PATH), get(krc20::get_krc20_operations))
+            .route(&format!("{}{}", base_path, miners::PATH), get(miners::get_miners))
             .split_for_parts();
         let swagger_config = Config::default().use_base_layout().try_it_out_enabled(true).display_request_duration(true);
         let swagger =
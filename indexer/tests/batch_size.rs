@@ -0,0 +1,45 @@
+use simply_kaspa_indexer::batch_size::{AdaptiveBatchSize, AdaptiveQueue};
+use std::time::Duration;
+
+#[test]
+fn shrinks_and_grows_batch_and_queue_together() {
+    let mut batch_size = AdaptiveBatchSize::new(1000, Duration::from_millis(500));
+    let queue = AdaptiveQueue::new(2000);
+    // Slow commits with an empty queue shrink the batch and the queue capacity
+    for _ in 0..10 {
+        batch_size.update(Duration::from_secs(5), batch_size.get(), queue.fill());
+        queue.set_fraction(batch_size.fraction());
+    }
+    assert_eq!(batch_size.get(), 100);
+    assert_eq!(queue.capacity(), 200);
+    for i in 0..200 {
+        assert!(queue.push(i).is_ok());
+    }
+    assert!(queue.push(200).is_err(), "Queue accepted values above its current capacity");
+    assert_eq!(queue.fill(), 1.0);
+
+    // Fast commits grow both back to their upper bounds, but not beyond
+    for _ in 0..20 {
+        batch_size.update(Duration::from_millis(100), batch_size.get(), queue.fill());
+        queue.set_fraction(batch_size.fraction());
+    }
+    assert_eq!(batch_size.get(), 1000);
+    assert_eq!(queue.capacity(), queue.max_capacity());
+}
+
+#[test]
+fn keeps_values_above_a_reduced_capacity() {
+    let queue = AdaptiveQueue::new(100);
+    for i in 0..100 {
+        queue.push(i).unwrap();
+    }
+    queue.set_fraction(0.1);
+    assert_eq!(queue.len(), 100);
+    assert!(queue.push(100).is_err());
+    while queue.len() > 10 {
+        queue.pop();
+    }
+    assert!(queue.push(100).is_err());
+    queue.pop();
+    assert!(queue.push(100).is_ok());
+}
@@ -0,0 +1,268 @@
+#![allow(dead_code)]
+
+use clap::Parser;
+use deadpool::managed::Pool;
+use kaspa_addresses::{Address, Prefix, Version};
+use kaspa_rpc_core::{RpcHash, RpcTransaction};
+use kaspa_wrpc_client::prelude::NetworkId;
+use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_database::client::KaspaDbClient;
+use simply_kaspa_indexer::indexer::IndexerBuilder;
+use simply_kaspa_indexer::settings::Settings;
+use simply_kaspa_kaspad::fixture::KaspadFixture;
+use simply_kaspa_kaspad::mock::MockKaspad;
+use simply_kaspa_kaspad::pool::manager::KaspadManager;
+use simply_kaspa_kaspad::pool::protocol::KaspadProtocol;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Builder, Runtime};
+use tokio::task::JoinHandle;
+
+pub const NETWORK: &str = "testnet-11";
+
+/// Runs the indexer against a MockKaspad and the PostgreSQL database in TEST_DATABASE_URL
+pub struct Harness {
+    pub mock: Arc<MockKaspad>,
+    pub pool: PgPool,
+    database_url: String,
+    run: Arc<AtomicBool>,
+    runtime: Option<Runtime>,
+    indexer: Option<JoinHandle<()>>,
+}
+
+impl Harness {
+    /// Creates a harness with an empty database schema
+    pub async fn new() -> Harness {
+        let database_url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+        let database = KaspaDbClient::new(&database_url).await.expect("Database connection FAILED");
+        database.drop_schema().await.expect("Unable to drop schema");
+        database.create_schema(false).await.expect("Unable to create schema");
+        let pool = PgPool::connect(&database_url).await.expect("Database connection FAILED");
+        let mock = Arc::new(MockKaspad::new(NetworkId::from_str(NETWORK).unwrap()));
+        Harness { mock, pool, database_url, run: Arc::new(AtomicBool::new(true)), runtime: None, indexer: None }
+    }
+
+    pub fn address(&self, seed: u8) -> Address {
+        Address::new(Prefix::Testnet, Version::PubKey, &[seed; 32])
+    }
+
+    /// Mines a block on top of the sink (merging the other tips) with a coinbase transaction plus the supplied transactions
+    pub fn mine(&self, transactions: Vec<RpcTransaction>) -> RpcHash {
+        let txs = self.with_coinbase(self.mock.sink(), transactions);
+        self.mock.mine_block(txs)
+    }
+
+    /// Mines a chain of blocks on top of the supplied parent (without merging other tips), returns the last block
+    pub fn mine_on(&self, parent: RpcHash, count: usize) -> RpcHash {
+        (0..count).fold(parent, |parent, _| {
+            let txs = self.with_coinbase(parent, vec![]);
+            self.mock.add_block(&[parent], txs)
+        })
+    }
+
+    fn with_coinbase(&self, parent: RpcHash, transactions: Vec<RpcTransaction>) -> Vec<RpcTransaction> {
+        let blue_score = self.mock.block(parent).unwrap().header.blue_score + 1;
+        let mut txs = vec![self.mock.coinbase_transaction(blue_score, &self.address(0), b"harness")];
+        txs.extend(transactions);
+        txs
+    }
+
+    /// Starts the indexer from the mock genesis with the supplied extra arguments
+    pub async fn start(&mut self, args: &[&str]) {
+        self.start_with(args, |builder| builder).await
+    }
+
+    /// Like start, the builder can be customized e.g. with batch callbacks
+    pub async fn start_with<F>(&mut self, args: &[&str], configure: F)
+    where
+        F: FnOnce(IndexerBuilder) -> IndexerBuilder + Send + 'static,
+    {
+        let genesis = self.mock.genesis().to_string();
+        let mut cli = vec![
+            "simply-kaspa-indexer",
+            "--network",
+            NETWORK,
+            "--rpc-url",
+            "ws://mock",
+            "--database-url",
+            &self.database_url,
+            "--listen",
+            "127.0.0.1:0",
+            "--disable",
+            "initial_utxo_import,vcp_wait_for_sync",
+        ];
+        if !self.has_checkpoint().await {
+            cli.extend(["--ignore-checkpoint", genesis.as_str()]);
+        }
+        cli.extend(args);
+        let cli_args = CliArgs::parse_from(cli);
+        let network_id = NetworkId::from_str(NETWORK).unwrap();
+        let kaspad_manager = KaspadManager::new(network_id, cli_args.rpc_url.clone(), KaspadProtocol::WrpcBorsh)
+            .with_fixture(KaspadFixture::Replay(self.mock.clone()));
+        let kaspad_pool: Pool<KaspadManager> = Pool::builder(kaspad_manager).max_size(10).build().unwrap();
+        let database_url = self.database_url.clone();
+        let run = Arc::new(AtomicBool::new(true));
+        self.run = run.clone();
+        // The indexer gets a runtime of its own, so that it can be killed without running any shutdown logic
+        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        self.indexer = Some(runtime.spawn(async move {
+            let database = KaspaDbClient::new(&database_url).await.expect("Database connection FAILED");
+            let builder = IndexerBuilder::new(Settings::new(cli_args), database, kaspad_pool).run(run);
+            let mut indexer = configure(builder).build();
+            indexer.start();
+            indexer.wait().await
+        }));
+        self.runtime = Some(runtime);
+    }
+
+    /// Stops the indexer gracefully and waits for it to finish
+    pub async fn stop(&mut self) {
+        self.run.store(false, Ordering::Relaxed);
+        if let Some(indexer) = self.indexer.take() {
+            tokio::time::timeout(Duration::from_secs(60), indexer).await.expect("Indexer did not stop in time").unwrap();
+        }
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+
+    /// Kills the indexer, all its tasks are dropped at their next await point and uncommitted transactions are rolled back
+    pub async fn kill(&mut self) {
+        self.indexer = None;
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+        // Give PostgreSQL time to notice the closed connections
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+
+    /// Makes the next `count` inserts into the table fail with a transient error (serialization failure)
+    pub async fn inject_db_errors(&self, table: &str, count: u32) {
+        for statement in [
+            format!("DROP SEQUENCE IF EXISTS fault_{table}"),
+            format!("CREATE SEQUENCE fault_{table}"),
+            "CREATE OR REPLACE FUNCTION inject_fault() RETURNS TRIGGER AS $$ BEGIN
+                IF nextval(TG_ARGV[0]::regclass) <= TG_ARGV[1]::bigint THEN
+                    RAISE EXCEPTION 'Injected fault on %', TG_TABLE_NAME USING ERRCODE = '40001';
+                END IF;
+                RETURN NULL;
+            END $$ LANGUAGE plpgsql"
+                .to_string(),
+            format!("DROP TRIGGER IF EXISTS inject_fault ON {table}"),
+            format!(
+                "CREATE TRIGGER inject_fault BEFORE INSERT ON {table} FOR EACH STATEMENT EXECUTE FUNCTION inject_fault('fault_{table}', '{count}')"
+            ),
+        ] {
+            sqlx::query(&statement).execute(&self.pool).await.unwrap();
+        }
+    }
+
+    /// The number of injected faults raised for the table so far
+    pub async fn injected_db_errors(&self, table: &str) -> i64 {
+        let last_value: Option<i64> = sqlx::query_scalar(&format!("SELECT last_value FROM fault_{table} WHERE is_called"))
+            .fetch_optional(&self.pool)
+            .await
+            .unwrap();
+        last_value.unwrap_or_default()
+    }
+
+    /// Polls the condition until it holds, panicking after the timeout
+    pub async fn wait_for<F, Fut>(&self, description: &str, timeout: Duration, condition: F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = bool>,
+    {
+        let start = Instant::now();
+        while !condition().await {
+            if start.elapsed() > timeout {
+                panic!("Timed out waiting for {description}");
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    }
+
+    /// A database client for the read queries
+    pub async fn database(&self) -> KaspaDbClient {
+        KaspaDbClient::new(&self.database_url).await.expect("Database connection FAILED")
+    }
+
+    pub async fn has_checkpoint(&self) -> bool {
+        self.checkpoint().await.is_some()
+    }
+
+    pub async fn checkpoint(&self) -> Option<String> {
+        sqlx::query_scalar("SELECT value FROM vars WHERE key = 'block_checkpoint'").fetch_optional(&self.pool).await.unwrap()
+    }
+
+    pub async fn count(&self, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}")).fetch_one(&self.pool).await.unwrap()
+    }
+
+    pub async fn hashes(&self, query: &str) -> HashSet<RpcHash> {
+        let rows: Vec<Vec<u8>> = sqlx::query_scalar(query).fetch_all(&self.pool).await.unwrap();
+        rows.into_iter().map(|h| RpcHash::from_slice(&h)).collect()
+    }
+
+    /// Accepted (transaction_id, block_hash) pairs stored in the database
+    pub async fn acceptances(&self) -> HashSet<(RpcHash, RpcHash)> {
+        let rows: Vec<(Vec<u8>, Vec<u8>)> =
+            sqlx::query_as("SELECT transaction_id, block_hash FROM transactions_acceptances").fetch_all(&self.pool).await.unwrap();
+        rows.into_iter().map(|(t, b)| (RpcHash::from_slice(&t), RpcHash::from_slice(&b))).collect()
+    }
+
+    /// Accepted (transaction_id, block_hash) pairs along the mock's current selected chain
+    pub fn expected_acceptances(&self) -> HashSet<(RpcHash, RpcHash)> {
+        self.expected_acceptances_until(self.mock.sink())
+    }
+
+    /// Accepted (transaction_id, block_hash) pairs along the mock's selected chain, up to and including the supplied chain block
+    pub fn expected_acceptances_until(&self, last: RpcHash) -> HashSet<(RpcHash, RpcHash)> {
+        let chain = self.mock.chain();
+        let end = chain.iter().position(|h| *h == last).expect("Block is not a chain block");
+        let mut accepted = self.mock.accepted_transactions();
+        chain[1..=end]
+            .iter()
+            .flat_map(|block_hash| accepted.remove(block_hash).unwrap_or_default().into_iter().map(move |id| (id, *block_hash)))
+            .collect()
+    }
+
+    /// Waits until transactions_acceptances matches the mock's selected chain
+    pub async fn wait_for_acceptances(&self, timeout: Duration) {
+        self.wait_for("acceptances", timeout, || async { self.acceptances().await == self.expected_acceptances() }).await
+    }
+
+    /// Waits until every block of the mock's selected chain is in the blocks table
+    pub async fn wait_for_blocks(&self, timeout: Duration) {
+        self.wait_for("blocks", timeout, || async {
+            let blocks = self.hashes("SELECT hash FROM blocks").await;
+            self.mock.chain().iter().all(|h| blocks.contains(h))
+        })
+        .await
+    }
+
+    /// Asserts that the saved block_checkpoint is a chain block and that everything it accepted is committed
+    pub async fn assert_checkpoint_consistent(&self) {
+        let checkpoint = self.checkpoint().await.expect("No block_checkpoint saved");
+        let checkpoint = RpcHash::from_str(&checkpoint).unwrap();
+        assert!(self.mock.chain().contains(&checkpoint), "block_checkpoint {checkpoint} is not a chain block");
+        let acceptances = self.acceptances().await;
+        let missing: Vec<_> = self.expected_acceptances_until(checkpoint).difference(&acceptances).copied().collect();
+        assert!(missing.is_empty(), "Acceptances before block_checkpoint {checkpoint} are missing: {missing:?}");
+        let blocks = self.hashes("SELECT hash FROM blocks").await;
+        assert!(blocks.contains(&checkpoint), "block_checkpoint {checkpoint} is not in the blocks table");
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        // Dropping a runtime blocks, which is not allowed inside the test runtime
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
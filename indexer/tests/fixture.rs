@@ -0,0 +1,53 @@
+use kaspa_addresses::{Address, Prefix, Version};
+use kaspa_wrpc_client::prelude::NetworkId;
+use serde::Serialize;
+use simply_kaspa_kaspad::api::KaspadApi;
+use simply_kaspa_kaspad::fixture::{FixtureRecorder, RecordingClient, ReplayClient};
+use simply_kaspa_kaspad::mock::MockKaspad;
+use std::str::FromStr;
+use std::sync::Arc;
+
+fn json<T: Serialize>(value: &T) -> serde_json::Value {
+    serde_json::to_value(value).unwrap()
+}
+
+#[tokio::test]
+async fn replays_recorded_responses() {
+    let path = std::env::temp_dir().join(format!("ski-fixture-{}.json.gz", std::process::id()));
+    let path = path.to_str().unwrap();
+    let mock = Arc::new(MockKaspad::new(NetworkId::from_str("testnet-10").unwrap()));
+    let address = Address::new(Prefix::Testnet, Version::PubKey, &[1; 32]);
+    let block = mock.mine_block(vec![mock.transaction(&[], &[(1_000_000, address.clone())])]);
+    let pending = mock.transaction(&[], &[(2_000_000, address.clone())]);
+    mock.add_to_mempool(pending.clone());
+
+    let recorder = Arc::new(FixtureRecorder::create(path).unwrap());
+    let recording = RecordingClient::new(mock.clone(), recorder.clone());
+    let server_info = recording.get_server_info().await.unwrap();
+    let dag_info = recording.get_block_dag_info().await.unwrap();
+    let blocks = recording.get_blocks(None, true, true).await.unwrap();
+    let chain = recording.get_virtual_chain_from_block(mock.genesis(), true).await.unwrap();
+    let mempool = recording.get_mempool_entries(true, false).await.unwrap();
+    mock.remove_from_mempool(pending.verbose_data.as_ref().unwrap().transaction_id);
+    let drained_mempool = recording.get_mempool_entries(true, false).await.unwrap();
+    drop(recording);
+    drop(recorder);
+
+    let replay = ReplayClient::open(path).unwrap();
+    let _ = std::fs::remove_file(path);
+    assert_eq!(json(&replay.get_server_info().await.unwrap()), json(&server_info));
+    assert_eq!(json(&replay.get_block_dag_info().await.unwrap()), json(&dag_info));
+    assert_eq!(json(&replay.get_blocks(None, true, true).await.unwrap()), json(&blocks));
+    assert!(blocks.block_hashes.contains(&block));
+    assert_eq!(json(&replay.get_virtual_chain_from_block(mock.genesis(), true).await.unwrap()), json(&chain));
+
+    // Mempool polls are replayed in order, the last one repeated
+    assert_eq!(mempool.len(), 1);
+    assert_eq!(json(&replay.get_mempool_entries(true, false).await.unwrap()), json(&mempool));
+    assert_eq!(json(&replay.get_mempool_entries(true, false).await.unwrap()), json(&drained_mempool));
+    assert!(replay.get_mempool_entries(true, false).await.unwrap().is_empty());
+
+    // Requests which were not recorded fail instead of returning an empty response
+    assert!(replay.get_mempool_entries(false, true).await.is_err());
+    assert!(replay.get_blocks(Some(block), true, true).await.is_err());
+}
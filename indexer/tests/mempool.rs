@@ -0,0 +1,48 @@
+mod common;
+
+use common::Harness;
+use kaspa_rpc_core::RpcHash;
+use std::collections::HashSet;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn inserts_and_evicts_mempool_transactions() {
+    let mut harness = Harness::new().await;
+    let coinbase_ids: Vec<RpcHash> = (0..3)
+        .map(|_| {
+            let block = harness.mock.block(harness.mine(vec![])).unwrap();
+            block.transactions[0].verbose_data.as_ref().unwrap().transaction_id
+        })
+        .collect();
+    let mined = harness.mock.transaction(&[(coinbase_ids[0], 0)], &[(1000, harness.address(1))]);
+    let dropped = harness.mock.transaction(&[(coinbase_ids[1], 0)], &[(1000, harness.address(2))]);
+    let mined_id = mined.verbose_data.as_ref().unwrap().transaction_id;
+    let dropped_id = dropped.verbose_data.as_ref().unwrap().transaction_id;
+    let mut without_verbose_data = harness.mock.transaction(&[(coinbase_ids[2], 0)], &[(1000, harness.address(3))]);
+    without_verbose_data.verbose_data = None;
+    harness.mock.add_to_mempool(mined.clone());
+    harness.mock.add_to_mempool(dropped);
+    harness.mock.add_to_mempool(without_verbose_data);
+    harness.inject_db_errors("mempool_outputs", 2).await;
+
+    harness.start(&["--enable", "mempool_processing", "--mempool-interval", "1"]).await;
+    let mempool = || harness.hashes("SELECT transaction_id FROM mempool_transactions");
+    harness
+        .wait_for("mempool inserts", Duration::from_secs(60), || async { mempool().await == HashSet::from([mined_id, dropped_id]) })
+        .await;
+    assert_eq!(harness.count("mempool_inputs").await, 2);
+    assert_eq!(harness.count("mempool_outputs").await, 2);
+    assert_eq!(harness.injected_db_errors("mempool_outputs").await, 2, "Injected errors were not retried");
+
+    // Mined transactions leave the mempool
+    harness.mine(vec![mined]);
+    harness.wait_for("mined eviction", Duration::from_secs(60), || async { mempool().await == HashSet::from([dropped_id]) }).await;
+
+    // As do transactions dropped by kaspad (e.g. expired or replaced)
+    harness.mock.remove_from_mempool(dropped_id);
+    harness.wait_for("dropped eviction", Duration::from_secs(60), || async { mempool().await.is_empty() }).await;
+    assert_eq!(harness.count("mempool_inputs").await, 0);
+    assert_eq!(harness.count("mempool_outputs").await, 0);
+    harness.stop().await;
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::Harness;
+use simply_kaspa_indexer::hooks::CommittedBatch;
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn indexes_blocks_transactions_and_acceptances() {
+    let mut harness = Harness::new().await;
+    let mut spendable = vec![];
+    for i in 0..20u8 {
+        let mut txs = vec![];
+        if let Some(outpoint) = spendable.pop() {
+            txs.push(harness.mock.transaction(&[outpoint], &[(1_000_000, harness.address(i + 1))]));
+        }
+        harness.mine(txs);
+        let coinbase = &harness.mock.block(harness.mock.sink()).unwrap().transactions[0];
+        spendable.push((coinbase.verbose_data.as_ref().unwrap().transaction_id, 0));
+    }
+    harness.start(&[]).await;
+
+    // The VCP only starts once the block processor has caught up, so keep mining while waiting
+    let expected_blocks = harness.mock.chain().len() as i64 - 1;
+    harness.wait_for("blocks", Duration::from_secs(30), || async { harness.count("blocks").await >= expected_blocks }).await;
+    for _ in 0..10 {
+        harness.mine(vec![]);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    harness
+        .wait_for("acceptances", Duration::from_secs(60), || async { harness.acceptances().await == harness.expected_acceptances() })
+        .await;
+    harness.stop().await;
+
+    let transactions = harness.hashes("SELECT transaction_id FROM transactions").await;
+    assert!(harness.expected_acceptances().iter().all(|(transaction_id, _)| transactions.contains(transaction_id)));
+    assert_eq!(harness.count("transactions_inputs").await, 19);
+    assert!(harness.checkpoint().await.is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn waits_for_kaspad_sync() {
+    let mut harness = Harness::new().await;
+    harness.mock.set_synced(false);
+    for _ in 0..10 {
+        harness.mine(vec![]);
+    }
+    harness.start(&[]).await;
+    tokio::time::sleep(Duration::from_secs(15)).await;
+    assert_eq!(harness.mock.calls("get_blocks"), 0, "Blocks were fetched from an unsynced kaspad");
+    assert!(harness.mock.calls("get_sync_status") > 0);
+
+    harness.mock.set_synced(true);
+    harness.wait_for("blocks", Duration::from_secs(60), || async { harness.count("blocks").await > 0 }).await;
+    harness.stop().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn notifies_batch_callbacks() {
+    let mut harness = Harness::new().await;
+    for _ in 0..10 {
+        harness.mine(vec![]);
+    }
+    let blocks = Arc::new(AtomicUsize::new(0));
+    let transactions = Arc::new(AtomicUsize::new(0));
+    let chain_blocks = Arc::new(AtomicI64::new(0));
+    let counters = (blocks.clone(), transactions.clone(), chain_blocks.clone());
+    harness
+        .start_with(&[], move |builder| {
+            builder.web_server(false).on_batch_committed(move |batch| match batch {
+                CommittedBatch::Blocks { blocks, .. } => {
+                    counters.0.fetch_add(blocks.len(), Ordering::Relaxed);
+                }
+                CommittedBatch::Transactions { transaction_ids, .. } => {
+                    counters.1.fetch_add(transaction_ids.len(), Ordering::Relaxed);
+                }
+                CommittedBatch::VirtualChain { added, removed, .. } => {
+                    // Signed, a reorg may remove blocks added by an earlier batch
+                    counters.2.fetch_add(added.len() as i64 - removed.len() as i64, Ordering::Relaxed);
+                }
+            })
+        })
+        .await;
+    for _ in 0..10 {
+        harness.mine(vec![]);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    harness.wait_for_acceptances(Duration::from_secs(60)).await;
+    harness.stop().await;
+
+    assert!(blocks.load(Ordering::Relaxed) as i64 >= harness.count("blocks").await);
+    assert!(transactions.load(Ordering::Relaxed) as i64 >= harness.count("transactions").await);
+    assert_eq!(chain_blocks.load(Ordering::Relaxed), harness.mock.chain().len() as i64 - 1);
+}
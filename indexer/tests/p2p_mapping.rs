@@ -0,0 +1,137 @@
+use clap::Parser;
+use kaspa_addresses::{Address, Prefix, Version};
+use kaspa_consensus_core::block::Block;
+use kaspa_consensus_core::header::Header;
+use kaspa_consensus_core::subnets::{SUBNETWORK_ID_COINBASE, SUBNETWORK_ID_NATIVE};
+use kaspa_consensus_core::tx::{ScriptPublicKey, Transaction, TransactionInput, TransactionOutpoint, TransactionOutput};
+use kaspa_consensus_core::BlueWorkType;
+use kaspa_rpc_core::{GetBlocksResponse, RpcBlock, RpcHash, RpcTransaction};
+use kaspa_txscript::pay_to_address_script;
+use kaspa_wrpc_client::prelude::NetworkId;
+use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_indexer::blocks::p2p_blocks::{map_transactions, p2p_mass_calculator};
+use simply_kaspa_kaspad::api::KaspadApi;
+use simply_kaspa_kaspad::fixture::ReplayClient;
+use simply_kaspa_mapping::mapper::KaspaDbMapper;
+use std::str::FromStr;
+
+fn test_block(address: &Address) -> Block {
+    let coinbase = Transaction::new(
+        0,
+        vec![],
+        vec![TransactionOutput::new(50_000_000_000, pay_to_address_script(address))],
+        0,
+        SUBNETWORK_ID_COINBASE,
+        0,
+        b"coinbase payload".to_vec(),
+    );
+    let spend = Transaction::new(
+        0,
+        vec![TransactionInput::new(TransactionOutpoint::new(coinbase.id(), 0), vec![0x41; 66], 0, 1)],
+        vec![
+            TransactionOutput::new(1_000_000, pay_to_address_script(address)),
+            // Non-standard, has no address
+            TransactionOutput::new(0, ScriptPublicKey::from_vec(0, vec![0x6a, 0x01, 0x02])),
+        ],
+        0,
+        SUBNETWORK_ID_NATIVE,
+        0,
+        vec![],
+    );
+    let header = Header::new_finalized(
+        1,
+        vec![vec![RpcHash::from_u64_word(1)]],
+        RpcHash::default(),
+        RpcHash::default(),
+        RpcHash::default(),
+        1_700_000_000_000,
+        0,
+        0,
+        100,
+        BlueWorkType::from_u64(100),
+        100,
+        RpcHash::default(),
+    );
+    Block::new(header, vec![coinbase, spend])
+}
+
+/// Asserts that the P2P transactions map to the same rows as the transactions returned by kaspad
+fn assert_same_mapping(mapper: &KaspaDbMapper, p2p: &[RpcTransaction], rpc: &[RpcTransaction]) {
+    assert_eq!(p2p.len(), rpc.len());
+    for (p2p, rpc) in p2p.iter().zip(rpc.iter()) {
+        let (p2p_tx, rpc_tx) = (mapper.map_transaction(p2p, 1), mapper.map_transaction(rpc, 1));
+        assert!(
+            p2p_tx.transaction_id == rpc_tx.transaction_id && p2p_tx.hash == rpc_tx.hash && p2p_tx.block_time == rpc_tx.block_time
+        );
+        assert_eq!(p2p_tx.mass, rpc_tx.mass, "Mass differs from kaspad for {}", rpc_tx.transaction_id);
+        assert_eq!(p2p_tx.payload, rpc_tx.payload);
+        assert!(mapper.map_block_transaction(p2p).block_hash == mapper.map_block_transaction(rpc).block_hash);
+
+        let (p2p_inputs, rpc_inputs) = (mapper.map_transaction_inputs(p2p), mapper.map_transaction_inputs(rpc));
+        assert_eq!(p2p_inputs.len(), rpc_inputs.len());
+        for (p2p_input, rpc_input) in p2p_inputs.iter().zip(rpc_inputs.iter()) {
+            assert!(p2p_input.previous_outpoint_hash == rpc_input.previous_outpoint_hash);
+            assert_eq!(p2p_input.previous_outpoint_index, rpc_input.previous_outpoint_index);
+            assert_eq!(p2p_input.signature_script, rpc_input.signature_script);
+            assert_eq!(p2p_input.sig_op_count, rpc_input.sig_op_count);
+        }
+
+        let (p2p_outputs, rpc_outputs) = (mapper.map_transaction_outputs(p2p), mapper.map_transaction_outputs(rpc));
+        assert_eq!(p2p_outputs.len(), rpc_outputs.len());
+        for (p2p_output, rpc_output) in p2p_outputs.iter().zip(rpc_outputs.iter()) {
+            assert_eq!(p2p_output.amount, rpc_output.amount);
+            assert_eq!(p2p_output.script_public_key, rpc_output.script_public_key);
+            assert_eq!(p2p_output.script_public_key_address, rpc_output.script_public_key_address);
+            assert_eq!(p2p_output.block_time, rpc_output.block_time);
+        }
+        let p2p_addresses: Vec<String> = mapper.map_transaction_outputs_address(p2p).into_iter().map(|a| a.address).collect();
+        let rpc_addresses: Vec<String> = mapper.map_transaction_outputs_address(rpc).into_iter().map(|a| a.address).collect();
+        assert_eq!(p2p_addresses, rpc_addresses);
+    }
+}
+
+#[test]
+fn maps_p2p_transactions() {
+    let address = Address::new(Prefix::Testnet, Version::PubKey, &[1; 32]);
+    let block = test_block(&address);
+    // testnet-11 has no consensus params, the mass parameters fall back to the shared defaults
+    let network_id = NetworkId::from_str("testnet-11").unwrap();
+    let transactions = map_transactions(&block, Prefix::Testnet, &p2p_mass_calculator(network_id));
+    assert_eq!(transactions.len(), 2);
+
+    for (transaction, expected) in transactions.iter().zip(block.transactions.iter()) {
+        let verbose_data = transaction.verbose_data.as_ref().unwrap();
+        assert_eq!(verbose_data.transaction_id, expected.id());
+        assert_eq!(verbose_data.block_hash, block.hash());
+        assert_eq!(verbose_data.block_time, 1_700_000_000_000);
+        assert!(verbose_data.compute_mass > 0, "Mass of P2P transaction is missing");
+    }
+    let outputs = &transactions[1].outputs;
+    assert_eq!(outputs[0].verbose_data.as_ref().unwrap().script_public_key_address, address);
+    assert!(outputs[1].verbose_data.is_none(), "Non-standard output has an address");
+    assert_eq!(transactions[1].inputs[0].previous_outpoint.transaction_id, block.transactions[0].id());
+}
+
+/// Compares the P2P mapping with kaspad's own verbose data, using get_blocks responses recorded with --kaspad-record
+#[tokio::test]
+#[ignore = "requires a recorded fixture, set TEST_KASPAD_FIXTURE"]
+async fn maps_p2p_transactions_like_recorded_rpc_transactions() {
+    let path = std::env::var("TEST_KASPAD_FIXTURE").expect("TEST_KASPAD_FIXTURE is not set");
+    let replay = ReplayClient::open(&path).unwrap();
+    let network_id = replay.get_server_info().await.unwrap().network_id;
+    let mass_calculator = p2p_mass_calculator(network_id);
+    let mapper = KaspaDbMapper::new(CliArgs::parse_from(["simply-kaspa-indexer", "--network", &network_id.to_string()]));
+
+    let rpc_blocks: Vec<RpcBlock> = replay
+        .responses::<GetBlocksResponse>("get_blocks")
+        .into_iter()
+        .flat_map(|response| response.blocks)
+        .filter(|block| !block.transactions.is_empty())
+        .collect();
+    assert!(!rpc_blocks.is_empty(), "The fixture has no get_blocks response with transactions");
+    for rpc_block in rpc_blocks {
+        let block = Block::try_from(rpc_block.clone()).expect("Unable to convert recorded block");
+        let p2p = map_transactions(&block, Prefix::from(network_id), &mass_calculator);
+        assert_same_mapping(&mapper, &p2p, &rpc_block.transactions);
+    }
+}
@@ -0,0 +1,129 @@
+mod common;
+
+use clap::Parser;
+use common::Harness;
+use kaspa_addresses::Address;
+use kaspa_rpc_core::{RpcHash, RpcTransaction};
+use kaspa_txscript::pay_to_address_script;
+use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_database::layout::{AddressKey, AddressMapping, ReadLayout};
+use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use simply_kaspa_indexer::indexer::read_layout;
+use std::time::Duration;
+
+fn address_key(address: &Address) -> AddressKey {
+    AddressKey::new(address.payload_to_string(), pay_to_address_script(address).script().to_vec())
+}
+
+fn transaction_id(transaction: &RpcTransaction) -> RpcHash {
+    transaction.verbose_data.as_ref().unwrap().transaction_id
+}
+
+/// Mines a transaction spending a coinbase to address 1 and indexes it with the options, returns the coinbase and spend ids
+async fn index_spend(harness: &mut Harness, args: &[&str]) -> (SqlHash, SqlHash) {
+    harness.mine(vec![]);
+    let coinbase = transaction_id(&harness.mock.block(harness.mock.sink()).unwrap().transactions[0]);
+    let spend = harness.mock.transaction(&[(coinbase, 0)], &[(1_000_000, harness.address(1))]);
+    let spend_id = transaction_id(&spend);
+    harness.mine(vec![spend]);
+    harness.start(args).await;
+    for _ in 0..10 {
+        harness.mine(vec![]);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    harness.wait_for_acceptances(Duration::from_secs(60)).await;
+    harness.stop().await;
+    (coinbase.into(), spend_id.into())
+}
+
+fn layout(args: &[&str]) -> ReadLayout {
+    let mut cli = vec!["simply-kaspa-indexer"];
+    cli.extend(args);
+    read_layout(&CliArgs::parse_from(cli))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn reads_indexed_data() {
+    let mut harness = Harness::new().await;
+    let (coinbase, spend_id) = index_spend(&mut harness, &[]).await;
+    let layout = layout(&[]);
+    assert_eq!(layout.address_mapping, AddressMapping::Addresses);
+    let database = harness.database().await;
+
+    let details = database.select_transaction(&layout, &spend_id).await.unwrap().expect("Transaction not found");
+    assert_eq!(details.inputs.len(), 1);
+    assert_eq!(details.outputs.len(), 1);
+    assert!(details.inputs[0].previous_outpoint_amount.is_some(), "Input was not resolved from transactions_outputs");
+    assert!(details.accepting_block_hash.is_some());
+    let block_hash = details.block_hashes.first().expect("Transaction has no block").clone();
+    assert!(database.select_block(&layout, &block_hash).await.unwrap().is_some());
+    let transactions = database.select_transactions_by_block(&layout, &block_hash).await.unwrap();
+    assert!(transactions.iter().any(|t| t.transaction_id == spend_id && t.mass.is_some()));
+
+    let address_transactions = database.select_address_transactions(&layout, &address_key(&harness.address(1)), 10, 0).await.unwrap();
+    assert!(address_transactions.len() == 1 && address_transactions[0].transaction_id == spend_id);
+    let utxos = database.select_utxos(&layout, &address_key(&harness.address(1)), true).await.unwrap();
+    assert_eq!(utxos.len(), 1);
+    assert!(utxos[0].accepting_block_hash.is_some());
+    let miner_utxos = database.select_utxos(&layout, &address_key(&harness.address(0)), true).await.unwrap();
+    assert!(!miner_utxos.is_empty());
+    assert!(miner_utxos.iter().all(|u| u.transaction_id != coinbase), "Spent coinbase output is returned as utxo");
+
+    let chain_blocks = database.select_chain_blocks(&layout, 0, i64::MAX).await.unwrap();
+    assert!(!chain_blocks.is_empty());
+    assert!(chain_blocks.windows(2).all(|w| w[0].blue_score < w[1].blue_score));
+
+    // Outputs of a transaction which is not accepted are only returned on request, and don't spend their inputs
+    sqlx::query("DELETE FROM transactions_acceptances WHERE transaction_id = $1")
+        .bind(&spend_id)
+        .execute(&harness.pool)
+        .await
+        .unwrap();
+    assert!(database.select_utxos(&layout, &address_key(&harness.address(1)), true).await.unwrap().is_empty());
+    let utxos = database.select_utxos(&layout, &address_key(&harness.address(1)), false).await.unwrap();
+    assert!(utxos.len() == 1 && utxos[0].transaction_id == spend_id && utxos[0].accepting_block_hash.is_none());
+    let miner_utxos = database.select_utxos(&layout, &address_key(&harness.address(0)), true).await.unwrap();
+    assert!(
+        miner_utxos.iter().any(|u| u.transaction_id == coinbase),
+        "Output spent by a transaction which is not accepted is missing"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn reads_without_optional_tables() {
+    let mut harness = Harness::new().await;
+    let args = [
+        "--disable",
+        "transactions_table,addresses_transactions_table",
+        "--enable",
+        "transactions_inputs_resolve",
+        "--exclude-fields",
+        "tx_out_script_public_key_address",
+    ];
+    let (coinbase, spend_id) = index_spend(&mut harness, &args).await;
+    let layout = layout(&args);
+    assert_eq!(layout.address_mapping, AddressMapping::None);
+    let database = harness.database().await;
+
+    let details = database.select_transaction(&layout, &spend_id).await.unwrap().expect("Transaction not found");
+    assert!(details.transaction.mass.is_none());
+    assert_eq!(details.inputs.len(), 1);
+    assert!(details.outputs.iter().all(|o| o.script_public_key_address.is_none()));
+    let block_hash = details.block_hashes.first().expect("Transaction has no block").clone();
+    let transactions = database.select_transactions_by_block(&layout, &block_hash).await.unwrap();
+    assert!(transactions.iter().any(|t| t.transaction_id == spend_id));
+
+    // Only the script public key is indexed for outputs
+    let address_only = AddressKey::address(harness.address(1).payload_to_string());
+    assert!(database.select_utxos(&layout, &address_only, false).await.is_err());
+    let without_acceptance = self::layout(&[&args[..], &["--disable", "transaction_acceptance"]].concat());
+    assert!(database.select_utxos(&without_acceptance, &address_key(&harness.address(1)), true).await.is_err());
+    let address_transactions = database.select_address_transactions(&layout, &address_key(&harness.address(1)), 10, 0).await.unwrap();
+    assert!(address_transactions.len() == 1 && address_transactions[0].transaction_id == spend_id);
+    let miner_transactions = database.select_address_transactions(&layout, &address_key(&harness.address(0)), 1000, 0).await.unwrap();
+    assert!(miner_transactions.iter().any(|t| t.transaction_id == spend_id), "Spending transaction is missing");
+    let miner_utxos = database.select_utxos(&layout, &address_key(&harness.address(0)), true).await.unwrap();
+    assert!(miner_utxos.iter().all(|u| u.transaction_id != coinbase));
+}
@@ -0,0 +1,25 @@
+use clap::Parser;
+use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_indexer::reload::SettingsReloader;
+
+#[test]
+fn keeps_reloaded_options_as_baseline() {
+    let (mut reloader, runtime) = SettingsReloader::new(CliArgs::parse_from(["simply-kaspa-indexer"]));
+
+    reloader.apply(&CliArgs::parse_from(["simply-kaspa-indexer", "--batch-scale", "2", "--database-max-connections", "20"]));
+    assert_eq!(runtime.borrow().batch_scale, 2.0);
+    assert_eq!(reloader.cli_args().batch_scale, 2.0);
+    assert_eq!(reloader.cli_args().database_max_connections, 10, "A non-reloadable option was applied");
+
+    // A later reload compares against the accepted values, not the ones from startup
+    reloader.apply(&CliArgs::parse_from(["simply-kaspa-indexer", "--batch-scale", "2", "--cache-ttl", "30"]));
+    assert_eq!(runtime.borrow().batch_scale, 2.0);
+    assert_eq!(runtime.borrow().cache_ttl, 30);
+    assert_eq!(reloader.cli_args().batch_scale, 2.0);
+    assert_eq!(reloader.cli_args().cache_ttl, 30);
+
+    // Invalid values keep the accepted value
+    reloader.apply(&CliArgs::parse_from(["simply-kaspa-indexer", "--batch-scale", "20"]));
+    assert_eq!(runtime.borrow().batch_scale, 2.0);
+    assert_eq!(reloader.cli_args().batch_scale, 2.0);
+}
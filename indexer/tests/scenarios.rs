@@ -0,0 +1,225 @@
+mod common;
+
+use common::Harness;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(90);
+
+/// A deterministic pseudo random sequence, so that failing scenarios can be reproduced
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound
+    }
+}
+
+/// Mines blocks where each block spends the coinbase of its predecessor
+fn mine_spending(harness: &Harness, count: usize) {
+    for i in 0..count {
+        let sink = harness.mock.block(harness.mock.sink()).unwrap();
+        let txs = match sink.transactions.first() {
+            Some(coinbase) => {
+                let outpoint = (coinbase.verbose_data.as_ref().unwrap().transaction_id, 0);
+                vec![harness.mock.transaction(&[outpoint], &[(1_000_000, harness.address(i as u8 + 1))])]
+            }
+            None => vec![],
+        };
+        harness.mine(txs);
+    }
+}
+
+/// Keeps mining while waiting, since the VCP only commits blocks it has received after the block processor caught up
+async fn mine_until_converged(harness: &Harness) {
+    for _ in 0..5 {
+        mine_spending(harness, 1);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    harness.wait_for_acceptances(TIMEOUT).await;
+    harness.wait_for_blocks(TIMEOUT).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn deep_reorg() {
+    let mut harness = Harness::new().await;
+    mine_spending(&harness, 30);
+    harness.start(&[]).await;
+    mine_until_converged(&harness).await;
+
+    // Fork off 30 blocks below the sink with a longer branch, orphaning the transactions of the old branch
+    let chain = harness.mock.chain();
+    let fork = chain[chain.len() - 31];
+    harness.mine_on(fork, 40);
+    harness.wait_for_acceptances(TIMEOUT).await;
+    let orphaned: Vec<_> = chain[chain.len() - 30..].to_vec();
+    let acceptances = harness.acceptances().await;
+    assert!(acceptances.iter().all(|(_, block_hash)| !orphaned.contains(block_hash)), "Acceptances by orphaned blocks remain");
+
+    // Merging the old branch accepts its transactions again
+    mine_until_converged(&harness).await;
+    harness.stop().await;
+    harness.assert_checkpoint_consistent().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn repeated_reorgs_with_dynamic_tip_distance() {
+    let mut harness = Harness::new().await;
+    mine_spending(&harness, 20);
+    harness.start(&["--enable", "dynamic_vcp_tip_distance", "--vcp-interval", "1"]).await;
+    for _ in 0..6 {
+        let chain = harness.mock.chain();
+        harness.mine_on(chain[chain.len() - 3], 3);
+        tokio::time::sleep(Duration::from_secs(2)).await;
+    }
+    // The tip distance holds back the newest chain blocks, so only the ones below it are expected
+    harness
+        .wait_for("acceptances below tip distance", TIMEOUT, || async {
+            let chain = harness.mock.chain();
+            let acceptances = harness.acceptances().await;
+            acceptances.is_subset(&harness.expected_acceptances())
+                && harness.expected_acceptances_until(chain[chain.len() - 20]).is_subset(&acceptances)
+        })
+        .await;
+    harness.stop().await;
+    harness.assert_checkpoint_consistent().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn duplicate_and_out_of_order_blocks() {
+    let mut harness = Harness::new().await;
+    harness.mock.set_duplicate_blocks(true);
+    harness.mock.set_out_of_order_blocks(true);
+    mine_spending(&harness, 50);
+    harness.start(&[]).await;
+    mine_until_converged(&harness).await;
+    harness.stop().await;
+
+    let chain = harness.mock.chain();
+    assert_eq!(harness.count("blocks").await, chain.len() as i64);
+    let expected_transactions: usize = chain.iter().map(|h| harness.mock.block(*h).unwrap().transactions.len()).sum();
+    assert_eq!(harness.count("transactions").await, expected_transactions as i64);
+    assert_eq!(harness.count("transactions_inputs").await, chain.len() as i64 - 2);
+    harness.assert_checkpoint_consistent().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn kills_between_commits_and_checkpoint_saves() {
+    let mut harness = Harness::new().await;
+    let mut random = Lcg(0x5eed);
+    for round in 0..6 {
+        mine_spending(&harness, 10 + random.next(20) as usize);
+        harness.start(&["--vcp-interval", "1"]).await;
+        let vcp_calls = harness.mock.calls("get_virtual_chain_from_block");
+        let kill_after = 1 + random.next(4);
+        harness
+            .wait_for("virtual chain calls", TIMEOUT, || async {
+                harness.mock.calls("get_virtual_chain_from_block") >= vcp_calls + kill_after
+            })
+            .await;
+        mine_spending(&harness, random.next(5) as usize);
+        // Checkpoints are saved at most once a minute, so a kill leaves commits which are newer than the checkpoint
+        harness.kill().await;
+        if round % 2 == 1 {
+            // Reorg while the indexer is down, orphaning blocks which might already be committed
+            let chain = harness.mock.chain();
+            let depth = 2 + random.next(5) as usize;
+            harness.mine_on(chain[chain.len() - 1 - depth], depth + 1);
+        }
+    }
+    harness.start(&["--vcp-interval", "1"]).await;
+    mine_until_converged(&harness).await;
+    harness.stop().await;
+    harness.assert_checkpoint_consistent().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn reorg_while_stopped() {
+    let mut harness = Harness::new().await;
+    mine_spending(&harness, 20);
+    harness.start(&[]).await;
+    mine_until_converged(&harness).await;
+    harness.stop().await;
+    harness.assert_checkpoint_consistent().await;
+
+    // The saved checkpoint is orphaned by the reorg
+    let checkpoint = harness.checkpoint().await.unwrap();
+    let chain = harness.mock.chain();
+    let checkpoint_position = chain.iter().position(|h| h.to_string() == checkpoint).unwrap();
+    harness.mine_on(chain[checkpoint_position - 5], 20);
+    assert!(!harness.mock.chain().iter().any(|h| h.to_string() == checkpoint));
+
+    harness.start(&[]).await;
+    mine_until_converged(&harness).await;
+    harness.stop().await;
+    harness.assert_checkpoint_consistent().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn transient_database_errors() {
+    let mut harness = Harness::new().await;
+    for table in ["blocks", "transactions", "transactions_outputs", "transactions_acceptances"] {
+        harness.inject_db_errors(table, 3).await;
+    }
+    mine_spending(&harness, 30);
+    harness.start(&[]).await;
+    mine_until_converged(&harness).await;
+    harness.stop().await;
+    for table in ["blocks", "transactions", "transactions_outputs", "transactions_acceptances"] {
+        assert!(harness.injected_db_errors(table).await >= 3, "Faults were not injected for {table}");
+    }
+    harness.assert_checkpoint_consistent().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn kaspad_errors() {
+    let mut harness = Harness::new().await;
+    mine_spending(&harness, 20);
+    harness.start(&[]).await;
+    harness.mock.fail_next("get_blocks", 3);
+    harness.mock.fail_next("get_virtual_chain_from_block", 3);
+    mine_until_converged(&harness).await;
+    harness.stop().await;
+    harness.assert_checkpoint_consistent().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "requires PostgreSQL, set TEST_DATABASE_URL"]
+async fn balance_changes_accepted_before_transactions_are_committed() {
+    let mut harness = Harness::new().await;
+    mine_spending(&harness, 5);
+    // Holds back the transaction processor's output inserts, while blocks and acceptances are committed
+    let mut lock = harness.pool.begin().await.unwrap();
+    sqlx::query("LOCK TABLE transactions_outputs IN EXCLUSIVE MODE").execute(&mut *lock).await.unwrap();
+    harness.start(&["--enable", "address_balance_changes_table"]).await;
+    for _ in 0..5 {
+        harness.mine(vec![]);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+    harness.wait_for_acceptances(TIMEOUT).await;
+    harness.wait_for_blocks(TIMEOUT).await;
+    assert_eq!(harness.count("transactions_outputs").await, 0, "Transaction outputs were committed before the acceptances");
+    assert_eq!(harness.count("address_balance_changes").await, 0);
+
+    lock.commit().await.unwrap();
+    let database = harness.database().await;
+    harness
+        .wait_for("balance changes", TIMEOUT, || async {
+            let mut balances = vec![];
+            for seed in 1..=5 {
+                let address = harness.address(seed).payload_to_string();
+                balances.push(database.select_address_balance_at_daa_score(&address, i64::MAX).await.unwrap());
+            }
+            balances.iter().all(|&b| b == 1_000_000)
+        })
+        .await;
+    harness.stop().await;
+    harness.assert_checkpoint_consistent().await;
+}
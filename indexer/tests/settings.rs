@@ -0,0 +1,39 @@
+use clap::Parser;
+use simply_kaspa_cli::cli_args::{CliArgs, CliDisable, CliEnable};
+use simply_kaspa_database::layout::AddressMapping;
+use simply_kaspa_indexer::settings::Settings;
+
+#[test]
+fn builds_settings_without_command_line() {
+    let mut cli_args = CliArgs::default();
+    assert_eq!(cli_args.network, "mainnet");
+    cli_args.set_enabled(CliEnable::MempoolProcessing, true);
+    cli_args.set_disabled(CliDisable::VirtualChainProcessing, true);
+    cli_args.set_disabled(CliDisable::VcpWaitForSync, true);
+    let settings = Settings::new(cli_args);
+    assert!(settings.cli_args.is_enabled(CliEnable::MempoolProcessing));
+    assert!(settings.cli_args.is_disabled(CliDisable::VirtualChainProcessing));
+    assert!(settings.disable_vcp_wait_for_sync);
+    assert_eq!(settings.runtime.borrow().batch_scale, settings.cli_args.batch_scale);
+
+    let mut cli_args = settings.cli_args;
+    cli_args.set_enabled(CliEnable::MempoolProcessing, false);
+    cli_args.set_disabled(CliDisable::VirtualChainProcessing, false);
+    assert!(!cli_args.is_enabled(CliEnable::MempoolProcessing));
+    assert!(!cli_args.is_disabled(CliDisable::VirtualChainProcessing));
+    assert!(cli_args.is_disabled(CliDisable::VcpWaitForSync));
+}
+
+#[test]
+fn derives_address_mapping_from_options() {
+    let mapping = |args: &[&str]| {
+        let cli_args = CliArgs::parse_from(["simply-kaspa-indexer"].iter().chain(args));
+        Settings::new(cli_args).read_layout().address_mapping
+    };
+    assert_eq!(mapping(&[]).table(), Some("addresses_transactions"));
+    assert_eq!(mapping(&["--exclude-fields=tx_out_script_public_key_address"]), AddressMapping::Scripts);
+    assert_eq!(mapping(&["--exclude-fields=tx_out_script_public_key_address"]).table(), Some("scripts_transactions"));
+    assert_eq!(mapping(&["--exclude-fields=tx_out_script_public_key_address,tx_out_script_public_key"]), AddressMapping::None);
+    assert_eq!(mapping(&["--disable=addresses_transactions_table"]), AddressMapping::None);
+    assert_eq!(mapping(&["--disable=transaction_processing"]), AddressMapping::None);
+}
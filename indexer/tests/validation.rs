@@ -0,0 +1,76 @@
+use clap::Parser;
+use simply_kaspa_cli::cli_args::CliArgs;
+use simply_kaspa_indexer::validation::{validate_cli_args, Issue};
+
+fn parse(args: &[&str]) -> CliArgs {
+    let mut cli_args = CliArgs::parse_from(["simply-kaspa-indexer"].iter().chain(args));
+    cli_args.apply_profile();
+    cli_args
+}
+
+fn assert_issue(args: &[&str], fatal: bool, needle: &str) {
+    let issues = validate_cli_args(&parse(args));
+    let found = issues.iter().any(|issue| match issue {
+        Issue::Fatal(msg) => fatal && msg.contains(needle),
+        Issue::Degraded(msg) => !fatal && msg.contains(needle),
+    });
+    assert!(found, "Expected a {} issue about {needle} for {args:?}, got {issues:?}", if fatal { "fatal" } else { "degraded" });
+}
+
+fn fatal(args: &[&str]) -> Vec<String> {
+    validate_cli_args(&parse(args))
+        .into_iter()
+        .filter_map(|issue| match issue {
+            Issue::Fatal(msg) => Some(msg),
+            Issue::Degraded(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn requires_balance_change_fields() {
+    assert!(fatal(&["--enable=address_balance_changes_table"]).is_empty());
+    for field in ["tx_out_script_public_key_address", "tx_out_amount", "tx_in_previous_outpoint"] {
+        let issues = fatal(&["--enable=address_balance_changes_table", &format!("--exclude-fields={field}")]);
+        assert!(issues.iter().any(|msg| msg.contains(field)), "Excluding {field} was not fatal: {issues:?}");
+    }
+    let issues = fatal(&["--profile=minimal", "--enable=address_balance_changes_table"]);
+    assert!(issues.iter().any(|msg| msg.contains("tx_out_script_public_key_address")), "Minimal profile was not fatal: {issues:?}");
+    assert!(fatal(&["--profile=minimal"]).is_empty());
+}
+
+#[test]
+fn accepts_defaults_and_profiles() {
+    assert_eq!(validate_cli_args(&parse(&[])), vec![]);
+    for profile in ["explorer", "exchange", "minimal", "archive"] {
+        assert!(fatal(&[&format!("--profile={profile}")]).is_empty(), "Profile {profile} is invalid");
+    }
+}
+
+#[test]
+fn rejects_fatal_combinations() {
+    assert_issue(
+        &["--enable=transactions_inputs_resolve", "--exclude-fields=tx_in_previous_outpoint"],
+        true,
+        "tx_in_previous_outpoint",
+    );
+    assert_issue(&["--enable=transactions_inputs_resolve", "--disable=transactions_outputs_table"], true, "transactions_outputs");
+    assert_issue(
+        &["--enable=address_balance_changes_table", "--disable=transaction_acceptance"],
+        true,
+        "address_balance_changes_table",
+    );
+    assert_issue(&["--database-min-connections=20", "--database-max-connections=10"], true, "database_min_connections");
+}
+
+#[test]
+fn warns_about_degraded_combinations() {
+    assert_issue(&["--enable=transactions_inputs_resolve", "--exclude-fields=tx_out_amount"], false, "tx_out_amount");
+    assert_issue(&["--enable=transactions_inputs_resolve", "--disable=transactions_inputs_table"], false, "transactions_inputs_table");
+    assert_issue(&["--exclude-fields=tx_out_script_public_key,tx_out_script_public_key_address"], false, "addresses_transactions");
+    assert_issue(&["--disable=virtual_chain_processing,vcp_wait_for_sync"], false, "vcp_wait_for_sync");
+    assert_issue(&["--disable=virtual_chain_processing,transaction_acceptance"], false, "transaction_acceptance");
+    assert_issue(&["--disable=transaction_processing", "--enable=block_rewards_table"], false, "block_rewards_table");
+    assert_issue(&["--database-ssl-root-cert=ca.pem", "--database-ssl-mode=require"], false, "database_ssl_root_cert");
+    assert_issue(&["--include-fields=tx_out_amount", "--exclude-fields=tx_hash"], false, "exclude_fields");
+}
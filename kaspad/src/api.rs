@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+use kaspa_grpc_client::GrpcClient;
+use kaspa_rpc_core::api::rpc::RpcApi;
+use kaspa_rpc_core::{
+    GetBlockDagInfoResponse, GetBlocksResponse, GetServerInfoResponse, GetVirtualChainFromBlockResponse, RpcBlock, RpcHash,
+    RpcMempoolEntry, RpcResult,
+};
+use kaspa_wrpc_client::KaspaRpcClient;
+
+/// The subset of the kaspad RPC api used by the indexer, allows the connection to be replaced (e.g. by a fixture replay)
+#[async_trait]
+pub trait KaspadApi: Send + Sync {
+    async fn get_server_info(&self) -> RpcResult<GetServerInfoResponse>;
+
+    async fn get_sync_status(&self) -> RpcResult<bool>;
+
+    async fn get_block_dag_info(&self) -> RpcResult<GetBlockDagInfoResponse>;
+
+    async fn get_block(&self, hash: RpcHash, include_transactions: bool) -> RpcResult<RpcBlock>;
+
+    async fn get_blocks(
+        &self,
+        low_hash: Option<RpcHash>,
+        include_blocks: bool,
+        include_transactions: bool,
+    ) -> RpcResult<GetBlocksResponse>;
+
+    async fn get_virtual_chain_from_block(
+        &self,
+        start_hash: RpcHash,
+        include_accepted_transaction_ids: bool,
+    ) -> RpcResult<GetVirtualChainFromBlockResponse>;
+
+    async fn get_mempool_entries(&self, include_orphan_pool: bool, filter_transaction_pool: bool) -> RpcResult<Vec<RpcMempoolEntry>>;
+
+    fn is_connected(&self) -> bool;
+}
+
+/// Implements KaspadApi for a kaspad RPC client, is_connected is not part of RpcApi so a blanket impl is not possible
+macro_rules! impl_kaspad_api {
+    ($client:ty) => {
+        #[async_trait]
+        impl KaspadApi for $client {
+            async fn get_server_info(&self) -> RpcResult<GetServerInfoResponse> {
+                RpcApi::get_server_info(self).await
+            }
+
+            async fn get_sync_status(&self) -> RpcResult<bool> {
+                RpcApi::get_sync_status(self).await
+            }
+
+            async fn get_block_dag_info(&self) -> RpcResult<GetBlockDagInfoResponse> {
+                RpcApi::get_block_dag_info(self).await
+            }
+
+            async fn get_block(&self, hash: RpcHash, include_transactions: bool) -> RpcResult<RpcBlock> {
+                RpcApi::get_block(self, hash, include_transactions).await
+            }
+
+            async fn get_blocks(
+                &self,
+                low_hash: Option<RpcHash>,
+                include_blocks: bool,
+                include_transactions: bool,
+            ) -> RpcResult<GetBlocksResponse> {
+                RpcApi::get_blocks(self, low_hash, include_blocks, include_transactions).await
+            }
+
+            async fn get_virtual_chain_from_block(
+                &self,
+                start_hash: RpcHash,
+                include_accepted_transaction_ids: bool,
+            ) -> RpcResult<GetVirtualChainFromBlockResponse> {
+                RpcApi::get_virtual_chain_from_block(self, start_hash, include_accepted_transaction_ids).await
+            }
+
+            async fn get_mempool_entries(
+                &self,
+                include_orphan_pool: bool,
+                filter_transaction_pool: bool,
+            ) -> RpcResult<Vec<RpcMempoolEntry>> {
+                RpcApi::get_mempool_entries(self, include_orphan_pool, filter_transaction_pool).await
+            }
+
+            fn is_connected(&self) -> bool {
+                <$client>::is_connected(self)
+            }
+        }
+    };
+}
+
+impl_kaspad_api!(KaspaRpcClient);
+impl_kaspad_api!(GrpcClient);
@@ -0,0 +1,233 @@
+use crate::api::KaspadApi;
+use async_trait::async_trait;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use kaspa_rpc_core::{
+    GetBlockDagInfoResponse, GetBlocksResponse, GetServerInfoResponse, GetVirtualChainFromBlockResponse, RpcBlock, RpcError, RpcHash,
+    RpcMempoolEntry, RpcResult,
+};
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::sync::{Arc, Mutex};
+
+/// One recorded response, stored as a json line in a gzip compressed file
+#[derive(Serialize, Deserialize)]
+struct FixtureEntry {
+    method: String,
+    key: String,
+    response: serde_json::Value,
+}
+
+#[derive(Clone)]
+pub enum KaspadFixture {
+    Record(Arc<FixtureRecorder>),
+    /// Serves every connection from the supplied client instead of kaspad (e.g. a ReplayClient or a mock)
+    Replay(Arc<dyn KaspadApi>),
+}
+
+pub struct FixtureRecorder {
+    writer: Mutex<GzEncoder<BufWriter<File>>>,
+}
+
+impl FixtureRecorder {
+    pub fn create(path: &str) -> std::io::Result<FixtureRecorder> {
+        info!("Recording kaspad responses to {}", path);
+        let file = File::create(path)?;
+        Ok(FixtureRecorder { writer: Mutex::new(GzEncoder::new(BufWriter::new(file), Compression::default())) })
+    }
+
+    pub fn record<T: Serialize>(&self, method: &str, key: String, response: &T) {
+        let entry = FixtureEntry { method: method.to_string(), key, response: serde_json::to_value(response).unwrap() };
+        let mut writer = self.writer.lock().unwrap();
+        // Flushing after each entry keeps the file readable if the process is terminated
+        let result = serde_json::to_writer(&mut *writer, &entry)
+            .map_err(std::io::Error::from)
+            .and_then(|_| writer.write_all(b"\n"))
+            .and_then(|_| writer.flush());
+        if let Err(e) = result {
+            warn!("Failed to record {} response: {}", method, e);
+        }
+    }
+}
+
+impl Drop for FixtureRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.writer.get_mut().unwrap().try_finish() {
+            warn!("Failed to finish recorded responses: {}", e);
+        }
+    }
+}
+
+/// Records the responses of the wrapped client
+pub struct RecordingClient {
+    inner: Arc<dyn KaspadApi>,
+    recorder: Arc<FixtureRecorder>,
+}
+
+impl RecordingClient {
+    pub fn new(inner: Arc<dyn KaspadApi>, recorder: Arc<FixtureRecorder>) -> RecordingClient {
+        RecordingClient { inner, recorder }
+    }
+
+    fn record<T: Serialize>(&self, method: &str, key: String, result: RpcResult<T>) -> RpcResult<T> {
+        if let Ok(response) = &result {
+            self.recorder.record(method, key, response);
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl KaspadApi for RecordingClient {
+    async fn get_server_info(&self) -> RpcResult<GetServerInfoResponse> {
+        self.record("get_server_info", String::new(), self.inner.get_server_info().await)
+    }
+
+    async fn get_sync_status(&self) -> RpcResult<bool> {
+        self.record("get_sync_status", String::new(), self.inner.get_sync_status().await)
+    }
+
+    async fn get_block_dag_info(&self) -> RpcResult<GetBlockDagInfoResponse> {
+        self.record("get_block_dag_info", String::new(), self.inner.get_block_dag_info().await)
+    }
+
+    async fn get_block(&self, hash: RpcHash, include_transactions: bool) -> RpcResult<RpcBlock> {
+        self.record("get_block", format!("{hash}/{include_transactions}"), self.inner.get_block(hash, include_transactions).await)
+    }
+
+    async fn get_blocks(
+        &self,
+        low_hash: Option<RpcHash>,
+        include_blocks: bool,
+        include_transactions: bool,
+    ) -> RpcResult<GetBlocksResponse> {
+        self.record(
+            "get_blocks",
+            format!("{low_hash:?}/{include_blocks}/{include_transactions}"),
+            self.inner.get_blocks(low_hash, include_blocks, include_transactions).await,
+        )
+    }
+
+    async fn get_virtual_chain_from_block(
+        &self,
+        start_hash: RpcHash,
+        include_accepted_transaction_ids: bool,
+    ) -> RpcResult<GetVirtualChainFromBlockResponse> {
+        self.record(
+            "get_virtual_chain_from_block",
+            format!("{start_hash}/{include_accepted_transaction_ids}"),
+            self.inner.get_virtual_chain_from_block(start_hash, include_accepted_transaction_ids).await,
+        )
+    }
+
+    async fn get_mempool_entries(&self, include_orphan_pool: bool, filter_transaction_pool: bool) -> RpcResult<Vec<RpcMempoolEntry>> {
+        self.record(
+            "get_mempool_entries",
+            format!("{include_orphan_pool}/{filter_transaction_pool}"),
+            self.inner.get_mempool_entries(include_orphan_pool, filter_transaction_pool).await,
+        )
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+}
+
+/// Replays recorded responses in the order they were recorded (per method and request).
+/// The last response for a request is repeated, to support polling
+pub struct ReplayClient {
+    responses: Mutex<HashMap<(String, String), VecDeque<serde_json::Value>>>,
+}
+
+impl ReplayClient {
+    pub fn open(path: &str) -> std::io::Result<ReplayClient> {
+        let reader = BufReader::new(MultiGzDecoder::new(File::open(path)?));
+        let mut responses: HashMap<(String, String), VecDeque<serde_json::Value>> = HashMap::new();
+        let mut count = 0;
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    warn!("Stopped reading recorded responses after {} entries: {}", count, e);
+                    break;
+                }
+            };
+            let entry: FixtureEntry = serde_json::from_str(&line)?;
+            responses.entry((entry.method, entry.key)).or_default().push_back(entry.response);
+            count += 1;
+        }
+        info!("Replaying {} kaspad responses from {}", count, path);
+        Ok(ReplayClient { responses: Mutex::new(responses) })
+    }
+
+    /// All recorded responses of a method which are not yet replayed, regardless of the request
+    pub fn responses<T: DeserializeOwned>(&self, method: &str) -> Vec<T> {
+        let responses = self.responses.lock().unwrap();
+        responses
+            .iter()
+            .filter(|((m, _), _)| m == method)
+            .flat_map(|(_, queue)| queue.iter())
+            .filter_map(|response| serde_json::from_value(response.clone()).ok())
+            .collect()
+    }
+
+    fn next<T: DeserializeOwned>(&self, method: &str, key: String) -> RpcResult<T> {
+        let mut responses = self.responses.lock().unwrap();
+        let queue = responses
+            .get_mut(&(method.to_string(), key))
+            .ok_or_else(|| RpcError::General(format!("No recorded {method} response for request")))?;
+        let response = if queue.len() > 1 { queue.pop_front().unwrap() } else { queue.front().unwrap().clone() };
+        serde_json::from_value(response).map_err(|e| RpcError::General(format!("Invalid recorded {method} response: {e}")))
+    }
+}
+
+#[async_trait]
+impl KaspadApi for ReplayClient {
+    async fn get_server_info(&self) -> RpcResult<GetServerInfoResponse> {
+        self.next("get_server_info", String::new())
+    }
+
+    async fn get_sync_status(&self) -> RpcResult<bool> {
+        // Older recordings lack the sync status, fall back to the server info
+        self.next("get_sync_status", String::new())
+            .or_else(|_| self.next::<GetServerInfoResponse>("get_server_info", String::new()).map(|s| s.is_synced))
+    }
+
+    async fn get_block_dag_info(&self) -> RpcResult<GetBlockDagInfoResponse> {
+        self.next("get_block_dag_info", String::new())
+    }
+
+    async fn get_block(&self, hash: RpcHash, include_transactions: bool) -> RpcResult<RpcBlock> {
+        self.next("get_block", format!("{hash}/{include_transactions}"))
+    }
+
+    async fn get_blocks(
+        &self,
+        low_hash: Option<RpcHash>,
+        include_blocks: bool,
+        include_transactions: bool,
+    ) -> RpcResult<GetBlocksResponse> {
+        self.next("get_blocks", format!("{low_hash:?}/{include_blocks}/{include_transactions}"))
+    }
+
+    async fn get_virtual_chain_from_block(
+        &self,
+        start_hash: RpcHash,
+        include_accepted_transaction_ids: bool,
+    ) -> RpcResult<GetVirtualChainFromBlockResponse> {
+        self.next("get_virtual_chain_from_block", format!("{start_hash}/{include_accepted_transaction_ids}"))
+    }
+
+    async fn get_mempool_entries(&self, include_orphan_pool: bool, filter_transaction_pool: bool) -> RpcResult<Vec<RpcMempoolEntry>> {
+        self.next("get_mempool_entries", format!("{include_orphan_pool}/{filter_transaction_pool}"))
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
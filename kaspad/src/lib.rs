@@ -1 +1,6 @@
+pub mod api;
+pub mod fixture;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod network;
 pub mod pool;
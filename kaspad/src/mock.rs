@@ -0,0 +1,490 @@
+use crate::api::KaspadApi;
+use async_trait::async_trait;
+use kaspa_addresses::Address;
+use kaspa_consensus_core::header::Header;
+use kaspa_consensus_core::subnets::{SubnetworkId, SUBNETWORK_ID_COINBASE, SUBNETWORK_ID_NATIVE};
+use kaspa_consensus_core::BlueWorkType;
+use kaspa_rpc_core::{
+    GetBlockDagInfoResponse, GetBlocksResponse, GetServerInfoResponse, GetVirtualChainFromBlockResponse, RpcAcceptedTransactionIds,
+    RpcBlock, RpcBlockVerboseData, RpcError, RpcHash, RpcMempoolEntry, RpcResult, RpcTransaction, RpcTransactionInput,
+    RpcTransactionInputVerboseData, RpcTransactionOutpoint, RpcTransactionOutput, RpcTransactionOutputVerboseData,
+    RpcTransactionVerboseData,
+};
+use kaspa_txscript::pay_to_address_script;
+use kaspa_txscript::script_class::ScriptClass;
+use kaspa_wrpc_client::prelude::NetworkId;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+const GENESIS_TIMESTAMP: u64 = 1_700_000_000_000;
+const BLOCK_INTERVAL_MS: u64 = 100;
+const SUBSIDY: u64 = 50_000_000_000;
+const MAX_BLOCKS_PER_REQUEST: usize = 1000;
+
+struct MockBlock {
+    block: RpcBlock,
+    parents: Vec<RpcHash>,
+    mergeset: Vec<RpcHash>,
+}
+
+struct MockDag {
+    blocks: Vec<MockBlock>,
+    index: HashMap<RpcHash, usize>,
+    tips: Vec<RpcHash>,
+    sink: RpcHash,
+    synced: bool,
+    failures: HashMap<String, u32>,
+    duplicate_blocks: bool,
+    out_of_order_blocks: bool,
+    calls: HashMap<&'static str, u64>,
+    sequence: u64,
+    mempool: Vec<RpcTransaction>,
+}
+
+impl MockDag {
+    fn get(&self, hash: &RpcHash) -> RpcResult<&MockBlock> {
+        self.index.get(hash).map(|&i| &self.blocks[i]).ok_or_else(|| RpcError::General(format!("Block {hash} not found")))
+    }
+
+    fn selected_parent(&self, hash: &RpcHash) -> Option<RpcHash> {
+        self.index.get(hash).and_then(|&i| self.blocks[i].parents.first().copied())
+    }
+
+    fn past(&self, hash: &RpcHash) -> HashSet<RpcHash> {
+        let mut past = HashSet::new();
+        let mut queue: VecDeque<RpcHash> = self.index.get(hash).map(|&i| self.blocks[i].parents.clone()).unwrap_or_default().into();
+        while let Some(h) = queue.pop_front() {
+            if past.insert(h) {
+                queue.extend(self.blocks[self.index[&h]].parents.iter().copied());
+            }
+        }
+        past
+    }
+
+    /// The selected chain from genesis to the sink
+    fn chain(&self) -> Vec<RpcHash> {
+        let mut chain = vec![self.sink];
+        while let Some(selected_parent) = self.selected_parent(chain.last().unwrap()) {
+            chain.push(selected_parent);
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Transaction ids accepted by each chain block, a transaction is only accepted once along the chain
+    fn accepted_transactions(&self, chain: &[RpcHash]) -> HashMap<RpcHash, Vec<RpcHash>> {
+        let mut accepted = HashSet::new();
+        let mut accepted_by_block = HashMap::new();
+        for chain_block in chain {
+            let mut mergeset = self.blocks[self.index[chain_block]].mergeset.clone();
+            mergeset.sort_by_key(|h| self.index[h]);
+            let ids = mergeset
+                .iter()
+                .flat_map(|h| self.blocks[self.index[h]].block.transactions.iter())
+                .map(|tx| tx.verbose_data.as_ref().unwrap().transaction_id)
+                .filter(|id| accepted.insert(*id))
+                .collect();
+            accepted_by_block.insert(*chain_block, ids);
+        }
+        accepted_by_block
+    }
+
+    fn check_failure(&mut self, method: &'static str) -> RpcResult<()> {
+        *self.calls.entry(method).or_default() += 1;
+        match self.failures.get_mut(method).filter(|remaining| **remaining > 0) {
+            Some(remaining) => {
+                *remaining -= 1;
+                Err(RpcError::General(format!("Injected {method} failure")))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// An in-process kaspad backed by a programmable synthetic DAG, for testing.
+/// The first parent of a block is its selected parent, the sink follows the highest blue score unless set explicitly
+pub struct MockKaspad {
+    network_id: NetworkId,
+    dag: Mutex<MockDag>,
+}
+
+impl MockKaspad {
+    pub fn new(network_id: NetworkId) -> MockKaspad {
+        let mock = MockKaspad {
+            network_id,
+            dag: Mutex::new(MockDag {
+                blocks: vec![],
+                index: HashMap::new(),
+                tips: vec![],
+                sink: RpcHash::default(),
+                synced: true,
+                failures: HashMap::new(),
+                duplicate_blocks: false,
+                out_of_order_blocks: false,
+                calls: HashMap::new(),
+                sequence: 0,
+                mempool: vec![],
+            }),
+        };
+        let genesis = mock.add_block(&[], vec![]);
+        mock.dag.lock().unwrap().sink = genesis;
+        mock
+    }
+
+    pub fn genesis(&self) -> RpcHash {
+        self.dag.lock().unwrap().blocks[0].block.header.hash
+    }
+
+    pub fn sink(&self) -> RpcHash {
+        self.dag.lock().unwrap().sink
+    }
+
+    pub fn tips(&self) -> Vec<RpcHash> {
+        self.dag.lock().unwrap().tips.clone()
+    }
+
+    pub fn chain(&self) -> Vec<RpcHash> {
+        self.dag.lock().unwrap().chain()
+    }
+
+    pub fn block(&self, hash: RpcHash) -> Option<RpcBlock> {
+        let dag = self.dag.lock().unwrap();
+        dag.get(&hash).ok().map(|b| b.block.clone())
+    }
+
+    /// Transaction ids accepted along the current selected chain, per accepting block
+    pub fn accepted_transactions(&self) -> HashMap<RpcHash, Vec<RpcHash>> {
+        let dag = self.dag.lock().unwrap();
+        dag.accepted_transactions(&dag.chain())
+    }
+
+    /// Sets the virtual selected parent, moving it to another branch simulates a reorg
+    pub fn set_sink(&self, hash: RpcHash) {
+        let mut dag = self.dag.lock().unwrap();
+        assert!(dag.index.contains_key(&hash), "Unknown block {hash}");
+        dag.sink = hash;
+    }
+
+    pub fn set_synced(&self, synced: bool) {
+        self.dag.lock().unwrap().synced = synced;
+    }
+
+    /// Makes the next `count` calls to the supplied RPC method fail
+    pub fn fail_next(&self, method: &str, count: u32) {
+        self.dag.lock().unwrap().failures.insert(method.to_string(), count);
+    }
+
+    /// Makes get_blocks return every block twice
+    pub fn set_duplicate_blocks(&self, duplicate_blocks: bool) {
+        self.dag.lock().unwrap().duplicate_blocks = duplicate_blocks;
+    }
+
+    /// Makes get_blocks return blocks in reverse order, except for the last one which is used as the next low hash
+    pub fn set_out_of_order_blocks(&self, out_of_order_blocks: bool) {
+        self.dag.lock().unwrap().out_of_order_blocks = out_of_order_blocks;
+    }
+
+    /// The number of calls made to the supplied RPC method
+    pub fn calls(&self, method: &str) -> u64 {
+        self.dag.lock().unwrap().calls.get(method).copied().unwrap_or_default()
+    }
+
+    /// Adds the transaction to the mempool, it stays there until it is mined or removed
+    pub fn add_to_mempool(&self, transaction: RpcTransaction) {
+        self.dag.lock().unwrap().mempool.push(transaction);
+    }
+
+    pub fn remove_from_mempool(&self, transaction_id: RpcHash) {
+        self.dag.lock().unwrap().mempool.retain(|tx| tx.verbose_data.as_ref().map(|v| v.transaction_id) != Some(transaction_id));
+    }
+
+    /// Adds a block on top of the current tips, with the sink as selected parent
+    pub fn mine_block(&self, transactions: Vec<RpcTransaction>) -> RpcHash {
+        let parents = {
+            let dag = self.dag.lock().unwrap();
+            let mut parents = vec![dag.sink];
+            parents.extend(dag.tips.iter().filter(|t| **t != dag.sink && !dag.past(&dag.sink).contains(t)));
+            parents
+        };
+        self.add_block(&parents, transactions)
+    }
+
+    /// Adds a block with the supplied parents (the first is the selected parent), returns the block hash
+    pub fn add_block(&self, parents: &[RpcHash], transactions: Vec<RpcTransaction>) -> RpcHash {
+        let mut dag = self.dag.lock().unwrap();
+        for parent in parents {
+            assert!(dag.index.contains_key(parent), "Unknown parent {parent}");
+        }
+        dag.sequence += 1;
+        let past = parents.first().map(|sp| dag.past(sp)).unwrap_or_default();
+        let mut mergeset: Vec<RpcHash> = parents.first().copied().into_iter().collect();
+        let mut queue: VecDeque<RpcHash> = parents.iter().skip(1).copied().collect();
+        while let Some(h) = queue.pop_front() {
+            if !past.contains(&h) && !mergeset.contains(&h) {
+                mergeset.push(h);
+                queue.extend(dag.blocks[dag.index[&h]].parents.iter().copied());
+            }
+        }
+        let selected_parent_blue_score = parents.first().map(|sp| dag.blocks[dag.index[sp]].block.header.blue_score).unwrap_or(0);
+        let blue_score = if parents.is_empty() { 0 } else { selected_parent_blue_score + mergeset.len() as u64 };
+        let timestamp = GENESIS_TIMESTAMP + dag.sequence * BLOCK_INTERVAL_MS;
+        let header = Header::new_finalized(
+            1,
+            if parents.is_empty() { vec![] } else { vec![parents.to_vec()] },
+            RpcHash::default(),
+            RpcHash::default(),
+            RpcHash::default(),
+            timestamp,
+            0,
+            dag.sequence,
+            blue_score,
+            BlueWorkType::from_u64(blue_score),
+            blue_score,
+            RpcHash::default(),
+        );
+        let hash = header.hash;
+        let transactions: Vec<RpcTransaction> = transactions
+            .into_iter()
+            .map(|mut tx| {
+                let verbose_data = tx.verbose_data.as_mut().expect("Use MockKaspad::transaction to create transactions");
+                verbose_data.block_hash = hash;
+                verbose_data.block_time = timestamp;
+                tx
+            })
+            .collect();
+        let block = RpcBlock {
+            header: (&header).into(),
+            verbose_data: Some(RpcBlockVerboseData {
+                hash,
+                difficulty: 1.0,
+                selected_parent_hash: parents.first().copied().unwrap_or_default(),
+                transaction_ids: transactions.iter().map(|tx| tx.verbose_data.as_ref().unwrap().transaction_id).collect(),
+                is_header_only: false,
+                blue_score,
+                children_hashes: vec![],
+                merge_set_blues_hashes: mergeset.clone(),
+                merge_set_reds_hashes: vec![],
+                is_chain_block: false,
+            }),
+            transactions,
+        };
+        for parent in parents {
+            let i = dag.index[parent];
+            dag.blocks[i].block.verbose_data.as_mut().unwrap().children_hashes.push(hash);
+        }
+        let mined_ids = &block.verbose_data.as_ref().unwrap().transaction_ids;
+        dag.mempool.retain(|tx| tx.verbose_data.as_ref().map_or(true, |v| !mined_ids.contains(&v.transaction_id)));
+        dag.tips.retain(|t| !parents.contains(t));
+        dag.tips.push(hash);
+        let index = dag.blocks.len();
+        dag.blocks.push(MockBlock { block, parents: parents.to_vec(), mergeset });
+        dag.index.insert(hash, index);
+        if !parents.is_empty() && blue_score > dag.blocks[dag.index[&dag.sink]].block.header.blue_score {
+            dag.sink = hash;
+        }
+        hash
+    }
+
+    /// Creates a transaction spending the supplied outpoints, paying the supplied amounts to the supplied addresses
+    pub fn transaction(&self, inputs: &[(RpcHash, u32)], outputs: &[(u64, Address)]) -> RpcTransaction {
+        self.create_transaction(inputs, outputs, SUBNETWORK_ID_NATIVE, vec![])
+    }
+
+    /// Creates a coinbase transaction paying the block subsidy to the supplied address
+    pub fn coinbase_transaction(&self, blue_score: u64, address: &Address, extra_data: &[u8]) -> RpcTransaction {
+        let script_public_key = pay_to_address_script(address);
+        let mut payload = vec![];
+        payload.extend(blue_score.to_le_bytes());
+        payload.extend(SUBSIDY.to_le_bytes());
+        payload.extend(script_public_key.version().to_le_bytes());
+        payload.push(script_public_key.script().len() as u8);
+        payload.extend(script_public_key.script());
+        payload.extend(extra_data);
+        self.create_transaction(&[], &[(SUBSIDY, address.clone())], SUBNETWORK_ID_COINBASE, payload)
+    }
+
+    fn create_transaction(
+        &self,
+        inputs: &[(RpcHash, u32)],
+        outputs: &[(u64, Address)],
+        subnetwork_id: SubnetworkId,
+        payload: Vec<u8>,
+    ) -> RpcTransaction {
+        let mut dag = self.dag.lock().unwrap();
+        dag.sequence += 1;
+        let transaction_id = RpcHash::from_u64_word(dag.sequence);
+        RpcTransaction {
+            version: 0,
+            inputs: inputs
+                .iter()
+                .map(|(transaction_id, index)| RpcTransactionInput {
+                    previous_outpoint: RpcTransactionOutpoint { transaction_id: *transaction_id, index: *index },
+                    signature_script: vec![],
+                    sequence: 0,
+                    sig_op_count: 1,
+                    verbose_data: Some(RpcTransactionInputVerboseData {}),
+                })
+                .collect(),
+            outputs: outputs
+                .iter()
+                .map(|(amount, address)| {
+                    let script_public_key = pay_to_address_script(address);
+                    RpcTransactionOutput {
+                        value: *amount,
+                        verbose_data: Some(RpcTransactionOutputVerboseData {
+                            script_public_key_type: ScriptClass::from_script(&script_public_key),
+                            script_public_key_address: address.clone(),
+                        }),
+                        script_public_key,
+                    }
+                })
+                .collect(),
+            lock_time: 0,
+            subnetwork_id,
+            gas: 0,
+            payload,
+            mass: 0,
+            verbose_data: Some(RpcTransactionVerboseData {
+                transaction_id,
+                hash: transaction_id,
+                compute_mass: 1000,
+                block_hash: RpcHash::default(),
+                block_time: 0,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl KaspadApi for MockKaspad {
+    async fn get_server_info(&self) -> RpcResult<GetServerInfoResponse> {
+        let mut dag = self.dag.lock().unwrap();
+        dag.check_failure("get_server_info")?;
+        Ok(GetServerInfoResponse {
+            rpc_api_version: 1,
+            rpc_api_revision: 0,
+            server_version: "mock".to_string(),
+            network_id: self.network_id.into(),
+            has_utxo_index: true,
+            is_synced: dag.synced,
+            virtual_daa_score: dag.blocks[dag.index[&dag.sink]].block.header.daa_score + 1,
+        })
+    }
+
+    async fn get_sync_status(&self) -> RpcResult<bool> {
+        let mut dag = self.dag.lock().unwrap();
+        dag.check_failure("get_sync_status")?;
+        Ok(dag.synced)
+    }
+
+    async fn get_block_dag_info(&self) -> RpcResult<GetBlockDagInfoResponse> {
+        let mut dag = self.dag.lock().unwrap();
+        dag.check_failure("get_block_dag_info")?;
+        let sink = &dag.blocks[dag.index[&dag.sink]].block;
+        Ok(GetBlockDagInfoResponse {
+            network: self.network_id.into(),
+            block_count: dag.blocks.len() as u64,
+            header_count: dag.blocks.len() as u64,
+            tip_hashes: dag.tips.clone(),
+            difficulty: 1.0,
+            past_median_time: sink.header.timestamp,
+            virtual_parent_hashes: vec![dag.sink],
+            pruning_point_hash: dag.blocks[0].block.header.hash,
+            virtual_daa_score: sink.header.daa_score + 1,
+            sink: dag.sink,
+        })
+    }
+
+    async fn get_block(&self, hash: RpcHash, include_transactions: bool) -> RpcResult<RpcBlock> {
+        let mut dag = self.dag.lock().unwrap();
+        dag.check_failure("get_block")?;
+        let chain: HashSet<RpcHash> = dag.chain().into_iter().collect();
+        let mut block = dag.get(&hash)?.block.clone();
+        block.verbose_data.as_mut().unwrap().is_chain_block = chain.contains(&hash);
+        if !include_transactions {
+            block.transactions = vec![];
+        }
+        Ok(block)
+    }
+
+    async fn get_blocks(
+        &self,
+        low_hash: Option<RpcHash>,
+        include_blocks: bool,
+        include_transactions: bool,
+    ) -> RpcResult<GetBlocksResponse> {
+        let mut dag = self.dag.lock().unwrap();
+        dag.check_failure("get_blocks")?;
+        let start = match low_hash {
+            Some(low_hash) => {
+                dag.index.get(&low_hash).copied().ok_or_else(|| RpcError::General(format!("Block {low_hash} not found")))?
+            }
+            None => 0,
+        };
+        let mut blocks: Vec<RpcBlock> = dag.blocks[start..]
+            .iter()
+            .take(MAX_BLOCKS_PER_REQUEST)
+            .map(|b| {
+                let mut block = b.block.clone();
+                if !include_transactions {
+                    block.transactions = vec![];
+                }
+                block
+            })
+            .collect();
+        if dag.out_of_order_blocks && blocks.len() > 2 {
+            let last = blocks.len() - 1;
+            blocks[..last].reverse();
+        }
+        if dag.duplicate_blocks {
+            blocks = blocks.into_iter().flat_map(|b| [b.clone(), b]).collect();
+        }
+        Ok(GetBlocksResponse {
+            block_hashes: blocks.iter().map(|b| b.header.hash).collect(),
+            blocks: if include_blocks { blocks } else { vec![] },
+        })
+    }
+
+    async fn get_virtual_chain_from_block(
+        &self,
+        start_hash: RpcHash,
+        include_accepted_transaction_ids: bool,
+    ) -> RpcResult<GetVirtualChainFromBlockResponse> {
+        let mut dag = self.dag.lock().unwrap();
+        dag.check_failure("get_virtual_chain_from_block")?;
+        let chain = dag.chain();
+        let mut removed = vec![];
+        let mut current = start_hash;
+        let fork = loop {
+            if let Some(position) = chain.iter().position(|h| *h == current) {
+                break position;
+            }
+            dag.get(&current)?;
+            removed.push(current);
+            current = dag.selected_parent(&current).ok_or_else(|| RpcError::General("Start hash is not in the DAG".to_string()))?;
+        };
+        let added = chain[fork + 1..].to_vec();
+        let mut accepted = if include_accepted_transaction_ids { dag.accepted_transactions(&chain) } else { HashMap::new() };
+        Ok(GetVirtualChainFromBlockResponse {
+            removed_chain_block_hashes: removed,
+            accepted_transaction_ids: added
+                .iter()
+                .map(|h| RpcAcceptedTransactionIds {
+                    accepting_block_hash: *h,
+                    accepted_transaction_ids: accepted.remove(h).unwrap_or_default(),
+                })
+                .collect(),
+            added_chain_block_hashes: added,
+        })
+    }
+
+    async fn get_mempool_entries(&self, _: bool, _: bool) -> RpcResult<Vec<RpcMempoolEntry>> {
+        let mut dag = self.dag.lock().unwrap();
+        dag.check_failure("get_mempool_entries")?;
+        Ok(dag.mempool.iter().map(|tx| RpcMempoolEntry::new(0, tx.clone(), false)).collect())
+    }
+
+    fn is_connected(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,147 @@
+use kaspa_consensus_core::config::params::{Params, DEVNET_PARAMS, MAINNET_PARAMS, SIMNET_PARAMS, TESTNET_PARAMS};
+use kaspa_wrpc_client::prelude::{NetworkId, NetworkType};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Assumed for networks without consensus params (e.g. testnet-11)
+const DEFAULT_BPS: u8 = 10;
+/// Upper bound of transactions per block, used for sizing caches
+const MAX_TXS_PER_BLOCK: u16 = 300;
+/// A fork counts as passed by the network one day (of its block rate) after its estimated activation, covering the error of the estimate
+const FORK_PASSED_MARGIN_SECS: u64 = 86400;
+
+/// The kaspa consensus params of the network, None for networks without (e.g. testnet-11)
+pub fn consensus_params(network_id: NetworkId) -> Option<Params> {
+    match network_id {
+        NetworkId { network_type: NetworkType::Mainnet, .. } => Some(MAINNET_PARAMS),
+        NetworkId { network_type: NetworkType::Testnet, suffix: Some(10) } => Some(TESTNET_PARAMS),
+        NetworkId { network_type: NetworkType::Simnet, .. } => Some(SIMNET_PARAMS),
+        NetworkId { network_type: NetworkType::Devnet, .. } => Some(DEVNET_PARAMS),
+        _ => None,
+    }
+}
+
+/// A consensus fork changing the block rate, active from (and including) the activation DAA score
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkFork {
+    pub name: String,
+    pub activation_daa_score: u64,
+    pub bps: u8,
+}
+
+/// Network specific parameters, derived from the kaspa consensus params
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkParams {
+    pub network_id: NetworkId,
+    /// Timestamp (in ms) of the genesis block, 0 for networks without consensus params
+    pub genesis_timestamp: u64,
+    pub genesis_daa_score: u64,
+    pub default_p2p_port: Option<u16>,
+    pub dns_seeders: Vec<String>,
+    /// Ordered by activation DAA score, the first fork is active from genesis
+    pub forks: Vec<NetworkFork>,
+}
+
+impl NetworkParams {
+    pub fn new(network_id: NetworkId) -> NetworkParams {
+        match consensus_params(network_id) {
+            Some(params) => NetworkParams::from_params(network_id, &params),
+            None => NetworkParams {
+                network_id,
+                genesis_timestamp: 0,
+                genesis_daa_score: 0,
+                default_p2p_port: None,
+                dns_seeders: vec![],
+                forks: vec![NetworkFork { name: "genesis".to_string(), activation_daa_score: 0, bps: DEFAULT_BPS }],
+            },
+        }
+    }
+
+    fn from_params(network_id: NetworkId, params: &Params) -> NetworkParams {
+        let mut forks = vec![NetworkFork {
+            name: "genesis".to_string(),
+            activation_daa_score: 0,
+            bps: (1000 / params.prior_target_time_per_block) as u8,
+        }];
+        let crescendo_activation = params.crescendo_activation.daa_score();
+        if crescendo_activation != u64::MAX {
+            let crescendo = NetworkFork {
+                name: "crescendo".to_string(),
+                activation_daa_score: crescendo_activation,
+                bps: (1000 / params.crescendo.target_time_per_block) as u8,
+            };
+            if crescendo_activation == 0 {
+                forks = vec![crescendo];
+            } else {
+                forks.push(crescendo);
+            }
+        }
+        NetworkParams {
+            network_id,
+            genesis_timestamp: params.genesis.timestamp,
+            genesis_daa_score: params.genesis.daa_score,
+            default_p2p_port: Some(params.default_p2p_port()),
+            dns_seeders: params.dns_seeders.iter().map(|s| s.to_string()).collect(),
+            forks,
+        }
+    }
+
+    /// The virtual DAA score the network is expected to have reached at the time (in ms), from the genesis and the block rates
+    pub fn estimated_daa_score(&self, timestamp: u64) -> u64 {
+        let mut daa_score = self.genesis_daa_score;
+        let mut secs = timestamp.saturating_sub(self.genesis_timestamp) / 1000;
+        for (i, fork) in self.forks.iter().enumerate() {
+            let bps = fork.bps.max(1) as u64;
+            match self.forks.get(i + 1) {
+                Some(next) if daa_score + secs * bps > next.activation_daa_score => {
+                    secs -= next.activation_daa_score.saturating_sub(daa_score) / bps;
+                    daa_score = daa_score.max(next.activation_daa_score);
+                }
+                _ => return daa_score + secs * bps,
+            }
+        }
+        daa_score
+    }
+
+    /// Kaspad may report is_synced while still far behind (e.g. during IBD), lower virtual DAA scores are never considered
+    /// synced. The activation score of the latest fork the network has passed at the time (in ms), so forks scheduled by
+    /// the consensus params only count once they are active. 0 for networks without consensus params
+    pub fn min_synced_daa_score(&self, timestamp: u64) -> u64 {
+        if self.genesis_timestamp == 0 {
+            return 0;
+        }
+        let estimated_daa_score = self.estimated_daa_score(timestamp);
+        self.forks
+            .iter()
+            .filter(|f| f.activation_daa_score + FORK_PASSED_MARGIN_SECS * f.bps as u64 <= estimated_daa_score)
+            .map(|f| f.activation_daa_score)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Like min_synced_daa_score, at the current time
+    pub fn min_synced_daa_score_now(&self) -> u64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0);
+        self.min_synced_daa_score(now)
+    }
+
+    /// The fork active at the DAA score
+    pub fn fork(&self, daa_score: u64) -> &NetworkFork {
+        self.forks.iter().rev().find(|f| f.activation_daa_score <= daa_score).unwrap_or(&self.forks[0])
+    }
+
+    /// The next fork activating after the DAA score, if any
+    pub fn next_fork(&self, daa_score: u64) -> Option<&NetworkFork> {
+        self.forks.iter().find(|f| f.activation_daa_score > daa_score)
+    }
+
+    pub fn bps(&self, daa_score: u64) -> u8 {
+        self.fork(daa_score).bps
+    }
+
+    pub fn tps_max(&self, daa_score: u64) -> u16 {
+        self.bps(daa_score) as u16 * MAX_TXS_PER_BLOCK
+    }
+}
@@ -1,49 +1,204 @@
-use deadpool::managed::{Manager, Metrics, RecycleError, RecycleResult};
-use kaspa_rpc_core::api::rpc::RpcApi;
-use kaspa_rpc_core::RpcNetworkType;
+use crate::api::KaspadApi;
+use crate::fixture::{KaspadFixture, RecordingClient};
+use crate::network::NetworkParams;
+use crate::pool::nodes::{KaspadNodes, MonitoredClient};
+use crate::pool::protocol::KaspadProtocol;
+use crate::pool::tls::TlsTunnels;
+use deadpool::managed::{Manager, Metrics, Pool, RecycleError, RecycleResult};
+use kaspa_grpc_client::GrpcClient;
+use kaspa_rpc_core::GetServerInfoResponse;
 use kaspa_wrpc_client::client::ConnectOptions;
 use kaspa_wrpc_client::error::Error;
 use kaspa_wrpc_client::prelude::*;
-use kaspa_wrpc_client::{KaspaRpcClient, WrpcEncoding};
+use kaspa_wrpc_client::KaspaRpcClient;
 use log::{debug, info, warn};
+use simply_kaspa_cli::cli_args::redact_url;
+use std::ops::Deref;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+const MONITOR_INTERVAL: Duration = Duration::from_secs(10);
 
 pub struct KaspadManager {
     pub network_id: NetworkId,
-    pub rpc_url: Option<String>,
+    pub nodes: Arc<KaspadNodes>,
+    pub fixture: Option<KaspadFixture>,
+    pub require_synced: bool,
+    pub tls_tunnels: Option<Arc<TlsTunnels>>,
+}
+
+impl KaspadManager {
+    pub fn new(network_id: NetworkId, rpc_urls: Option<Vec<String>>, protocol: KaspadProtocol) -> KaspadManager {
+        let nodes = Arc::new(KaspadNodes::new(network_id, rpc_urls, protocol));
+        KaspadManager { network_id, nodes, fixture: None, require_synced: true, tls_tunnels: None }
+    }
+
+    pub fn with_fixture(mut self, fixture: KaspadFixture) -> KaspadManager {
+        self.fixture = Some(fixture);
+        self
+    }
+
+    /// Verifies wss:// nodes against the CA certificates of the tunnels instead of the bundled webpki roots
+    pub fn with_tls_tunnels(mut self, tls_tunnels: TlsTunnels) -> KaspadManager {
+        self.tls_tunnels = Some(Arc::new(tls_tunnels));
+        self
+    }
+
+    /// The url to connect to, wss:// urls are replaced by their TLS tunnel if one is configured
+    async fn connect_url(&self, node: usize) -> Result<Option<String>, Error> {
+        match (&self.tls_tunnels, self.nodes.url(node)) {
+            (Some(tls_tunnels), Some(url)) => tls_tunnels.local_url(&url).await.map(Some).map_err(Error::Custom),
+            (_, url) => Ok(url),
+        }
+    }
+
+    /// Allows pooled connections to nodes which are not synced (e.g. archival nodes performing IBD)
+    pub fn allow_unsynced(mut self) -> KaspadManager {
+        self.require_synced = false;
+        self
+    }
+
+    /// Connects to the preferred node regardless of its sync state, used to follow kaspad's progress while it syncs
+    pub async fn connect_unsynced(&self) -> Result<KaspadClient, Error> {
+        if let Some(KaspadFixture::Replay(replay_client)) = &self.fixture {
+            return Ok(KaspadClient { node: 0, client: replay_client.clone() });
+        }
+        let mut last_error = None;
+        for node in self.nodes.ranked() {
+            let connected = match self.connect_url(node).await {
+                Ok(url) => connect(self.network_id, self.nodes.protocol(node), url).await,
+                Err(e) => Err(e),
+            };
+            match connected {
+                Ok(client) => {
+                    return Ok(KaspadClient { node, client: Arc::new(MonitoredClient::new(node, self.nodes.clone(), client)) })
+                }
+                Err(e) => {
+                    self.nodes.record_error(node, e.to_string());
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| Error::Custom("No kaspad nodes configured".to_string())))
+    }
+}
+
+/// A pooled connection, remembering which node it is connected to
+pub struct KaspadClient {
+    pub node: usize,
+    client: Arc<dyn KaspadApi>,
+}
+
+impl Deref for KaspadClient {
+    type Target = dyn KaspadApi;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
 }
 
 impl Manager for KaspadManager {
-    type Type = Arc<KaspaRpcClient>;
+    type Type = KaspadClient;
     type Error = Error;
 
     async fn create(&self) -> Result<Self::Type, Self::Error> {
-        debug!("Creating connection");
-        Ok(Arc::new(connect_client(self.network_id, self.rpc_url.clone()).await?))
+        if let Some(KaspadFixture::Replay(replay_client)) = &self.fixture {
+            return Ok(KaspadClient { node: 0, client: replay_client.clone() });
+        }
+        let mut last_error = None;
+        for node in self.nodes.ranked() {
+            debug!("Creating connection to {}", self.nodes.name(node));
+            let start_time = Instant::now();
+            let connected = match self.connect_url(node).await {
+                Ok(url) => connect_client(self.network_id, self.nodes.protocol(node), url, self.require_synced).await,
+                Err(e) => Err(e),
+            };
+            match connected {
+                Ok((client, server_info)) => {
+                    self.nodes.record_server_info(node, &server_info, start_time.elapsed());
+                    let client: Arc<dyn KaspadApi> = match &self.fixture {
+                        Some(KaspadFixture::Record(recorder)) => {
+                            recorder.record("get_server_info", String::new(), &server_info);
+                            Arc::new(RecordingClient::new(client, recorder.clone()))
+                        }
+                        _ => client,
+                    };
+                    let client = Arc::new(MonitoredClient::new(node, self.nodes.clone(), client));
+                    return Ok(KaspadClient { node, client });
+                }
+                Err(e) => {
+                    self.nodes.record_error(node, e.to_string());
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| Error::Custom("No kaspad nodes configured".to_string())))
     }
 
     async fn recycle(&self, conn: &mut Self::Type, _: &Metrics) -> RecycleResult<Self::Error> {
         debug!("Recycling connection");
-        if conn.is_connected() {
-            Ok(())
-        } else {
+        if !conn.is_connected() {
             let err_msg = "Kaspad connection lost";
             warn!("{err_msg}");
+            self.nodes.record_error(conn.node, err_msg.to_string());
             Err(RecycleError::Message(err_msg.into()))
+        } else if self.nodes.should_fail_over(conn.node) {
+            let err_msg = format!("Kaspad {} is unhealthy, failing over", self.nodes.name(conn.node));
+            warn!("{err_msg}");
+            Err(RecycleError::Message(err_msg.into()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Periodically checks the node of a pooled connection, fails over (when recycling) if it became unhealthy.
+/// The latency and error rate are recorded from all pooled calls, see MonitoredClient
+pub async fn monitor(pool: Pool<KaspadManager>) {
+    loop {
+        match pool.get().await {
+            Ok(kaspad) => {
+                let _ = kaspad.get_server_info().await;
+            }
+            Err(e) => debug!("Kaspad monitor found no connection: {e}"),
+        }
+        let statuses = pool.manager().nodes.statuses();
+        debug!("Kaspad nodes: {:?}", statuses);
+        if !statuses.iter().any(|s| s.is_healthy()) {
+            warn!("No healthy kaspad nodes available");
         }
+        tokio::time::sleep(MONITOR_INTERVAL).await;
     }
 }
 
-pub async fn connect_client(network_id: NetworkId, rpc_url: Option<String>) -> Result<KaspaRpcClient, Error> {
-    let url = if let Some(url) = &rpc_url { url } else { &Resolver::default().get_url(WrpcEncoding::Borsh, network_id).await? };
+pub async fn connect(network_id: NetworkId, protocol: KaspadProtocol, rpc_url: Option<String>) -> Result<Arc<dyn KaspadApi>, Error> {
+    let Some(encoding) = protocol.wrpc_encoding() else {
+        let url = rpc_url.ok_or_else(|| Error::Custom("An rpc url is required for gRPC, the resolver only supports wRPC".into()))?;
+        debug!("Connecting to Kaspad {} using gRPC", redact_url(&url));
+        let client = GrpcClient::connect(url).await.map_err(|e| {
+            warn!("Kaspad connection failed: {e}");
+            Error::Custom(e.to_string())
+        })?;
+        return Ok(Arc::new(client));
+    };
+    let url = if let Some(url) = &rpc_url { url } else { &Resolver::default().get_url(encoding, network_id).await? };
 
-    debug!("Connecting to Kaspad {}", url);
-    let client = KaspaRpcClient::new_with_args(WrpcEncoding::Borsh, Some(url), None, Some(network_id), None)?;
+    debug!("Connecting to Kaspad {} using wRPC {}", redact_url(url), protocol);
+    let client = KaspaRpcClient::new_with_args(encoding, Some(url), None, Some(network_id), None)?;
     client.connect(Some(connect_options())).await.map_err(|e| {
         warn!("Kaspad connection failed: {e}");
         e
     })?;
+    Ok(Arc::new(client))
+}
+
+pub async fn connect_client(
+    network_id: NetworkId,
+    protocol: KaspadProtocol,
+    rpc_url: Option<String>,
+    require_synced: bool,
+) -> Result<(Arc<dyn KaspadApi>, GetServerInfoResponse), Error> {
+    let client = connect(network_id, protocol, rpc_url.clone()).await?;
 
     let server_info = client.get_server_info().await?;
     let connected_network = format!(
@@ -51,21 +206,31 @@ pub async fn connect_client(network_id: NetworkId, rpc_url: Option<String>) -> R
         server_info.network_id.network_type,
         server_info.network_id.suffix.map(|s| format!("-{}", s)).unwrap_or_default()
     );
-    info!("Connected to Kaspad {}, version: {}, network: {}", url, server_info.server_version, connected_network);
+    info!(
+        "Connected to Kaspad {}, version: {}, network: {}",
+        rpc_url.as_deref().map(redact_url).unwrap_or_else(|| "(resolver)".to_string()),
+        server_info.server_version,
+        connected_network
+    );
 
     if network_id != server_info.network_id {
-        panic!("Network mismatch, expected '{}', actual '{}'", network_id, connected_network);
-    } else if !server_info.is_synced
-        || server_info.network_id.network_type == RpcNetworkType::Mainnet && server_info.virtual_daa_score < 107107107
-    {
-        let err_msg = format!("Kaspad {} is NOT synced", server_info.server_version);
+        let err_msg = format!("Network mismatch, expected '{}', actual '{}'", network_id, connected_network);
         warn!("{err_msg}");
         Err(Error::Custom(err_msg))
+    } else if require_synced && !is_synced(&server_info) {
+        // Not logged as a warning, the sync progress is reported while the indexer waits for kaspad
+        let err_msg = format!("Kaspad {} is NOT synced", server_info.server_version);
+        debug!("{err_msg}");
+        Err(Error::Custom(err_msg))
     } else {
-        Ok(client)
+        Ok((client, server_info))
     }
 }
 
+pub fn is_synced(server_info: &GetServerInfoResponse) -> bool {
+    server_info.is_synced && server_info.virtual_daa_score >= NetworkParams::new(server_info.network_id).min_synced_daa_score_now()
+}
+
 fn connect_options() -> ConnectOptions {
     ConnectOptions {
         block_async_connect: true,
@@ -1 +1,4 @@
 pub mod manager;
+pub mod nodes;
+pub mod protocol;
+pub mod tls;
@@ -0,0 +1,262 @@
+use crate::api::KaspadApi;
+use crate::pool::manager::is_synced;
+use crate::pool::protocol::KaspadProtocol;
+use async_trait::async_trait;
+use kaspa_rpc_core::{
+    GetBlockDagInfoResponse, GetBlocksResponse, GetServerInfoResponse, GetVirtualChainFromBlockResponse, RpcBlock, RpcHash,
+    RpcMempoolEntry, RpcResult,
+};
+use kaspa_wrpc_client::prelude::NetworkId;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use url::Url;
+
+const RESULTS_WINDOW: usize = 20;
+const MAX_ERROR_RATE: f64 = 0.5;
+/// Nodes without results for this long are tried again when failing over, as they may have recovered
+const STALE_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Default)]
+pub struct KaspadNodeStatus {
+    pub name: String,
+    pub connected: bool,
+    pub synced: bool,
+    pub server_version: Option<String>,
+    pub virtual_daa_score: Option<u64>,
+    /// Median latency of the successful calls in the results window
+    pub latency_ms: Option<u64>,
+    pub error_rate: f64,
+    pub last_error: Option<String>,
+}
+
+impl KaspadNodeStatus {
+    pub fn is_healthy(&self) -> bool {
+        self.connected && self.synced && self.error_rate <= MAX_ERROR_RATE
+    }
+}
+
+struct KaspadNode {
+    protocol: KaspadProtocol,
+    url: Option<String>,
+    status: KaspadNodeStatus,
+    /// The latency of successful calls, None for failed calls
+    results: VecDeque<Option<Duration>>,
+    last_result: Option<Instant>,
+}
+
+impl KaspadNode {
+    fn record(&mut self, latency: Option<Duration>) {
+        if self.results.len() >= RESULTS_WINDOW {
+            self.results.pop_front();
+        }
+        self.results.push_back(latency);
+        self.last_result = Some(Instant::now());
+        self.status.error_rate = self.results.iter().filter(|r| r.is_none()).count() as f64 / self.results.len() as f64;
+        let mut latencies: Vec<Duration> = self.results.iter().flatten().copied().collect();
+        latencies.sort();
+        if let Some(median) = latencies.get(latencies.len() / 2) {
+            self.status.latency_ms = Some(median.as_millis() as u64);
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        self.last_result.map_or(true, |t| t.elapsed() > STALE_AFTER)
+    }
+}
+
+/// Tracks latency, sync state and error rate of the configured kaspad nodes, used for ranking connections
+pub struct KaspadNodes {
+    network_id: NetworkId,
+    nodes: Vec<Mutex<KaspadNode>>,
+}
+
+impl KaspadNodes {
+    /// The protocol of each node is taken from its url scheme, falling back to the supplied default protocol
+    pub fn new(network_id: NetworkId, rpc_urls: Option<Vec<String>>, default_protocol: KaspadProtocol) -> KaspadNodes {
+        let urls: Vec<(KaspadProtocol, Option<String>)> = match rpc_urls {
+            Some(rpc_urls) if !rpc_urls.is_empty() => rpc_urls
+                .iter()
+                .map(|u| KaspadProtocol::from_url(u, default_protocol))
+                .map(|(protocol, url)| (protocol, Some(url)))
+                .collect(),
+            _ => vec![(default_protocol, None)],
+        };
+        let nodes = urls
+            .into_iter()
+            .enumerate()
+            .map(|(i, (protocol, url))| {
+                let name = url
+                    .as_ref()
+                    .and_then(|u| Url::parse(u).ok())
+                    .and_then(|u| u.host_str().map(|h| format!("{}{}", h, u.port().map(|p| format!(":{p}")).unwrap_or_default())))
+                    .unwrap_or_else(|| if url.is_some() { format!("node-{i}") } else { "resolver".to_string() });
+                let status = KaspadNodeStatus { name, ..Default::default() };
+                let results = VecDeque::with_capacity(RESULTS_WINDOW);
+                Mutex::new(KaspadNode { protocol, url, status, results, last_result: None })
+            })
+            .collect();
+        KaspadNodes { network_id, nodes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn url(&self, node: usize) -> Option<String> {
+        self.nodes[node].lock().unwrap().url.clone()
+    }
+
+    pub fn protocol(&self, node: usize) -> KaspadProtocol {
+        self.nodes[node].lock().unwrap().protocol
+    }
+
+    pub fn name(&self, node: usize) -> String {
+        self.nodes[node].lock().unwrap().status.name.clone()
+    }
+
+    pub fn statuses(&self) -> Vec<KaspadNodeStatus> {
+        self.nodes.iter().map(|n| n.lock().unwrap().status.clone()).collect()
+    }
+
+    /// A candidate is healthy, or unhealthy by results which are stale
+    fn is_candidate(&self, node: usize) -> bool {
+        let n = self.nodes[node].lock().unwrap();
+        n.status.is_healthy() || n.is_stale()
+    }
+
+    /// Node indexes ordered from most to least preferred: candidates first, then by error rate and latency
+    pub fn ranked(&self) -> Vec<usize> {
+        let statuses = self.statuses();
+        let candidates: Vec<bool> = (0..statuses.len()).map(|i| self.is_candidate(i)).collect();
+        let mut ranked: Vec<usize> = (0..statuses.len()).collect();
+        ranked.sort_by_key(|&i| {
+            let s = &statuses[i];
+            (!candidates[i], (s.error_rate * 10.0).round() as u64, s.latency_ms.unwrap_or(u64::MAX))
+        });
+        ranked
+    }
+
+    /// True if the node is unhealthy and another candidate exists
+    pub fn should_fail_over(&self, node: usize) -> bool {
+        !self.statuses()[node].is_healthy() && self.ranked().first().is_some_and(|&best| best != node && self.is_candidate(best))
+    }
+
+    /// Records a get_server_info response, from connecting or from a pooled call
+    pub fn record_server_info(&self, node: usize, server_info: &GetServerInfoResponse, latency: Duration) {
+        let mut n = self.nodes[node].lock().unwrap();
+        n.status.connected = true;
+        n.status.server_version = Some(server_info.server_version.clone());
+        n.status.virtual_daa_score = Some(server_info.virtual_daa_score);
+        if server_info.network_id != self.network_id {
+            n.status.synced = false;
+            n.status.last_error =
+                Some(format!("Network mismatch, expected '{}', actual '{}'", self.network_id, server_info.network_id));
+            n.record(None);
+        } else {
+            n.status.synced = is_synced(server_info);
+            n.record(Some(latency));
+        }
+    }
+
+    /// Records the result of a pooled call
+    pub fn record_call<T>(&self, node: usize, result: &RpcResult<T>, latency: Duration) {
+        let mut n = self.nodes[node].lock().unwrap();
+        match result {
+            Ok(_) => {
+                n.status.connected = true;
+                n.record(Some(latency));
+            }
+            Err(e) => {
+                n.status.last_error = Some(e.to_string());
+                n.record(None);
+            }
+        }
+    }
+
+    /// Records a failed connection attempt or a lost connection
+    pub fn record_error(&self, node: usize, error: String) {
+        let mut n = self.nodes[node].lock().unwrap();
+        n.status.connected = false;
+        n.status.last_error = Some(error);
+        n.record(None);
+    }
+}
+
+/// Records the latency and result of every call in the node statuses, wraps the pooled connections
+pub struct MonitoredClient {
+    node: usize,
+    nodes: Arc<KaspadNodes>,
+    inner: Arc<dyn KaspadApi>,
+}
+
+impl MonitoredClient {
+    pub fn new(node: usize, nodes: Arc<KaspadNodes>, inner: Arc<dyn KaspadApi>) -> MonitoredClient {
+        MonitoredClient { node, nodes, inner }
+    }
+
+    fn record<T>(&self, start_time: Instant, result: RpcResult<T>) -> RpcResult<T> {
+        self.nodes.record_call(self.node, &result, start_time.elapsed());
+        result
+    }
+}
+
+#[async_trait]
+impl KaspadApi for MonitoredClient {
+    async fn get_server_info(&self) -> RpcResult<GetServerInfoResponse> {
+        let start_time = Instant::now();
+        let result = self.inner.get_server_info().await;
+        match &result {
+            Ok(server_info) => self.nodes.record_server_info(self.node, server_info, start_time.elapsed()),
+            Err(_) => self.nodes.record_call(self.node, &result, start_time.elapsed()),
+        }
+        result
+    }
+
+    async fn get_sync_status(&self) -> RpcResult<bool> {
+        let start_time = Instant::now();
+        self.record(start_time, self.inner.get_sync_status().await)
+    }
+
+    async fn get_block_dag_info(&self) -> RpcResult<GetBlockDagInfoResponse> {
+        let start_time = Instant::now();
+        self.record(start_time, self.inner.get_block_dag_info().await)
+    }
+
+    async fn get_block(&self, hash: RpcHash, include_transactions: bool) -> RpcResult<RpcBlock> {
+        let start_time = Instant::now();
+        self.record(start_time, self.inner.get_block(hash, include_transactions).await)
+    }
+
+    async fn get_blocks(
+        &self,
+        low_hash: Option<RpcHash>,
+        include_blocks: bool,
+        include_transactions: bool,
+    ) -> RpcResult<GetBlocksResponse> {
+        let start_time = Instant::now();
+        self.record(start_time, self.inner.get_blocks(low_hash, include_blocks, include_transactions).await)
+    }
+
+    async fn get_virtual_chain_from_block(
+        &self,
+        start_hash: RpcHash,
+        include_accepted_transaction_ids: bool,
+    ) -> RpcResult<GetVirtualChainFromBlockResponse> {
+        let start_time = Instant::now();
+        self.record(start_time, self.inner.get_virtual_chain_from_block(start_hash, include_accepted_transaction_ids).await)
+    }
+
+    async fn get_mempool_entries(&self, include_orphan_pool: bool, filter_transaction_pool: bool) -> RpcResult<Vec<RpcMempoolEntry>> {
+        let start_time = Instant::now();
+        self.record(start_time, self.inner.get_mempool_entries(include_orphan_pool, filter_transaction_pool).await)
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+}
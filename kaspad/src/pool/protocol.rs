@@ -0,0 +1,63 @@
+use kaspa_wrpc_client::WrpcEncoding;
+use std::fmt;
+use std::str::FromStr;
+
+/// The protocol used for connecting to kaspad
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KaspadProtocol {
+    WrpcBorsh,
+    WrpcJson,
+    Grpc,
+}
+
+impl KaspadProtocol {
+    pub fn wrpc_encoding(&self) -> Option<WrpcEncoding> {
+        match self {
+            KaspadProtocol::WrpcBorsh => Some(WrpcEncoding::Borsh),
+            KaspadProtocol::WrpcJson => Some(WrpcEncoding::SerdeJson),
+            KaspadProtocol::Grpc => None,
+        }
+    }
+
+    /// Splits the protocol from the url, the scheme takes precedence over the supplied default:
+    /// grpc:// selects gRPC, borsh+ws(s):// and json+ws(s):// select the wRPC encoding
+    pub fn from_url(url: &str, default: KaspadProtocol) -> (KaspadProtocol, String) {
+        if let Some(rest) = url.strip_prefix("borsh+") {
+            (KaspadProtocol::WrpcBorsh, rest.to_string())
+        } else if let Some(rest) = url.strip_prefix("json+") {
+            (KaspadProtocol::WrpcJson, rest.to_string())
+        } else if url.starts_with("grpc://") {
+            (KaspadProtocol::Grpc, url.to_string())
+        } else if default == KaspadProtocol::Grpc && !url.contains("://") {
+            (KaspadProtocol::Grpc, format!("grpc://{url}"))
+        } else if default == KaspadProtocol::Grpc && (url.starts_with("ws://") || url.starts_with("wss://")) {
+            (KaspadProtocol::WrpcBorsh, url.to_string())
+        } else {
+            (default, url.to_string())
+        }
+    }
+}
+
+impl FromStr for KaspadProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "borsh" => Ok(KaspadProtocol::WrpcBorsh),
+            "json" => Ok(KaspadProtocol::WrpcJson),
+            "grpc" => Ok(KaspadProtocol::Grpc),
+            _ => Err(format!("Invalid kaspad protocol '{s}', expected borsh, json or grpc")),
+        }
+    }
+}
+
+impl fmt::Display for KaspadProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let protocol_str = match self {
+            KaspadProtocol::WrpcBorsh => "borsh",
+            KaspadProtocol::WrpcJson => "json",
+            KaspadProtocol::Grpc => "grpc",
+        };
+        write!(f, "{}", protocol_str)
+    }
+}
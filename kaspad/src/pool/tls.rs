@@ -0,0 +1,148 @@
+use log::{debug, warn};
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, RootCertStore};
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio_rustls::TlsConnector;
+use url::Url;
+
+/// Upper limit of the HTTP upgrade request sent by the wRPC client
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+
+/// Loads the CA certificate(s) of a PEM file into a rustls client config, no other roots are trusted
+pub fn load_tls_config(ca_file: &str) -> Result<Arc<ClientConfig>, String> {
+    let file = std::fs::File::open(ca_file).map_err(|e| format!("Unable to open CA file {ca_file}: {e}"))?;
+    let mut roots = RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut BufReader::new(file)) {
+        let cert = cert.map_err(|e| format!("Unable to read CA file {ca_file}: {e}"))?;
+        roots.add(cert).map_err(|e| format!("Invalid CA certificate in {ca_file}: {e}"))?;
+    }
+    if roots.is_empty() {
+        return Err(format!("No CA certificates found in {ca_file}"));
+    }
+    let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Unable to create TLS config: {e}"))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+/// Local plain-text endpoints for wss:// nodes, TLS is terminated here using the configured CA certificates.
+/// The wRPC client only trusts its bundled webpki roots and offers no way to pass a TLS connector
+pub struct TlsTunnels {
+    config: Arc<ClientConfig>,
+    tunnels: Mutex<HashMap<String, String>>,
+}
+
+impl TlsTunnels {
+    pub fn new(config: Arc<ClientConfig>) -> TlsTunnels {
+        TlsTunnels { config, tunnels: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the ws:// url of the tunnel to a wss:// url (started on first use), other urls are returned unchanged
+    pub async fn local_url(&self, rpc_url: &str) -> Result<String, String> {
+        let mut url = Url::parse(rpc_url).map_err(|e| format!("Invalid url {rpc_url}: {e}"))?;
+        if url.scheme() != "wss" {
+            return Ok(rpc_url.to_string());
+        }
+        let host = url.host_str().ok_or_else(|| format!("Missing host in {rpc_url}"))?.to_string();
+        let port = url.port_or_known_default().unwrap_or(443);
+        let authority = match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.clone(),
+        };
+        let mut tunnels = self.tunnels.lock().await;
+        let local_authority = match tunnels.get(&authority) {
+            Some(local_authority) => local_authority.clone(),
+            None => {
+                let server_name = ServerName::try_from(host.trim_start_matches('[').trim_end_matches(']').to_string())
+                    .map_err(|e| format!("Invalid host in {rpc_url}: {e}"))?;
+                let listener = TcpListener::bind("127.0.0.1:0").await.map_err(|e| format!("Unable to start TLS tunnel: {e}"))?;
+                let local_authority = listener.local_addr().map_err(|e| format!("Unable to start TLS tunnel: {e}"))?.to_string();
+                debug!("Tunneling {local_authority} to {authority} over TLS");
+                let tunnel = Tunnel {
+                    connector: TlsConnector::from(self.config.clone()),
+                    server_name,
+                    host,
+                    port,
+                    authority: authority.clone(),
+                };
+                tokio::spawn(tunnel.run(listener));
+                tunnels.insert(authority, local_authority.clone());
+                local_authority
+            }
+        };
+        url.set_scheme("ws").map_err(|_| format!("Unable to rewrite {rpc_url}"))?;
+        let local = Url::parse(&format!("ws://{local_authority}")).map_err(|e| e.to_string())?;
+        url.set_host(local.host_str()).map_err(|e| e.to_string())?;
+        url.set_port(local.port()).map_err(|_| format!("Unable to rewrite {rpc_url}"))?;
+        Ok(url.to_string())
+    }
+}
+
+struct Tunnel {
+    connector: TlsConnector,
+    server_name: ServerName<'static>,
+    host: String,
+    port: u16,
+    authority: String,
+}
+
+impl Tunnel {
+    async fn run(self, listener: TcpListener) {
+        let tunnel = Arc::new(self);
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let tunnel = tunnel.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = tunnel.forward(stream).await {
+                            warn!("TLS connection to {} failed: {e}", tunnel.authority);
+                        }
+                    });
+                }
+                Err(e) => warn!("TLS tunnel to {} failed to accept: {e}", tunnel.authority),
+            }
+        }
+    }
+
+    async fn forward(&self, mut local: TcpStream) -> std::io::Result<()> {
+        let head = read_request_head(&mut local).await?;
+        let head = rewrite_host(&head, &self.authority);
+        let remote = TcpStream::connect((self.host.trim_start_matches('[').trim_end_matches(']'), self.port)).await?;
+        let mut remote = self.connector.connect(self.server_name.clone(), remote).await?;
+        remote.write_all(&head).await?;
+        tokio::io::copy_bidirectional(&mut local, &mut remote).await?;
+        Ok(())
+    }
+}
+
+/// Reads the HTTP request head, including the terminating empty line
+async fn read_request_head(stream: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= MAX_REQUEST_HEAD {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Request head too large"));
+        }
+        if stream.read(&mut byte).await? == 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Connection closed before request head"));
+        }
+        head.push(byte[0]);
+    }
+    Ok(head)
+}
+
+/// Replaces the Host header (pointing at the tunnel) with the authority of the node
+fn rewrite_host(head: &[u8], authority: &str) -> Vec<u8> {
+    String::from_utf8_lossy(head)
+        .split_inclusive("\r\n")
+        .map(|line| if line.to_ascii_lowercase().starts_with("host:") { format!("Host: {authority}\r\n") } else { line.to_string() })
+        .collect::<String>()
+        .into_bytes()
+}
@@ -0,0 +1,34 @@
+use kaspa_rpc_core::{RpcError, RpcResult};
+use kaspa_wrpc_client::prelude::NetworkId;
+use simply_kaspa_kaspad::pool::nodes::KaspadNodes;
+use simply_kaspa_kaspad::pool::protocol::KaspadProtocol;
+use std::str::FromStr;
+use std::time::Duration;
+
+fn nodes() -> KaspadNodes {
+    let urls = vec!["ws://node-a:17110".to_string(), "ws://node-b:17110".to_string()];
+    KaspadNodes::new(NetworkId::from_str("mainnet").unwrap(), Some(urls), KaspadProtocol::WrpcBorsh)
+}
+
+#[test]
+fn records_latency_and_errors_of_calls() {
+    let nodes = nodes();
+    for millis in [30, 10, 20] {
+        nodes.record_call(0, &RpcResult::Ok(()), Duration::from_millis(millis));
+    }
+    nodes.record_call::<()>(0, &Err(RpcError::General("timeout".to_string())), Duration::from_secs(5));
+    let status = &nodes.statuses()[0];
+    assert_eq!(status.latency_ms, Some(20), "Latency is not the median of the successful calls");
+    assert!((status.error_rate - 0.25).abs() < 1e-9, "Unexpected error rate {}", status.error_rate);
+    assert!(status.last_error.as_ref().unwrap().contains("timeout"));
+}
+
+#[test]
+fn ranks_nodes_by_call_results() {
+    let nodes = nodes();
+    for _ in 0..3 {
+        nodes.record_call::<()>(0, &Err(RpcError::General("failed".to_string())), Duration::from_millis(10));
+        nodes.record_call(1, &RpcResult::Ok(()), Duration::from_millis(50));
+    }
+    assert_eq!(nodes.ranked(), vec![1, 0]);
+}
@@ -0,0 +1,50 @@
+use kaspa_consensus_core::config::params::MAINNET_PARAMS;
+use kaspa_wrpc_client::prelude::NetworkId;
+use simply_kaspa_kaspad::network::{NetworkFork, NetworkParams};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DAY_MS: u64 = 86_400_000;
+
+fn network(network: &str) -> NetworkParams {
+    NetworkParams::new(NetworkId::from_str(network).unwrap())
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+#[test]
+fn requires_passed_forks_for_sync() {
+    let crescendo = MAINNET_PARAMS.crescendo_activation.daa_score();
+    assert_eq!(network("mainnet").min_synced_daa_score(now()), crescendo);
+    assert_eq!(
+        network("mainnet").min_synced_daa_score(MAINNET_PARAMS.genesis.timestamp + DAY_MS),
+        0,
+        "Crescendo counted before it activated"
+    );
+    assert_eq!(network("testnet-11").min_synced_daa_score(now()), 0, "Networks without consensus params have no minimum");
+}
+
+#[test]
+fn ignores_scheduled_forks_until_activated() {
+    let mut mainnet = network("mainnet");
+    let crescendo = mainnet.min_synced_daa_score(now());
+    // A fork scheduled a year ahead, as an upgraded kaspa_consensus_core would add it
+    let activation_daa_score = mainnet.estimated_daa_score(now() + 365 * DAY_MS);
+    mainnet.forks.push(NetworkFork { name: "scheduled".to_string(), activation_daa_score, bps: 20 });
+    assert_eq!(mainnet.min_synced_daa_score(now()), crescendo);
+    assert_eq!(mainnet.min_synced_daa_score(now() + 364 * DAY_MS), crescendo);
+    assert_eq!(mainnet.min_synced_daa_score(now() + 367 * DAY_MS), activation_daa_score);
+}
+
+#[test]
+fn estimates_daa_score_across_forks() {
+    let mainnet = network("mainnet");
+    let crescendo = mainnet.forks.last().unwrap().activation_daa_score;
+    assert_eq!(mainnet.estimated_daa_score(mainnet.genesis_timestamp), mainnet.genesis_daa_score);
+    let before = mainnet.estimated_daa_score(now());
+    let after = mainnet.estimated_daa_score(now() + 1000);
+    assert!(before > crescendo);
+    assert_eq!(after - before, mainnet.fork(before).bps as u64, "The block rate of the active fork was not used");
+}
@@ -0,0 +1,139 @@
+use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa, KeyPair};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::ServerConfig;
+use simply_kaspa_kaspad::pool::tls::{load_tls_config, TlsTunnels};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
+use url::Url;
+
+/// Writes a file unique to the test, removed when dropped
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, content: &str) -> TempFile {
+        let path = std::env::temp_dir().join(format!("ski-{}-{name}", std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        TempFile(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+fn private_ca(name: &str) -> (Certificate, KeyPair) {
+    let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.distinguished_name.push(DnType::CommonName, name);
+    let key = KeyPair::generate().unwrap();
+    (params.self_signed(&key).unwrap(), key)
+}
+
+/// A TLS server for 'localhost' signed by the CA, answering the request head with the Host header received and echoing the rest
+async fn start_server(ca: &Certificate, ca_key: &KeyPair) -> u16 {
+    let key = KeyPair::generate().unwrap();
+    let cert = CertificateParams::new(vec!["localhost".to_string()]).unwrap().signed_by(&key, ca, ca_key).unwrap();
+    let config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![CertificateDer::from(cert.der().to_vec())],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key.serialize_der())),
+        )
+        .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+            tokio::spawn(async move {
+                let Ok(mut stream) = acceptor.accept(stream).await else { return };
+                let mut head = Vec::new();
+                let mut byte = [0u8; 1];
+                while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).await.unwrap() > 0 {
+                    head.push(byte[0]);
+                }
+                let host = String::from_utf8(head).unwrap().lines().find(|l| l.starts_with("Host:")).unwrap().to_string();
+                stream.write_all(format!("{host}\r\n").as_bytes()).await.unwrap();
+                let mut buf = [0u8; 64];
+                let n = stream.read(&mut buf).await.unwrap();
+                stream.write_all(&buf[..n]).await.unwrap();
+                stream.flush().await.unwrap();
+            });
+        }
+    });
+    port
+}
+
+/// Sends an upgrade request through the local url, returns what came back
+async fn request(local_url: &str) -> String {
+    let url = Url::parse(local_url).unwrap();
+    let authority = format!("{}:{}", url.host_str().unwrap(), url.port().unwrap());
+    let mut stream = TcpStream::connect(&authority).await.unwrap();
+    let head = format!("GET {} HTTP/1.1\r\nHost: {authority}\r\nUpgrade: websocket\r\n\r\nping", url.path());
+    stream.write_all(head.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    let _ = stream.read_to_end(&mut response).await;
+    String::from_utf8_lossy(&response).to_string()
+}
+
+#[tokio::test]
+async fn connects_to_node_signed_by_private_ca() {
+    let (ca, ca_key) = private_ca("Private kaspad CA");
+    let port = start_server(&ca, &ca_key).await;
+    let ca_file = TempFile::new("ca.pem", &ca.pem());
+    let tunnels = TlsTunnels::new(load_tls_config(ca_file.path()).unwrap());
+
+    let rpc_url = format!("wss://localhost:{port}/kaspa/testnet-10/wrpc/borsh");
+    let local_url = tunnels.local_url(&rpc_url).await.unwrap();
+    assert!(local_url.starts_with("ws://127.0.0.1:"));
+    assert!(local_url.ends_with("/kaspa/testnet-10/wrpc/borsh"));
+    // The tunnel is reused for the same node
+    assert_eq!(tunnels.local_url(&rpc_url).await.unwrap(), local_url);
+
+    let response = request(&local_url).await;
+    assert_eq!(response, format!("Host: localhost:{port}\r\nping"));
+}
+
+#[tokio::test]
+async fn rejects_node_signed_by_other_ca() {
+    let (ca, ca_key) = private_ca("Private kaspad CA");
+    let port = start_server(&ca, &ca_key).await;
+    let (other_ca, _) = private_ca("Other CA");
+    let ca_file = TempFile::new("other-ca.pem", &other_ca.pem());
+    let tunnels = TlsTunnels::new(load_tls_config(ca_file.path()).unwrap());
+
+    let local_url = tunnels.local_url(&format!("wss://localhost:{port}")).await.unwrap();
+    // The handshake fails, the tunnel closes the connection without a response
+    assert_eq!(request(&local_url).await, "");
+}
+
+#[tokio::test]
+async fn keeps_other_urls() {
+    let (ca, _) = private_ca("Private kaspad CA");
+    let ca_file = TempFile::new("keep-ca.pem", &ca.pem());
+    let tunnels = TlsTunnels::new(load_tls_config(ca_file.path()).unwrap());
+    assert_eq!(tunnels.local_url("ws://localhost:17210").await.unwrap(), "ws://localhost:17210");
+    assert_eq!(tunnels.local_url("grpc://localhost:16210").await.unwrap(), "grpc://localhost:16210");
+}
+
+#[test]
+fn rejects_invalid_ca_files() {
+    assert!(load_tls_config("/nonexistent/ca.pem").unwrap_err().contains("Unable to open"));
+    let empty = TempFile::new("empty-ca.pem", "no certificates here\n");
+    assert!(load_tls_config(empty.path()).unwrap_err().contains("No CA certificates"));
+    let invalid = TempFile::new("invalid-ca.pem", "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n");
+    assert!(load_tls_config(invalid.path()).is_err());
+}
@@ -0,0 +1,72 @@
+use bigdecimal::ToPrimitive;
+use kaspa_addresses::Prefix;
+use kaspa_consensus_core::subnets::SUBNETWORK_ID_COINBASE;
+use kaspa_consensus_core::tx::ScriptPublicKey;
+use kaspa_rpc_core::RpcTransaction;
+use kaspa_txscript::extract_script_pub_key_address;
+use log::warn;
+
+use simply_kaspa_database::models::block_reward::BlockReward as SqlBlockReward;
+
+const BLUE_SCORE_LEN: usize = 8;
+const SUBSIDY_LEN: usize = 8;
+const SCRIPT_VERSION_LEN: usize = 2;
+const SCRIPT_LENGTH_LEN: usize = 1;
+const MIN_PAYLOAD_LEN: usize = BLUE_SCORE_LEN + SUBSIDY_LEN + SCRIPT_VERSION_LEN + SCRIPT_LENGTH_LEN;
+
+/// Decoded coinbase payload: blue_score (u64 le), subsidy (u64 le), spk version (u16 le), spk length (u8), spk, extra data
+pub struct CoinbasePayload {
+    pub blue_score: u64,
+    pub subsidy: u64,
+    pub script_public_key: ScriptPublicKey,
+    pub extra_data: Vec<u8>,
+}
+
+pub fn decode_coinbase_payload(payload: &[u8]) -> Option<CoinbasePayload> {
+    if payload.len() < MIN_PAYLOAD_LEN {
+        return None;
+    }
+    let (blue_score, rest) = payload.split_at(BLUE_SCORE_LEN);
+    let (subsidy, rest) = rest.split_at(SUBSIDY_LEN);
+    let (script_version, rest) = rest.split_at(SCRIPT_VERSION_LEN);
+    let (script_length, rest) = rest.split_at(SCRIPT_LENGTH_LEN);
+    let script_length = script_length[0] as usize;
+    if rest.len() < script_length {
+        return None;
+    }
+    let (script, extra_data) = rest.split_at(script_length);
+    Some(CoinbasePayload {
+        blue_score: u64::from_le_bytes(blue_score.try_into().ok()?),
+        subsidy: u64::from_le_bytes(subsidy.try_into().ok()?),
+        script_public_key: ScriptPublicKey::from_vec(u16::from_le_bytes(script_version.try_into().ok()?), script.to_vec()),
+        extra_data: extra_data.to_vec(),
+    })
+}
+
+pub fn map_block_reward(transaction: &RpcTransaction, prefix: Prefix) -> Option<SqlBlockReward> {
+    if transaction.subnetwork_id != SUBNETWORK_ID_COINBASE {
+        return None;
+    }
+    let verbose_data = transaction.verbose_data.as_ref().expect("Transaction verbose_data is missing");
+    let Some(coinbase_payload) = decode_coinbase_payload(&transaction.payload) else {
+        warn!("Unable to decode coinbase payload for transaction {}", verbose_data.transaction_id);
+        return None;
+    };
+    let coinbase_amount = transaction.outputs.iter().try_fold(0u64, |sum, output| sum.checked_add(output.value));
+    let (Some(blue_score), Some(subsidy), Some(coinbase_amount)) =
+        (coinbase_payload.blue_score.to_i64(), coinbase_payload.subsidy.to_i64(), coinbase_amount.and_then(|a| a.to_i64()))
+    else {
+        warn!("Coinbase amounts out of range for transaction {}", verbose_data.transaction_id);
+        return None;
+    };
+    Some(SqlBlockReward {
+        block_hash: verbose_data.block_hash.into(),
+        blue_score,
+        subsidy,
+        coinbase_amount,
+        miner_address: extract_script_pub_key_address(&coinbase_payload.script_public_key, prefix).ok().map(|a| a.payload_to_string()),
+        miner_script_public_key: coinbase_payload.script_public_key.script().to_vec(),
+        extra_data: (!coinbase_payload.extra_data.is_empty()).then_some(coinbase_payload.extra_data),
+        block_time: verbose_data.block_time.to_i64().unwrap(),
+    })
+}
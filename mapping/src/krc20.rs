@@ -0,0 +1,131 @@
+use bigdecimal::ToPrimitive;
+use kaspa_rpc_core::RpcTransaction;
+use log::trace;
+use serde::Deserialize;
+
+use simply_kaspa_database::models::krc20_operation::Krc20Operation as SqlKrc20Operation;
+
+const OP_FALSE: u8 = 0x00;
+const OP_DATA_1: u8 = 0x01;
+const OP_DATA_75: u8 = 0x4b;
+const OP_PUSH_DATA_1: u8 = 0x4c;
+const OP_PUSH_DATA_2: u8 = 0x4d;
+const OP_PUSH_DATA_4: u8 = 0x4e;
+const OP_IF: u8 = 0x63;
+const OP_ENDIF: u8 = 0x68;
+
+const PROTOCOL_MARKER: &[u8] = b"kasplex";
+const PROTOCOL: &str = "krc-20";
+const OPERATIONS: [&str; 3] = ["deploy", "mint", "transfer"];
+
+enum ScriptOp<'a> {
+    Push(&'a [u8]),
+    Code(u8),
+}
+
+/// Kasplex inscription content, all values are strings in the protocol
+#[derive(Deserialize)]
+pub struct Krc20Envelope {
+    pub p: String,
+    pub op: String,
+    pub tick: Option<String>,
+    pub amt: Option<String>,
+    pub max: Option<String>,
+    pub lim: Option<String>,
+    pub dec: Option<String>,
+    pub to: Option<String>,
+    #[serde(skip)]
+    pub content: String,
+}
+
+/// Splits a script into data pushes and opcodes, returns None for malformed scripts
+fn parse_script(script: &[u8]) -> Option<Vec<ScriptOp<'_>>> {
+    let mut ops = vec![];
+    let mut i = 0;
+    while i < script.len() {
+        let opcode = script[i];
+        i += 1;
+        let length = match opcode {
+            OP_FALSE => 0,
+            OP_DATA_1..=OP_DATA_75 => opcode as usize,
+            OP_PUSH_DATA_1 => {
+                let length = *script.get(i)? as usize;
+                i += 1;
+                length
+            }
+            OP_PUSH_DATA_2 => {
+                let length = u16::from_le_bytes(script.get(i..i.checked_add(2)?)?.try_into().ok()?) as usize;
+                i += 2;
+                length
+            }
+            OP_PUSH_DATA_4 => {
+                let length = u32::from_le_bytes(script.get(i..i.checked_add(4)?)?.try_into().ok()?) as usize;
+                i += 4;
+                length
+            }
+            _ => {
+                ops.push(ScriptOp::Code(opcode));
+                continue;
+            }
+        };
+        ops.push(ScriptOp::Push(script.get(i..i.checked_add(length)?)?));
+        i += length;
+    }
+    Some(ops)
+}
+
+/// Extracts the content of a kasplex envelope (OP_FALSE OP_IF "kasplex" ... OP_0 <content> OP_ENDIF)
+/// from the redeem script revealed by a P2SH signature script
+pub fn decode_envelope(signature_script: &[u8]) -> Option<Krc20Envelope> {
+    let Some(ScriptOp::Push(redeem_script)) = parse_script(signature_script)?.pop() else {
+        return None;
+    };
+    let ops = parse_script(redeem_script)?;
+    let marker = ops.windows(3).position(
+        |w| matches!(w, [ScriptOp::Push([]), ScriptOp::Code(OP_IF), ScriptOp::Push(marker)] if *marker == PROTOCOL_MARKER),
+    )?;
+    let mut content = None;
+    let mut after_separator = false;
+    for op in &ops[marker + 3..] {
+        match op {
+            ScriptOp::Push([]) => after_separator = true,
+            ScriptOp::Push(data) if after_separator => {
+                content = Some(*data);
+                after_separator = false;
+            }
+            ScriptOp::Push(_) => {}
+            ScriptOp::Code(OP_ENDIF) => break,
+            ScriptOp::Code(_) => {}
+        }
+    }
+    let content = content?;
+    let mut envelope: Krc20Envelope = serde_json::from_slice(content).ok()?;
+    envelope.content = String::from_utf8_lossy(content).into_owned();
+    (envelope.p.eq_ignore_ascii_case(PROTOCOL) && OPERATIONS.contains(&envelope.op.to_lowercase().as_str())).then_some(envelope)
+}
+
+pub fn map_krc20_operations(transaction: &RpcTransaction) -> Vec<SqlKrc20Operation> {
+    let verbose_data = transaction.verbose_data.as_ref().expect("Transaction verbose_data is missing");
+    transaction
+        .inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, input)| {
+            let envelope = decode_envelope(&input.signature_script)?;
+            trace!("Decoded KRC-20 {} in transaction {}", envelope.op, verbose_data.transaction_id);
+            Some(SqlKrc20Operation {
+                transaction_id: verbose_data.transaction_id.into(),
+                index: i.to_i16().unwrap(),
+                operation: envelope.op.to_lowercase(),
+                tick: envelope.tick.map(|t| t.to_uppercase()),
+                amount: envelope.amt,
+                max_supply: envelope.max,
+                mint_limit: envelope.lim,
+                decimals: envelope.dec.and_then(|d| d.parse().ok()),
+                to_address: envelope.to,
+                content: envelope.content,
+                block_time: verbose_data.block_time.to_i64().unwrap(),
+            })
+        })
+        .collect()
+}
@@ -1,3 +1,6 @@
+pub mod block_rewards;
 mod blocks;
+pub mod krc20;
 pub mod mapper;
+mod mempool;
 mod transactions;
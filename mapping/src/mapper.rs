@@ -1,19 +1,28 @@
-use kaspa_rpc_core::{RpcBlock, RpcTransaction};
+use kaspa_addresses::Prefix;
+use kaspa_consensus_core::network::NetworkId;
+use kaspa_rpc_core::{RpcBlock, RpcMempoolEntry, RpcTransaction};
 use simply_kaspa_cli::cli_args::{CliArgs, CliField};
 use simply_kaspa_database::models::address_transaction::AddressTransaction as SqlAddressTransaction;
 use simply_kaspa_database::models::block::Block as SqlBlock;
 use simply_kaspa_database::models::block_parent::BlockParent as SqlBlockParent;
+use simply_kaspa_database::models::block_reward::BlockReward as SqlBlockReward;
 use simply_kaspa_database::models::block_transaction::BlockTransaction as SqlBlockTransaction;
+use simply_kaspa_database::models::krc20_operation::Krc20Operation as SqlKrc20Operation;
+use simply_kaspa_database::models::mempool_input::MempoolInput as SqlMempoolInput;
+use simply_kaspa_database::models::mempool_output::MempoolOutput as SqlMempoolOutput;
+use simply_kaspa_database::models::mempool_transaction::MempoolTransaction as SqlMempoolTransaction;
 use simply_kaspa_database::models::script_transaction::ScriptTransaction as SqlScriptTransaction;
 use simply_kaspa_database::models::transaction::Transaction as SqlTransaction;
 use simply_kaspa_database::models::transaction_input::TransactionInput as SqlTransactionInput;
 use simply_kaspa_database::models::transaction_output::TransactionOutput as SqlTransactionOutput;
 use simply_kaspa_database::models::types::hash::Hash as SqlHash;
+use std::str::FromStr;
 
-use crate::{blocks, transactions};
+use crate::{block_rewards, blocks, krc20, mempool, transactions};
 
 #[derive(Clone)]
 pub struct KaspaDbMapper {
+    prefix: Prefix,
     block_accepted_id_merkle_root: bool,
     block_merge_set_blues_hashes: bool,
     block_merge_set_reds_hashes: bool,
@@ -45,7 +54,9 @@ pub struct KaspaDbMapper {
 
 impl KaspaDbMapper {
     pub fn new(cli_args: CliArgs) -> KaspaDbMapper {
+        let network_id = NetworkId::from_str(&cli_args.network).expect("Invalid network");
         KaspaDbMapper {
+            prefix: Prefix::from(network_id),
             block_accepted_id_merkle_root: !cli_args.is_excluded(CliField::BlockAcceptedIdMerkleRoot),
             block_merge_set_blues_hashes: !cli_args.is_excluded(CliField::BlockMergeSetBluesHashes),
             block_merge_set_reds_hashes: !cli_args.is_excluded(CliField::BlockMergeSetRedsHashes),
@@ -144,6 +155,14 @@ impl KaspaDbMapper {
         )
     }
 
+    pub fn map_block_reward(&self, transaction: &RpcTransaction) -> Option<SqlBlockReward> {
+        block_rewards::map_block_reward(transaction, self.prefix)
+    }
+
+    pub fn map_krc20_operations(&self, transaction: &RpcTransaction) -> Vec<SqlKrc20Operation> {
+        krc20::map_krc20_operations(transaction)
+    }
+
     pub fn map_transaction_outputs_address(&self, transaction: &RpcTransaction) -> Vec<SqlAddressTransaction> {
         transactions::map_transaction_outputs_address(transaction)
     }
@@ -151,4 +170,24 @@ impl KaspaDbMapper {
     pub fn map_transaction_outputs_script(&self, transaction: &RpcTransaction) -> Vec<SqlScriptTransaction> {
         transactions::map_transaction_outputs_script(transaction)
     }
+
+    pub fn map_mempool_transaction(&self, entry: &RpcMempoolEntry, subnetwork_key: i32, first_seen: i64) -> SqlMempoolTransaction {
+        mempool::map_mempool_transaction(
+            subnetwork_key,
+            entry,
+            first_seen,
+            self.tx_subnetwork_id,
+            self.tx_hash,
+            self.tx_mass,
+            self.tx_payload,
+        )
+    }
+
+    pub fn map_mempool_inputs(&self, entry: &RpcMempoolEntry) -> Vec<SqlMempoolInput> {
+        mempool::map_mempool_inputs(entry, self.tx_in_previous_outpoint, self.tx_in_signature_script, self.tx_in_sig_op_count)
+    }
+
+    pub fn map_mempool_outputs(&self, entry: &RpcMempoolEntry) -> Vec<SqlMempoolOutput> {
+        mempool::map_mempool_outputs(entry, self.tx_out_amount, self.tx_out_script_public_key, self.tx_out_script_public_key_address)
+    }
 }
@@ -0,0 +1,76 @@
+use bigdecimal::ToPrimitive;
+use kaspa_rpc_core::RpcMempoolEntry;
+
+use simply_kaspa_database::models::mempool_input::MempoolInput as SqlMempoolInput;
+use simply_kaspa_database::models::mempool_output::MempoolOutput as SqlMempoolOutput;
+use simply_kaspa_database::models::mempool_transaction::MempoolTransaction as SqlMempoolTransaction;
+
+pub fn map_mempool_transaction(
+    subnetwork_key: i32,
+    entry: &RpcMempoolEntry,
+    first_seen: i64,
+    include_subnetwork_id: bool,
+    include_hash: bool,
+    include_mass: bool,
+    include_payload: bool,
+) -> SqlMempoolTransaction {
+    let transaction = &entry.transaction;
+    let verbose_data = transaction.verbose_data.as_ref().expect("Transaction verbose_data is missing");
+    SqlMempoolTransaction {
+        transaction_id: verbose_data.transaction_id.into(),
+        subnetwork_id: include_subnetwork_id.then_some(subnetwork_key),
+        hash: include_hash.then_some(verbose_data.hash.into()),
+        mass: (include_mass && verbose_data.compute_mass != 0).then_some(verbose_data.compute_mass.to_i32().unwrap()),
+        payload: (include_payload && !transaction.payload.is_empty()).then_some(transaction.payload.to_owned()),
+        fee: entry.fee.to_i64(),
+        is_orphan: entry.is_orphan,
+        first_seen,
+    }
+}
+
+pub fn map_mempool_inputs(
+    entry: &RpcMempoolEntry,
+    include_previous_outpoint: bool,
+    include_signature_script: bool,
+    include_sig_op_count: bool,
+) -> Vec<SqlMempoolInput> {
+    let tx_verbose_data = entry.transaction.verbose_data.as_ref().expect("Transaction verbose_data is missing");
+    entry
+        .transaction
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, input)| SqlMempoolInput {
+            transaction_id: tx_verbose_data.transaction_id.into(),
+            index: i.to_i16().unwrap(),
+            previous_outpoint_hash: include_previous_outpoint.then_some(input.previous_outpoint.transaction_id.into()),
+            previous_outpoint_index: include_previous_outpoint.then_some(input.previous_outpoint.index.to_i16().unwrap()),
+            signature_script: include_signature_script.then_some(input.signature_script.clone()),
+            sig_op_count: include_sig_op_count.then_some(input.sig_op_count as i16),
+        })
+        .collect::<Vec<SqlMempoolInput>>()
+}
+
+pub fn map_mempool_outputs(
+    entry: &RpcMempoolEntry,
+    include_amount: bool,
+    include_script_public_key: bool,
+    include_script_public_key_address: bool,
+) -> Vec<SqlMempoolOutput> {
+    let tx_verbose_data = entry.transaction.verbose_data.as_ref().expect("Transaction verbose_data is missing");
+    entry
+        .transaction
+        .outputs
+        .iter()
+        .enumerate()
+        .map(|(i, output)| SqlMempoolOutput {
+            transaction_id: tx_verbose_data.transaction_id.into(),
+            index: i.to_i16().expect("Tx output index is too large for i16"),
+            amount: include_amount.then_some(output.value.to_i64().expect("Tx output amount is too large for i64")),
+            script_public_key: include_script_public_key.then_some(output.script_public_key.script().to_vec()),
+            script_public_key_address: include_script_public_key_address
+                .then(|| output.verbose_data.as_ref().map(|v| v.script_public_key_address.payload_to_string()))
+                .flatten(),
+        })
+        .collect::<Vec<SqlMempoolOutput>>()
+}
@@ -71,17 +71,16 @@ pub fn map_transaction_outputs(
         .outputs
         .iter()
         .enumerate()
-        .map(|(i, output)| {
-            let verbose_data = output.verbose_data.as_ref().expect("Transaction output verbose_data is missing");
-            SqlTransactionOutput {
-                transaction_id: tx_verbose_data.transaction_id.into(),
-                index: i.to_i16().expect("Tx output index is too large for i16"),
-                amount: include_amount.then_some(output.value.to_i64().expect("Tx output amount is too large for i64")),
-                script_public_key: include_script_public_key.then_some(output.script_public_key.script().to_vec()),
-                script_public_key_address: include_script_public_key_address
-                    .then_some(verbose_data.script_public_key_address.payload_to_string()),
-                block_time: include_block_time.then_some(tx_verbose_data.block_time.to_i64().unwrap()),
-            }
+        .map(|(i, output)| SqlTransactionOutput {
+            transaction_id: tx_verbose_data.transaction_id.into(),
+            index: i.to_i16().expect("Tx output index is too large for i16"),
+            amount: include_amount.then_some(output.value.to_i64().expect("Tx output amount is too large for i64")),
+            script_public_key: include_script_public_key.then_some(output.script_public_key.script().to_vec()),
+            // Outputs downloaded over P2P have no verbose data if their script is non-standard
+            script_public_key_address: include_script_public_key_address
+                .then(|| output.verbose_data.as_ref().map(|v| v.script_public_key_address.payload_to_string()))
+                .flatten(),
+            block_time: include_block_time.then_some(tx_verbose_data.block_time.to_i64().unwrap()),
         })
         .collect::<Vec<SqlTransactionOutput>>()
 }
@@ -91,13 +90,11 @@ pub fn map_transaction_outputs_address(transaction: &RpcTransaction) -> Vec<SqlA
     transaction
         .outputs
         .iter()
-        .map(|output| {
-            let verbose_data = output.verbose_data.as_ref().expect("Transaction output verbose_data is missing");
-            SqlAddressTransaction {
-                address: verbose_data.script_public_key_address.payload_to_string(),
-                transaction_id: tx_verbose_data.transaction_id.into(),
-                block_time: tx_verbose_data.block_time.to_i64().unwrap(),
-            }
+        .filter_map(|output| output.verbose_data.as_ref())
+        .map(|verbose_data| SqlAddressTransaction {
+            address: verbose_data.script_public_key_address.payload_to_string(),
+            transaction_id: tx_verbose_data.transaction_id.into(),
+            block_time: tx_verbose_data.block_time.to_i64().unwrap(),
         })
         .collect::<Vec<SqlAddressTransaction>>()
 }
@@ -0,0 +1,117 @@
+use kaspa_addresses::{Address, Prefix, Version};
+use kaspa_consensus_core::subnets::{SUBNETWORK_ID_COINBASE, SUBNETWORK_ID_NATIVE};
+use kaspa_consensus_core::tx::ScriptPublicKey;
+use kaspa_rpc_core::{RpcHash, RpcTransaction, RpcTransactionOutput, RpcTransactionVerboseData};
+use kaspa_txscript::pay_to_address_script;
+use simply_kaspa_mapping::block_rewards::{decode_coinbase_payload, map_block_reward};
+
+const EXTRA_DATA: &[u8] = b"0.16.1/kaspa-pool";
+
+fn miner_script() -> ScriptPublicKey {
+    pay_to_address_script(&Address::new(Prefix::Mainnet, Version::PubKey, &[7; 32]))
+}
+
+fn payload(blue_score: u64, subsidy: u64, script_public_key: &ScriptPublicKey, extra_data: &[u8]) -> Vec<u8> {
+    let mut payload = vec![];
+    payload.extend(blue_score.to_le_bytes());
+    payload.extend(subsidy.to_le_bytes());
+    payload.extend(script_public_key.version().to_le_bytes());
+    payload.push(script_public_key.script().len() as u8);
+    payload.extend(script_public_key.script());
+    payload.extend(extra_data);
+    payload
+}
+
+fn coinbase(payload: Vec<u8>, outputs: &[u64]) -> RpcTransaction {
+    RpcTransaction {
+        version: 0,
+        inputs: vec![],
+        outputs: outputs
+            .iter()
+            .map(|value| RpcTransactionOutput { value: *value, script_public_key: miner_script(), verbose_data: None })
+            .collect(),
+        lock_time: 0,
+        subnetwork_id: SUBNETWORK_ID_COINBASE,
+        gas: 0,
+        payload,
+        mass: 0,
+        verbose_data: Some(RpcTransactionVerboseData {
+            transaction_id: RpcHash::from_u64_word(1),
+            hash: RpcHash::from_u64_word(1),
+            compute_mass: 0,
+            block_hash: RpcHash::from_u64_word(2),
+            block_time: 1738706345528,
+        }),
+    }
+}
+
+#[test]
+fn decodes_payload_with_extra_data() {
+    let decoded = decode_coinbase_payload(&payload(1000, 50_000_000, &miner_script(), EXTRA_DATA)).unwrap();
+    assert_eq!(decoded.blue_score, 1000);
+    assert_eq!(decoded.subsidy, 50_000_000);
+    assert_eq!(decoded.script_public_key, miner_script());
+    assert_eq!(decoded.extra_data, EXTRA_DATA);
+}
+
+#[test]
+fn decodes_payload_without_extra_data() {
+    let decoded = decode_coinbase_payload(&payload(1000, 50_000_000, &miner_script(), &[])).unwrap();
+    assert_eq!(decoded.script_public_key, miner_script());
+    assert!(decoded.extra_data.is_empty());
+
+    let reward = map_block_reward(&coinbase(payload(1000, 50_000_000, &miner_script(), &[]), &[]), Prefix::Mainnet).unwrap();
+    assert_eq!(reward.extra_data, None);
+}
+
+#[test]
+fn rejects_truncated_payloads() {
+    let payload = payload(1000, 50_000_000, &miner_script(), &[]);
+    // The script length exceeds the remaining payload
+    assert!(decode_coinbase_payload(&payload[..payload.len() - 1]).is_none());
+    // The script length is missing
+    assert!(decode_coinbase_payload(&payload[..18]).is_none());
+    assert!(decode_coinbase_payload(&[]).is_none());
+    assert!(map_block_reward(&coinbase(payload[..payload.len() - 1].to_vec(), &[]), Prefix::Mainnet).is_none());
+}
+
+#[test]
+fn keeps_non_standard_miner_scripts() {
+    let script_public_key = ScriptPublicKey::from_vec(0, vec![0x51]);
+    let reward = map_block_reward(&coinbase(payload(1000, 50_000_000, &script_public_key, EXTRA_DATA), &[]), Prefix::Mainnet).unwrap();
+    assert_eq!(reward.miner_address, None);
+    assert_eq!(reward.miner_script_public_key, vec![0x51]);
+    assert_eq!(reward.extra_data.as_deref(), Some(EXTRA_DATA));
+}
+
+#[test]
+fn maps_subsidy_and_coinbase_amount() {
+    let reward = map_block_reward(
+        &coinbase(payload(1000, 50_000_000, &miner_script(), EXTRA_DATA), &[60_000_000, 45_000_000]),
+        Prefix::Mainnet,
+    )
+    .unwrap();
+    assert_eq!(reward.blue_score, 1000);
+    assert_eq!(reward.subsidy, 50_000_000);
+    assert_eq!(reward.coinbase_amount, 105_000_000);
+    assert_eq!(
+        reward.miner_address.as_deref(),
+        Some(Address::new(Prefix::Mainnet, Version::PubKey, &[7; 32]).payload_to_string().as_str())
+    );
+    assert_eq!(reward.block_time, 1738706345528);
+}
+
+#[test]
+fn skips_out_of_range_amounts() {
+    let transaction = coinbase(payload(1000, u64::MAX, &miner_script(), &[]), &[]);
+    assert!(map_block_reward(&transaction, Prefix::Mainnet).is_none());
+    let transaction = coinbase(payload(1000, 50_000_000, &miner_script(), &[]), &[u64::MAX, 1]);
+    assert!(map_block_reward(&transaction, Prefix::Mainnet).is_none());
+}
+
+#[test]
+fn ignores_non_coinbase_transactions() {
+    let mut transaction = coinbase(payload(1000, 50_000_000, &miner_script(), &[]), &[]);
+    transaction.subnetwork_id = SUBNETWORK_ID_NATIVE;
+    assert!(map_block_reward(&transaction, Prefix::Mainnet).is_none());
+}
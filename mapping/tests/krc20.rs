@@ -0,0 +1,115 @@
+use simply_kaspa_mapping::krc20::decode_envelope;
+
+const SIGNATURE: [u8; 65] = [0x5a; 65];
+const PUBKEY: [u8; 32] = [0x3c; 32];
+
+fn push(data: &[u8]) -> Vec<u8> {
+    let mut script = match data.len() {
+        0 => vec![0x00],
+        1..=75 => vec![data.len() as u8],
+        76..=255 => vec![0x4c, data.len() as u8],
+        _ => [&[0x4d][..], &(data.len() as u16).to_le_bytes()].concat(),
+    };
+    script.extend_from_slice(data);
+    script
+}
+
+/// Kasplex commit/reveal redeem script: <pubkey> OP_CHECKSIG OP_FALSE OP_IF "kasplex" OP_1 "" OP_0 <content> OP_ENDIF
+fn redeem_script(marker: &[u8], content: &[u8]) -> Vec<u8> {
+    [push(&PUBKEY), vec![0xac, 0x00, 0x63], push(marker), vec![0x51], push(&[]), push(&[]), push(content), vec![0x68]].concat()
+}
+
+/// Reveal transaction input: <signature> <redeem script>
+fn signature_script(redeem_script: &[u8]) -> Vec<u8> {
+    [push(&SIGNATURE), push(redeem_script)].concat()
+}
+
+fn reveal(content: &str) -> Vec<u8> {
+    signature_script(&redeem_script(b"kasplex", content.as_bytes()))
+}
+
+#[test]
+fn decodes_deploy() {
+    let content = r#"{"p":"krc-20","op":"deploy","tick":"KASP","max":"2100000000000000","lim":"100000000000","dec":"8"}"#;
+    let envelope = decode_envelope(&reveal(content)).expect("deploy was not decoded");
+    assert_eq!(envelope.op, "deploy");
+    assert_eq!(envelope.tick.as_deref(), Some("KASP"));
+    assert_eq!(envelope.max.as_deref(), Some("2100000000000000"));
+    assert_eq!(envelope.lim.as_deref(), Some("100000000000"));
+    assert_eq!(envelope.dec.as_deref(), Some("8"));
+    assert_eq!(envelope.content, content);
+}
+
+#[test]
+fn decodes_mint() {
+    let envelope = decode_envelope(&reveal(r#"{"p":"KRC-20","op":"MINT","tick":"kasp"}"#)).expect("mint was not decoded");
+    assert_eq!(envelope.op, "MINT");
+    assert_eq!(envelope.tick.as_deref(), Some("kasp"));
+    assert_eq!(envelope.amt, None);
+}
+
+#[test]
+fn decodes_transfer() {
+    let content = r#"{"p":"krc-20","op":"transfer","tick":"KASP","amt":"50000000000","to":"kaspa:qypr7ayn2rmgwe5jrc9q4e6rlmx6vewd9pqyng7sazyxzdj5ax2n5ysk8uf3qk9"}"#;
+    let envelope = decode_envelope(&reveal(content)).expect("transfer was not decoded");
+    assert_eq!(envelope.op, "transfer");
+    assert_eq!(envelope.amt.as_deref(), Some("50000000000"));
+    assert_eq!(envelope.to.as_deref(), Some("kaspa:qypr7ayn2rmgwe5jrc9q4e6rlmx6vewd9pqyng7sazyxzdj5ax2n5ysk8uf3qk9"));
+}
+
+#[test]
+fn decodes_large_redeem_scripts() {
+    // A redeem script above 255 bytes is pushed with OP_PUSHDATA2
+    let content = format!(r#"{{"p":"krc-20","op":"deploy","tick":"KASP","max":"{}"}}"#, "9".repeat(300));
+    let script = reveal(&content);
+    assert_eq!(script[66], 0x4d);
+    assert!(decode_envelope(&script).is_some());
+}
+
+#[test]
+fn decodes_push_data_4() {
+    let redeem_script = redeem_script(b"kasplex", br#"{"p":"krc-20","op":"mint","tick":"KASP"}"#);
+    let script = [push(&SIGNATURE), vec![0x4e], (redeem_script.len() as u32).to_le_bytes().to_vec(), redeem_script].concat();
+    assert!(decode_envelope(&script).is_some());
+}
+
+#[test]
+fn rejects_truncated_pushes() {
+    let script = reveal(r#"{"p":"krc-20","op":"mint","tick":"KASP"}"#);
+    // Missing length byte and short data for OP_PUSHDATA1
+    assert!(decode_envelope(&script[..67]).is_none());
+    assert!(decode_envelope(&script[..script.len() - 1]).is_none());
+    // Short length for OP_PUSHDATA2 and OP_PUSHDATA4
+    assert!(decode_envelope(&[push(&SIGNATURE), vec![0x4d, 0x01]].concat()).is_none());
+    assert!(decode_envelope(&[push(&SIGNATURE), vec![0x4e, 0x01, 0x00, 0x00]].concat()).is_none());
+    // Lengths beyond the script
+    assert!(decode_envelope(&[push(&SIGNATURE), vec![0x4d, 0xff, 0xff, 0x00]].concat()).is_none());
+    assert!(decode_envelope(&[push(&SIGNATURE), vec![0x4e, 0xff, 0xff, 0xff, 0xff, 0x00]].concat()).is_none());
+}
+
+#[test]
+fn rejects_wrong_marker() {
+    let content = br#"{"p":"krc-20","op":"mint","tick":"KASP"}"#;
+    assert!(decode_envelope(&signature_script(&redeem_script(b"kaspley", content))).is_none());
+    assert!(decode_envelope(&signature_script(&redeem_script(b"KASPLEX", content))).is_none());
+}
+
+#[test]
+fn rejects_invalid_content() {
+    assert!(decode_envelope(&reveal("mint KASP")).is_none());
+    assert!(decode_envelope(&reveal(r#"{"p":"krc-20","op":"mint""#)).is_none());
+    assert!(decode_envelope(&reveal(r#"{"op":"mint","tick":"KASP"}"#)).is_none());
+}
+
+#[test]
+fn rejects_other_protocols_and_operations() {
+    assert!(decode_envelope(&reveal(r#"{"p":"brc-20","op":"mint","tick":"KASP"}"#)).is_none());
+    assert!(decode_envelope(&reveal(r#"{"p":"krc-721","op":"mint","tick":"KASP"}"#)).is_none());
+    assert!(decode_envelope(&reveal(r#"{"p":"krc-20","op":"burn","tick":"KASP"}"#)).is_none());
+}
+
+#[test]
+fn ignores_regular_p2pk_inputs() {
+    assert!(decode_envelope(&push(&SIGNATURE)).is_none());
+    assert!(decode_envelope(&[]).is_none());
+}